@@ -1,93 +1,96 @@
-use std::{
-	error::Error,
-	fs::File,
-	io::{self, Read, Write},
-};
+use std::{fs, path::Path};
 
-fn main() {
-	println!("Action: Append/Detail.sh");
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
 
-	// Get the current directory
-	let current_dir = match std::env::current_dir() {
-		Ok(dir) => dir,
-		Err(err) => {
-			eprintln!("Error getting current directory: {}", err);
-			return;
-		},
-	};
-	println!("Current directory: {:?}", current_dir);
+use crate::Fn::Task::RepoContext;
 
-	// Define the JSON content
-	let json_content = r#"
-    {
-        "homepage": "HTTPS://GitHub.Com/CodeEditorLand/Build#readme",
-        "bugs": {
-            "url": "HTTPS://GitHub.Com/CodeEditorLand/Build/issues"
-        },
-        "repository": {
-            "type": "git",
-            "url": "git+HTTPS://github.com/CodeEditorLand/Build.git"
-        },
-        "version": "0.0.1",
-        "license": "SEE LICENSE IN LICENSE",
-        "type": "module",
-        "publisher": "codeeditorland",
-        "private": false,
-        "publishConfig": {
-            "access": "public"
-        },
-        "author": {
-            "name": "🖋️ Source — 👐🏻 Open —",
-            "email": "Source/Open@Editor.Land",
-            "url": "HTTPS://Editor.Land"
-        },
-        "scripts": {
-            "prepublishOnly": "Build 'Source/**/*.ts'"
-        },
-        "devDependencies": {
-            "@playform/build": "0.3.7"
-        }
+// Publishing details every package of the fleet carries, unless `[detail]` is configured.
+const DETAIL:&str = r#"
+{
+    "homepage": "HTTPS://GitHub.Com/CodeEditorLand/Build#readme",
+    "bugs": {
+        "url": "HTTPS://GitHub.Com/CodeEditorLand/Build/issues"
+    },
+    "repository": {
+        "type": "git",
+        "url": "git+HTTPS://github.com/CodeEditorLand/Build.git"
+    },
+    "version": "0.0.1",
+    "license": "SEE LICENSE IN LICENSE",
+    "type": "module",
+    "publisher": "codeeditorland",
+    "private": false,
+    "publishConfig": {
+        "access": "public"
+    },
+    "author": {
+        "name": "🖋️ Source — 👐🏻 Open —",
+        "email": "Source/Open@Editor.Land",
+        "url": "HTTPS://Editor.Land"
+    },
+    "scripts": {
+        "prepublishOnly": "Build 'Source/**/*.ts'"
+    },
+    "devDependencies": {
+        "@playform/build": "0.3.7"
     }
-    "#;
+}
+"#;
+
+// Merges the detail into the package.json in `directory`. Nested objects such as `scripts` are
+// merged key by key, so existing entries survive unless the detail sets them too. Returns whether
+// the file changed.
+pub fn Fn(context:&RepoContext, directory:&Path) -> Result<bool> {
+	context.log("Action: Append/Detail.sh");
 
 	// Open package.json file
-	let package_json_path = current_dir.join("package.json");
-	let mut package_json_file = match File::open(&package_json_path) {
-		Ok(file) => file,
-		Err(err) => {
-			eprintln!("Error opening package.json file: {}", err);
-			return;
-		},
-	};
+	let package_json_path = directory.join("package.json");
 
-	// Read package.json content
-	let mut package_json_content = String::new();
-	if let Err(err) = package_json_file.read_to_string(&mut package_json_content) {
-		eprintln!("Error reading package.json content: {}", err);
-		return;
+	let mut package_json:Value = serde_json::from_str(
+		&fs::read_to_string(&package_json_path)
+			.with_context(|| format!("Failed to read {}", package_json_path.display()))?,
+	)
+	.with_context(|| format!("Failed to parse {}", package_json_path.display()))?;
+
+	if !package_json.is_object() {
+		bail!("{} is not a JSON object", package_json_path.display());
 	}
 
-	// Append JSON content
-	let mut new_content = package_json_content + json_content;
+	let original = package_json.clone();
 
-	// Write new content to a temporary file
-	let package_json_tmp_path = current_dir.join("package.json.tmp");
-	let mut package_json_tmp_file = match File::create(&package_json_tmp_path) {
-		Ok(file) => file,
-		Err(err) => {
-			eprintln!("Error creating package.json.tmp file: {}", err);
-			return;
-		},
+	// Append JSON content
+	let detail = match &context.workspace.config.detail {
+		Some(detail) => detail.clone(),
+		None => serde_json::from_str(DETAIL)?,
 	};
 
-	if let Err(err) = package_json_tmp_file.write_all(new_content.as_bytes()) {
-		eprintln!("Error writing to package.json.tmp file: {}", err);
-		return;
+	merge(&mut package_json, detail);
+
+	if package_json == original {
+		return Ok(false);
 	}
 
-	// Rename temporary file to package.json
-	if let Err(err) = std::fs::rename(&package_json_tmp_path, &package_json_path) {
-		eprintln!("Error renaming package.json.tmp to package.json: {}", err);
-		return;
+	// Write new content through a temporary file
+	context
+		.write(&package_json_path, serde_json::to_string_pretty(&package_json)? + "\n")
+		.context("Error writing package.json")?;
+
+	Ok(true)
+}
+
+fn merge(target:&mut Value, detail:Value) {
+	match (target, detail) {
+		(Value::Object(target), Value::Object(detail)) => {
+			for (key, value) in detail {
+				match target.get_mut(&key) {
+					Some(current) => merge(current, value),
+					None => {
+						target.insert(key, value);
+					},
+				}
+			}
+		},
+		(target, detail) => *target = detail,
 	}
 }
@@ -1,15 +1,26 @@
-use std::fs;
+use std::{fs, path::Path};
 
-use serde_json::{json, Value};
+use anyhow::{Context, Result};
+use serde_json::Value;
 
-fn main() {
+use crate::Fn::Task::RepoContext;
+
+// Strips linting, formatting and test configuration from the package.json in `directory`. Returns
+// whether anything was removed.
+pub fn Fn(context:&RepoContext, directory:&Path) -> Result<bool> {
 	// Read package.json file
-	let package_json_str = fs::read_to_string("package.json").expect("Failed to read package.json");
-	let mut package_json:Value =
-		serde_json::from_str(&package_json_str).expect("Failed to parse package.json");
+	let package_json_path = directory.join("package.json");
+
+	let package_json_str = fs::read_to_string(&package_json_path)
+		.with_context(|| format!("Failed to read {}", package_json_path.display()))?;
+
+	let original:Value = serde_json::from_str(&package_json_str)
+		.with_context(|| format!("Failed to parse {}", package_json_path.display()))?;
+
+	let mut package_json = original.clone();
 
 	// Delete keys from package.json
-	let keys_to_delete = vec![
+	let keys_to_delete = [
 		".eslintConfig",
 		".prettier",
 		".peerDependencies",
@@ -40,7 +51,7 @@ fn main() {
 	}
 
 	// Delete specific dependencies/devDependencies
-	let omit = vec![
+	let omit = [
 		"@babel/eslint-config-internal",
 		"@babel/eslint-parser",
 		"@babel/eslint-plugin-development-internal",
@@ -110,26 +121,104 @@ fn main() {
 	remove_dependencies(&mut package_json, &omit, "dependencies");
 	remove_dependencies(&mut package_json, &omit, "devDependencies");
 
+	if package_json == original {
+		return Ok(false);
+	}
+
 	// Write modified package.json back to file
-	fs::write(
-		"package.json",
-		serde_json::to_string_pretty(&package_json).expect("Failed to serialize package.json"),
-	)
-	.expect("Failed to write modified package.json");
+	context
+		.write(&package_json_path, serde_json::to_string_pretty(&package_json)? + "\n")
+		.context("Failed to write modified package.json")?;
+
+	context.log("Action: Clean/Detail.sh completed successfully.");
 
-	println!("Action: Clean/Detail.sh completed successfully.");
+	Ok(true)
 }
 
+// Removes a jq-style path such as `.scripts."lint:fix"`.
 fn remove_key(value:&mut Value, key:&str) {
-	if let Some(obj) = value.as_object_mut() {
-		obj.remove(key);
+	let mut segment = Vec::new();
+
+	let mut rest = key.trim_start_matches('.');
+
+	while !rest.is_empty() {
+		let (current, next) = match rest.strip_prefix('"') {
+			Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+			None => rest.split_once('.').unwrap_or((rest, "")),
+		};
+
+		segment.push(current);
+
+		rest = next.trim_start_matches('.');
+	}
+
+	let Some((last, parent)) = segment.split_last() else {
+		return;
+	};
+
+	let parent = parent.iter().try_fold(value, |value, segment| value.get_mut(*segment));
+
+	// `remove` would move the last key into the gap, reordering the file
+	if let Some(object) = parent.and_then(Value::as_object_mut) {
+		object.shift_remove(*last);
 	}
 }
 
 fn remove_dependencies(value:&mut Value, omit:&[&str], dependency_type:&str) {
-	if let Some(mut dependencies) = value.get_mut(dependency_type).and_then(Value::as_object_mut) {
+	if let Some(dependencies) = value.get_mut(dependency_type).and_then(Value::as_object_mut) {
 		for dependency in omit {
-			dependencies.remove(*dependency);
+			dependencies.shift_remove(*dependency);
 		}
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use std::fs;
+
+	use crate::Fn::Task::Test::Scratch;
+
+	#[test]
+	fn keeps_the_order_of_the_keys_left() {
+		let scratch = Scratch::new("clean-detail");
+
+		let package = scratch.checkout().join("package.json");
+
+		fs::write(
+			&package,
+			r#"{
+	"name": "name",
+	"eslintConfig": {},
+	"version": "1.0.0",
+	"scripts": { "lint": "eslint", "build": "tsc", "test": "jest", "watch": "tsc -w" },
+	"devDependencies": { "eslint": "9", "typescript": "5", "prettier": "3", "vite": "5" },
+	"engines": { "node": ">=18" },
+	"license": "MIT"
+}
+"#,
+		)
+		.unwrap();
+
+		assert!(super::Fn(&scratch.context("clean detail"), &scratch.checkout()).unwrap());
+
+		assert_eq!(
+			fs::read_to_string(&package).unwrap(),
+			r#"{
+  "name": "name",
+  "version": "1.0.0",
+  "scripts": {
+    "build": "tsc",
+    "watch": "tsc -w"
+  },
+  "devDependencies": {
+    "typescript": "5",
+    "vite": "5"
+  },
+  "license": "MIT"
+}
+"#
+		);
+
+		assert!(!super::Fn(&scratch.context("clean detail"), &scratch.checkout()).unwrap());
+	}
+}
@@ -0,0 +1,23 @@
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+// Directories never searched: dependencies, build output and version control.
+pub const PRUNE:[&str; 6] = ["node_modules", "vendor", "dist", "target", ".git", ".next"];
+
+// Every file under `root` called `name`, ignoring case, outside the `PRUNE` directories. Same as
+// `find . -type d \( -iname node_modules -o … \) -prune -false -o -iname <name> -type f`.
+pub fn Fn(root:&Path, name:&str) -> Vec<PathBuf> {
+	WalkDir::new(root)
+		.sort_by_file_name()
+		.into_iter()
+		.filter_entry(|entry| {
+			!(entry.file_type().is_dir()
+				&& entry.depth() > 0
+				&& PRUNE.iter().any(|prune| entry.file_name().eq_ignore_ascii_case(prune)))
+		})
+		.filter_map(|entry| entry.ok())
+		.filter(|entry| entry.file_type().is_file() && entry.file_name().eq_ignore_ascii_case(name))
+		.map(|entry| entry.into_path())
+		.collect()
+}
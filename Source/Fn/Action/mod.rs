@@ -1 +1,3 @@
-pub mod Maintain;
+pub mod Append;
+pub mod Clean;
+pub mod Find;
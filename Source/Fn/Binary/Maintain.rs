@@ -1,28 +1,564 @@
 #![allow(non_snake_case)]
 
-use clap::{arg, Command};
-use Library::Fn::Cache;
+use std::{
+	fs,
+	path::{Path, PathBuf},
+	time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use clap::{arg, value_parser, ArgAction, ArgMatches, Command};
+use Library::Fn::{
+	Cache,
+	Clone::Repository::CloneRepository,
+	Config,
+	Configure::Repository::ConfigureRepository,
+	Cron,
+	Fork::Organization::ForkOrganization,
+	History,
+	Module::Git::ModuleGit,
+	Rename::Branch::RenameBranch,
+	Report,
+	Rollback::Repository::RollbackRepository,
+	Select::Selector,
+	Sync::{Repository::SyncRepository, Upstream},
+	Task::{Cancel, Journal::Journal, Registry::Registry, Run, Summary, Task},
+	Workspace::{Lock, Workspace},
+};
 
 fn main() {
-	let matches = Command::new("🤸🏽 Maintain.")
+	let matches = command().get_matches();
+
+	if let Err(error) = Cancel::install() {
+		eprintln!("Warning: {:#}", error);
+	}
+
+	match run(&matches) {
+		Ok(0) => {},
+		Ok(code) => std::process::exit(code),
+		Err(error) => {
+			eprintln!("Error: {:#}", error);
+
+			let busy = error.chain().any(|cause| cause.is::<Lock::Busy>());
+
+			std::process::exit(if busy { Lock::BUSY } else { 1 });
+		},
+	}
+}
+
+fn command() -> Command {
+	Command::new("🤸🏽 Maintain.")
 		.version(env!("CARGO_PKG_VERSION"))
 		.author("Nikola Hristov")
-		.about("Build")
-		.arg(arg!(--category <VALUE>).required(true))
-		.arg(arg!(--process <VALUE>).required(true))
-		.get_matches();
+		.about("Maintain")
+		.subcommand_required(true)
+		.arg_required_else_help(true)
+		.after_help(
+			"Exit status is 0 when every repository succeeded, 1 when the run could not start, 2 \
+			 when some repositories failed, 75 when another run holds the workspace and 130 when \
+			 Ctrl-C cancelled the rest of the run.",
+		)
+		.arg(
+			arg!(--workspace <DIR> "Workspace root [default: the nearest directory up holding Maintain.toml]")
+				.value_parser(value_parser!(PathBuf))
+				.global(true),
+		)
+		.arg(
+			arg!(--cache <DIR> "Directory holding Build.toml [default: [workspace] cache]")
+				.value_parser(value_parser!(PathBuf))
+				.global(true),
+		)
+		.arg(
+			arg!(--only <PATTERN> "Only these repositories, globs over owner/name")
+				.action(ArgAction::Append)
+				.global(true),
+		)
+		.arg(
+			arg!(--except <PATTERN> "Leave these repositories out")
+				.action(ArgAction::Append)
+				.global(true),
+		)
+		.arg(arg!(--filter <EXPRESSION> "e.g. 'tag:extension && !archived'").global(true))
+		.arg(
+			arg!(--"dry-run" "Print what every task would change without changing it").global(true),
+		)
+		.arg(
+			arg!(--config <FILE> "Configuration file [default: <workspace>/Maintain.toml]")
+				.value_parser(value_parser!(PathBuf))
+				.global(true),
+		)
+		.arg(
+			arg!(--jobs <N> "Repositories a parallel task works on at once, 0 for one per CPU")
+				.value_parser(value_parser!(usize))
+				.global(true),
+		)
+		.arg(
+			arg!(--summary <FILE> "Summary file, none on a dry run [default: <state>/Summary.json]")
+				.value_parser(value_parser!(PathBuf))
+				.global(true),
+		)
+		.subcommand(
+			Command::new("cache")
+				.about("Repository inventory")
+				.subcommand_required(true)
+				.subcommand(Command::new("get").about("List every forge owner into Build.md"))
+				.subcommand(
+					Command::new("diff")
+						.about("Compare the last two inventory snapshots")
+						.arg(arg!(--json "Print the changes as JSON")),
+				),
+		)
+		.subcommand(
+			Command::new("clone").about("Clone missing repositories").arg(
+				arg!(--depth <N> "History depth, 0 for all of it")
+					.value_parser(value_parser!(u32))
+					.default_value("1"),
+			),
+		)
+		.subcommand(
+			Command::new("sync")
+				.about("Sync forks with their parent")
+				.arg(
+					arg!(--strategy <STRATEGY> "How the fork takes in the parent")
+						.value_parser(Upstream::Strategy::ALL.map(|strategy| strategy.name()))
+						.default_value("merge"),
+				)
+				.arg(arg!(--parent <REMOTE> "Remote of the parent").default_value("upstream"))
+				.arg(arg!(--fork <REMOTE> "Remote of the fork").default_value("origin"))
+				.arg(arg!(--branch <NAME> "Branch of the parent, its default branch unless set"))
+				.arg(
+					arg!(--overlay <PATH> "Path of the fork to keep over the parent")
+						.action(ArgAction::Append)
+						.value_parser(value_parser!(PathBuf)),
+				)
+				.arg(arg!(--push "Push the synced branch to the fork")),
+		)
+		.subcommand(
+			Command::new("rename")
+				.about("Rename repositories or their default branch")
+				.subcommand_required(true)
+				.subcommand(Command::new("repo").about("Rename repositories"))
+				.subcommand(
+					Command::new("branch")
+						.about("Make one branch the default")
+						.arg(arg!(--branch <NAME> "Default branch").default_value("main")),
+				),
+		)
+		.subcommand(
+			Command::new("clean")
+				.about("Remove what the fleet does not use")
+				.subcommand_required(true)
+				.subcommand(Command::new("detail").about("Strip package.json fields")),
+		)
+		.subcommand(
+			Command::new("move")
+				.about("Move files into place")
+				.subcommand_required(true)
+				.subcommand(Command::new("license").about("Rename license files to LICENSE"))
+				.subcommand(Command::new("src").about("Move src into Source"))
+				.subcommand(Command::new("package").about("Move package files")),
+		)
+		.subcommand(
+			Command::new("append")
+				.about("Add what the fleet shares")
+				.subcommand_required(true)
+				.subcommand(Command::new("detail").about("Merge shared package.json fields")),
+		)
+		.subcommand(
+			Command::new("sort")
+				.about("Sort files")
+				.subcommand_required(true)
+				.subcommand(Command::new("detail").about("Sort package.json")),
+		)
+		.subcommand(
+			Command::new("replace")
+				.about("Rewrite sources")
+				.subcommand_required(true)
+				.subcommand(
+					Command::new("copyright")
+						.about("Replace copyright headers following [repository.override] copyright"),
+				)
+				.subcommand(
+					Command::new("import")
+						.about("Rewrite imports following [repository.override.import]"),
+				),
+		)
+		.subcommand(
+			Command::new("fork")
+				.about("Fork parents into the organization")
+				.arg(arg!(--organization <NAME> "Organization to fork into")),
+		)
+		.subcommand(
+			Command::new("module").about("Submodules").subcommand_required(true).subcommand(
+				Command::new("git").about("Write .gitmodules").arg(
+					arg!(--output <PATH> "Where to write it [default: [module] output]")
+						.value_parser(value_parser!(PathBuf)),
+				),
+			),
+		)
+		.subcommand(
+			Command::new("configure")
+				.about("Add the parent as a remote")
+				.arg(arg!(--remote <NAME> "Remote name").default_value("upstream")),
+		)
+		.subcommand(Command::new("setting").about("Apply repository settings"))
+		.subcommand(
+			Command::new("rollback")
+				.about("Restore the checkouts a run backed up before changing them")
+				.arg(arg!(<RUN> "Id the run printed when it backed up"))
+				.arg(
+					arg!(--repo <NAME> "Only this repository, a glob over owner/name")
+						.action(ArgAction::Append),
+				)
+				.arg(arg!(--push "Force-push the branches of the remotes the run pushed to back as well")),
+		)
+		.subcommand(Command::new("daemon").about(
+			"Run every [[schedule]] when it comes due, until Ctrl-C; one process per workspace",
+		))
+		.subcommand(
+			Command::new("history")
+				.about("Runs recorded in <state>/History")
+				.subcommand_required(true)
+				.subcommand(
+					Command::new("list")
+						.about("Recent runs, or with a filter their entries")
+						.arg(arg!(--repo <PATTERN> "Entries for this repository, a glob over owner/name"))
+						.arg(arg!(--task <NAME> "Entries for this task or pipeline step"))
+						.arg(arg!(--succeeded "Only entries that changed or found nothing to change"))
+						.arg(
+							arg!(--limit <N> "How many, most recent first")
+								.value_parser(value_parser!(usize))
+								.default_value("20"),
+						),
+				)
+				.subcommand(
+					Command::new("show")
+						.about("Everything recorded about a run")
+						.arg(arg!(<RUN> "Run id, or last")),
+				)
+				.subcommand(
+					Command::new("compare")
+						.about("Time per task and changed outcomes between two runs")
+						.arg(arg!(<BEFORE> "Run id, or last"))
+						.arg(arg!(<AFTER> "Run id, or last")),
+				),
+		)
+		.subcommand(Command::new("tasks").about("List every registered task"))
+		.subcommand(
+			Command::new("config").about("Configuration").subcommand_required(true).subcommand(
+				Command::new("show").about("Print the configuration in effect, every layer merged"),
+			),
+		)
+		.subcommand(
+			Command::new("daily")
+				.about("Run every task in order")
+				.arg(arg!(--"if-changed" "Stop when the inventory did not change"))
+				.arg(
+					arg!(--resume "Continue the last run, retrying only what failed or never ran"),
+				)
+				.arg(arg!(--from <STEP> "Start at this pipeline step"))
+				.arg(arg!(--until <STEP> "Stop after this pipeline step"))
+				.arg(
+					arg!(--"only-step" <STEP> "Only this pipeline step, repeat for several")
+						.action(ArgAction::Append),
+				),
+		)
+}
+
+// Exit status of a run that got going.
+fn run(matches:&ArgMatches) -> Result<i32> {
+	let list = |name:&str| -> Vec<String> {
+		matches.get_many::<String>(name).map(|value| value.cloned().collect()).unwrap_or_default()
+	};
+
+	let mut only = list("only");
+
+	if let Some(("rollback", matches)) = matches.subcommand() {
+		only.extend(matches.get_many::<String>("repo").into_iter().flatten().cloned());
+	}
+
+	let selector = Selector::new(
+		&only,
+		&list("except"),
+		matches.get_one::<String>("filter").map(String::as_str),
+	)?;
+
+	// The same workspace from anywhere inside it
+	let root = match matches.get_one::<PathBuf>("workspace") {
+		Some(root) => root.clone(),
+		None => {
+			let current = std::env::current_dir()?;
+
+			Workspace::discover(&current).unwrap_or(current)
+		},
+	};
+
+	let file = matches.get_one::<PathBuf>("config").map(PathBuf::as_path);
+
+	// Like the configuration, relative to the workspace root wherever the command runs
+	let cache = matches.get_one::<PathBuf>("cache").map(PathBuf::as_path);
+
+	let config = Config::Load::Fn(&root, file, cache)?;
+
+	if let Some(("config", _)) = matches.subcommand() {
+		println!("# {}", Config::Load::file(&root, file).display());
+
+		print!("{}", toml::to_string_pretty(&config)?);
+
+		return Ok(0);
+	}
+
+	let mut workspace = Workspace::new(&root, config, selector);
+
+	// The command line goes over the configuration
+	if let Some(jobs) = matches.get_one::<usize>("jobs") {
+		workspace.jobs = *jobs;
+	}
+
+	workspace.dry_run = matches.get_flag("dry-run");
+
+	let workspace = &workspace;
+
+	// A dry run leaves the summary of the last real run in place, unless told where to write
+	let output = match matches.get_one::<PathBuf>("summary") {
+		Some(output) => Some(output.clone()),
+		None if workspace.dry_run => None,
+		None => Some(workspace.state.join(Summary::FILE)),
+	};
+
+	let registry = Registry::new();
+
+	// Runs that change something take the workspace, so no two work on it at once
+	let _lock = match matches.subcommand() {
+		Some(("tasks" | "daemon" | "history", _)) => None,
+		Some(("cache", matches)) if matches.subcommand_name() == Some("diff") => None,
+		_ if workspace.dry_run => None,
+		_ => Some(Lock::Lock::hold(&workspace.state.join(Lock::FILE))?),
+	};
+
+	// Leaf subcommands name their registered task; arguments replace its defaults.
+	let task:Box<dyn Task> = match matches.subcommand() {
+		Some(("cache", matches)) => {
+			return match matches.subcommand() {
+				Some(("get", _)) => Cache::Get::Fn(workspace).map(|_| 0),
+				Some(("diff", matches)) => {
+					Cache::Diff::Fn(workspace, matches.get_flag("json")).map(|_| 0)
+				},
+				_ => unreachable!(),
+			};
+		},
+		Some(("daily", matches)) => {
+			let mut summary = summary(workspace);
+
+			summary.entry = Cron::Daily::Fn(
+				workspace,
+				matches.get_flag("if-changed"),
+				matches.get_flag("resume"),
+				&Cron::Pipeline::Selection {
+					from:matches.get_one::<String>("from").cloned(),
+					until:matches.get_one::<String>("until").cloned(),
+					only:matches
+						.get_many::<String>("only-step")
+						.map(|step| step.cloned().collect())
+						.unwrap_or_default(),
+				},
+			)?;
+
+			return report(workspace, output.as_deref(), summary, true);
+		},
+		Some(("daemon", _)) => {
+			// Every run sees the workspace and the options the daemon does
+			let mut argument = vec!["--workspace".to_string(), root.display().to_string()];
+
+			if let Some(file) = file {
+				argument.extend(["--config".to_string(), file.display().to_string()]);
+			}
+
+			for name in ["cache", "jobs", "filter"] {
+				if let Some(value) = matches.get_raw(name).into_iter().flatten().next() {
+					argument.extend([format!("--{}", name), value.to_string_lossy().into_owned()]);
+				}
+			}
+
+			for name in ["only", "except"] {
+				for value in list(name) {
+					argument.extend([format!("--{}", name), value]);
+				}
+			}
+
+			if workspace.dry_run {
+				argument.push("--dry-run".to_string());
+			}
 
-	let category = matches
-		.get_one::<String>("category")
-		.expect("Cannot get the value of category!");
+			Cron::Daemon::Fn(workspace, &argument)?;
 
-	let process = matches.get_one::<String>("process").expect("Cannot get the value of process!");
+			return Ok(0);
+		},
+		Some(("history", matches)) => {
+			return match matches.subcommand() {
+				Some(("list", matches)) => History::List::Fn(
+					workspace,
+					&History::List::Filter {
+						repository:matches.get_one::<String>("repo").cloned(),
+						task:matches.get_one::<String>("task").cloned(),
+						succeeded:matches.get_flag("succeeded"),
+						limit:*matches.get_one::<usize>("limit").unwrap_or(&20),
+					},
+				),
+				Some(("show", matches)) => History::Show::Fn(workspace, value(matches, "RUN")),
+				Some(("compare", matches)) => History::Compare::Fn(
+					workspace,
+					value(matches, "BEFORE"),
+					value(matches, "AFTER"),
+				),
+				_ => unreachable!(),
+			}
+			.map(|_| 0);
+		},
+		Some(("tasks", _)) => {
+			for task in registry.iter() {
+				println!("{:<20}{}", task.name(), task.description());
+			}
 
-	if category == "cache" {
-		if process == "get" {
-			Cache::Get::Fn();
+			return Ok(0);
+		},
+		Some(("clone", matches)) => {
+			Box::new(CloneRepository { depth:*matches.get_one::<u32>("depth").unwrap_or(&1) })
+		},
+		Some(("rename", matches)) if matches.subcommand_name() == Some("branch") => {
+			let matches = matches.subcommand_matches("branch").expect("Cannot get rename branch!");
+
+			Box::new(RenameBranch { branch:value(matches, "branch").to_string() })
+		},
+		Some(("sync", matches)) => Box::new(SyncRepository {
+			upstream:Upstream::Upstream {
+				strategy:value(matches, "strategy").parse()?,
+				parent:Upstream::Remote::new(value(matches, "parent"))?,
+				fork:Upstream::Remote::new(value(matches, "fork"))?,
+				branch:matches.get_one::<String>("branch").cloned(),
+				overlay:matches
+					.get_many::<PathBuf>("overlay")
+					.map(|path| path.cloned().collect())
+					.unwrap_or_default(),
+				push:matches.get_flag("push"),
+			},
+		}),
+		Some(("rollback", matches)) => Box::new(RollbackRepository::new(
+			workspace,
+			value(matches, "RUN"),
+			matches.get_flag("push"),
+		)?),
+		Some(("fork", matches)) => Box::new(ForkOrganization {
+			organization:matches.get_one::<String>("organization").cloned(),
+		}),
+		Some(("module", matches)) => {
+			let matches = matches.subcommand_matches("git").expect("Cannot get module git!");
+
+			Box::new(ModuleGit { output:matches.get_one::<PathBuf>("output").cloned() })
+		},
+		Some(("configure", matches)) => {
+			Box::new(ConfigureRepository { remote:value(matches, "remote").to_string() })
+		},
+		Some((name, matches)) => {
+			let name = match matches.subcommand_name() {
+				Some(process) => format!("{} {}", name, process),
+				None => name.to_string(),
+			};
+
+			let Some(task) = registry.get(&name) else {
+				bail!("Unknown task {}", name);
+			};
+
+			return execute(workspace, task, output.as_deref());
+		},
+		None => unreachable!(),
+	};
+
+	execute(workspace, task.as_ref(), output.as_deref())
+}
+
+fn execute(workspace:&Workspace, task:&dyn Task, output:Option<&Path>) -> Result<i32> {
+	let mut summary = summary(workspace);
+
+	summary.entry = Run::Fn(workspace, task.name(), task, &Journal::default())?;
+
+	report(workspace, output, summary, false)
+}
+
+fn summary(workspace:&Workspace) -> Summary::Summary {
+	let command = std::env::args().skip(1).collect::<Vec<_>>().join(" ");
+
+	Summary::Summary::new(&workspace.run, command, workspace.dry_run)
+}
+
+// Prints the summary table and keeps it as JSON at `output` for whatever schedules the run, and
+// in the history unless nothing really ran. The report of a `daily` goes next to it, where the
+// next real `daily` replaces it, and to the webhook.
+fn report(
+	workspace:&Workspace,
+	output:Option<&Path>,
+	mut summary:Summary::Summary,
+	daily:bool,
+) -> Result<i32> {
+	summary.finish();
+
+	print!("\n{}", summary);
+
+	if let Some(output) = output {
+		summary.save(output)?;
+
+		println!("Summary written to {}", output.display());
+	}
+
+	if !summary.dry_run {
+		History::Store::save(&workspace.state, &summary)?;
+	}
+
+	// A dry run keeps the last reports and tells nobody about a plan
+	if daily && !summary.dry_run {
+		let digest = Report::Digest::new(&summary);
+
+		let markdown = Report::Markdown::Fn(&digest);
+
+		for (name, content) in
+			[(Report::MARKDOWN, markdown.clone()), (Report::HTML, Report::Html::Fn(&digest))]
+		{
+			let path = workspace.state.join(name);
+
+			fs::write(&path, content)
+				.with_context(|| format!("Failed to write {}", path.display()))?;
+		}
+
+		println!("Report written to {}", workspace.state.join(Report::MARKDOWN).display());
+
+		let webhook = &workspace.config.report.webhook;
+
+		// The run is done either way, so a webhook that is down only warns
+		if !webhook.is_empty() {
+			let timeout = Duration::from_secs_f64(workspace.config.report.timeout);
+
+			match Report::Webhook::Fn(webhook, timeout, &digest, &markdown) {
+				Ok(()) => println!("Report posted to {}", webhook),
+				Err(error) => eprintln!("Warning: {:#}", error),
+			}
 		}
-	} else {
-		eprintln!("Invalid process specified. Please use 'get'.");
 	}
+
+	if summary.cancelled() > 0 {
+		eprintln!("Cancelled, {} repositories did not run", summary.cancelled());
+
+		return Ok(130);
+	}
+
+	if summary.failed() > 0 {
+		eprintln!("Error: {} repositories failed", summary.failed());
+
+		return Ok(2);
+	}
+
+	Ok(0)
+}
+
+fn value<'a>(matches:&'a ArgMatches, name:&str) -> &'a str {
+	matches.get_one::<String>(name).map(String::as_str).expect("Cannot get the value!")
 }
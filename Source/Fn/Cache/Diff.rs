@@ -0,0 +1,307 @@
+use std::{collections::BTreeMap, fmt, path::Path};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::Fn::{Cache::Snapshot, Forge::Repository, Workspace::Workspace};
+
+// What changed in the fleet between two snapshots. Repositories are matched by forge and id, so a
+// rename is not mistaken for a removal followed by an addition.
+#[derive(Clone, Debug, Default, Serialize, PartialEq)]
+pub struct Diff {
+	pub previous:String,
+
+	pub current:String,
+
+	pub added:Vec<String>,
+
+	pub removed:Vec<String>,
+
+	pub renamed:Vec<Change>,
+
+	pub archived:Vec<String>,
+
+	pub parent:Vec<Change>,
+
+	pub default_branch:Vec<Change>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, PartialEq)]
+pub struct Change {
+	pub repository:String,
+
+	pub from:String,
+
+	pub to:String,
+}
+
+impl Diff {
+	pub fn new(previous:&Snapshot::Snapshot, current:&Snapshot::Snapshot) -> Self {
+		let (before, after) = (index(previous), index(current));
+
+		let mut diff = Self {
+			previous:previous.generated.clone(),
+			current:current.generated.clone(),
+			..Default::default()
+		};
+
+		for (key, old) in &before {
+			if !after.contains_key(key) {
+				diff.removed.push(old.full_name());
+			}
+		}
+
+		for (key, new) in &after {
+			let Some(old) = before.get(key) else {
+				diff.added.push(new.full_name());
+
+				continue;
+			};
+
+			let repository = new.full_name();
+
+			if old.full_name() != repository {
+				diff.renamed.push(Change {
+					repository:repository.clone(),
+					from:old.full_name(),
+					to:repository.clone(),
+				});
+			}
+
+			if new.archived && !old.archived {
+				diff.archived.push(repository.clone());
+			}
+
+			if old.parent != new.parent {
+				diff.parent.push(Change {
+					repository:repository.clone(),
+					from:old.parent.clone().unwrap_or_default(),
+					to:new.parent.clone().unwrap_or_default(),
+				});
+			}
+
+			if old.default_branch != new.default_branch {
+				diff.default_branch.push(Change {
+					repository,
+					from:old.default_branch.clone(),
+					to:new.default_branch.clone(),
+				});
+			}
+		}
+
+		for list in [&mut diff.added, &mut diff.removed, &mut diff.archived] {
+			list.sort();
+		}
+
+		for list in [&mut diff.renamed, &mut diff.parent, &mut diff.default_branch] {
+			list.sort_by(|a, b| a.repository.cmp(&b.repository));
+		}
+
+		diff
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.added.is_empty()
+			&& self.removed.is_empty()
+			&& self.renamed.is_empty()
+			&& self.archived.is_empty()
+			&& self.parent.is_empty()
+			&& self.default_branch.is_empty()
+	}
+}
+
+impl fmt::Display for Diff {
+	fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+		let previous = if self.previous.is_empty() { "no snapshot" } else { &self.previous };
+
+		if self.is_empty() {
+			return writeln!(f, "No changes between {} and {}.", previous, self.current);
+		}
+
+		writeln!(f, "Changes between {} and {}:", previous, self.current)?;
+
+		for repository in &self.added {
+			writeln!(f, "Added: {}", repository)?;
+		}
+
+		for repository in &self.removed {
+			writeln!(f, "Removed: {}", repository)?;
+		}
+
+		for change in &self.renamed {
+			writeln!(f, "Renamed: {} -> {}", change.from, change.to)?;
+		}
+
+		for repository in &self.archived {
+			writeln!(f, "Archived: {}", repository)?;
+		}
+
+		let or_none =
+			|value:&str| if value.is_empty() { "(none)".to_string() } else { value.to_string() };
+
+		for change in &self.parent {
+			writeln!(
+				f,
+				"Parent: {}: {} -> {}",
+				change.repository,
+				or_none(&change.from),
+				or_none(&change.to)
+			)?;
+		}
+
+		for change in &self.default_branch {
+			writeln!(
+				f,
+				"Default branch: {}: {} -> {}",
+				change.repository,
+				or_none(&change.from),
+				or_none(&change.to)
+			)?;
+		}
+
+		Ok(())
+	}
+}
+
+fn index(snapshot:&Snapshot::Snapshot) -> BTreeMap<(String, u64), &Repository> {
+	snapshot
+		.repository
+		.iter()
+		.map(|entry| ((entry.forge.clone(), entry.repository.id), &entry.repository))
+		.collect()
+}
+
+pub fn Fn(workspace:&Workspace, json:bool) -> Result<()> {
+	let diff = diff(&workspace.cache).context("Failed to compare the repository cache")?;
+
+	if json {
+		println!("{}", serde_json::to_string_pretty(&diff)?);
+	} else {
+		print!("{}", diff);
+	}
+
+	Ok(())
+}
+
+// Compares the snapshot the last `Cache::Get` wrote with the one it replaced. Without an earlier
+// snapshot every repository counts as added.
+pub fn diff(directory:&Path) -> Result<Diff> {
+	let current = Snapshot::Snapshot::load(&directory.join(Snapshot::FILE))?;
+
+	let previous = Snapshot::Snapshot::load_or_default(&directory.join(Snapshot::PREVIOUS))?;
+
+	Ok(Diff::new(&previous, &current))
+}
+
+#[cfg(test)]
+mod test {
+	use super::{Change, Diff};
+	use crate::Fn::{
+		Cache::Snapshot::{Entry, Snapshot},
+		Forge::Repository,
+	};
+
+	fn entry(forge:&str, id:u64, name:&str) -> Entry {
+		Entry {
+			forge:forge.to_string(),
+			repository:Repository {
+				id,
+				owner:"owner".to_string(),
+				name:name.to_string(),
+				default_branch:"main".to_string(),
+				..Default::default()
+			},
+		}
+	}
+
+	fn snapshot(generated:&str, repository:Vec<Entry>) -> Snapshot {
+		Snapshot { generated:generated.to_string(), repository }
+	}
+
+	fn change(repository:&str, from:&str, to:&str) -> Change {
+		Change { repository:repository.to_string(), from:from.to_string(), to:to.to_string() }
+	}
+
+	#[test]
+	fn tells_every_kind_of_change_apart() {
+		let previous = snapshot(
+			"yesterday",
+			vec![
+				entry("github", 1, "kept"),
+				entry("github", 2, "gone"),
+				entry("github", 3, "old-name"),
+				entry("github", 4, "archive"),
+				entry("github", 5, "fork"),
+				entry("github", 6, "branch"),
+			],
+		);
+
+		let mut current = vec![
+			entry("github", 1, "kept"),
+			entry("github", 3, "NewName"),
+			entry("github", 4, "archive"),
+			entry("github", 5, "fork"),
+			entry("github", 6, "branch"),
+			entry("github", 7, "fresh"),
+		];
+
+		current[2].repository.archived = true;
+
+		current[3].repository.parent = Some("upstream/fork".to_string());
+
+		current[4].repository.default_branch = "trunk".to_string();
+
+		let diff = Diff::new(&previous, &snapshot("today", current));
+
+		assert_eq!(
+			diff,
+			Diff {
+				previous:"yesterday".to_string(),
+				current:"today".to_string(),
+				added:vec!["owner/fresh".to_string()],
+				removed:vec!["owner/gone".to_string()],
+				renamed:vec![change("owner/NewName", "owner/old-name", "owner/NewName")],
+				archived:vec!["owner/archive".to_string()],
+				parent:vec![change("owner/fork", "", "upstream/fork")],
+				default_branch:vec![change("owner/branch", "main", "trunk")],
+			}
+		);
+
+		assert_eq!(
+			diff.to_string(),
+			"Changes between yesterday and today:\nAdded: owner/fresh\nRemoved: owner/gone\nRenamed: \
+			 owner/old-name -> owner/NewName\nArchived: owner/archive\nParent: owner/fork: (none) -> \
+			 upstream/fork\nDefault branch: owner/branch: main -> trunk\n"
+		);
+	}
+
+	#[test]
+	fn matches_by_forge_and_id_only() {
+		let previous = snapshot("yesterday", vec![entry("github", 1, "name")]);
+
+		// The same id on another forge is another repository, whatever its name
+		let diff = Diff::new(&previous, &snapshot("today", vec![entry("mirror", 1, "name")]));
+
+		assert_eq!(diff.added, ["owner/name"]);
+
+		assert_eq!(diff.removed, ["owner/name"]);
+
+		assert!(diff.renamed.is_empty());
+
+		// Unarchiving is no change worth reporting
+		let mut archived = entry("github", 1, "name");
+
+		archived.repository.archived = true;
+
+		let diff = Diff::new(&snapshot("yesterday", vec![archived]), &previous);
+
+		assert!(diff.is_empty());
+
+		assert_eq!(diff.to_string(), "No changes between yesterday and yesterday.\n");
+
+		assert_eq!(
+			Diff::new(&Snapshot::default(), &previous).to_string(),
+			"Changes between no snapshot and yesterday:\nAdded: owner/name\n"
+		);
+	}
+}
@@ -0,0 +1,111 @@
+use anyhow::{Context, Result};
+use regex::{Regex, RegexBuilder};
+
+// Compiled `exclude` patterns of the manifest. Matching ignores case, as forges do for names.
+#[derive(Clone, Debug, Default)]
+pub struct Exclude {
+	pattern:Vec<Regex>,
+}
+
+impl Exclude {
+	pub fn new(pattern:&[String]) -> Result<Self> {
+		Ok(Self { pattern:pattern.iter().map(|pattern| compile(pattern)).collect::<Result<_>>()? })
+	}
+
+	pub fn matches(&self, full_name:&str) -> bool {
+		self.pattern.iter().any(|pattern| pattern.is_match(full_name))
+	}
+}
+
+// `/…/` is a regular expression searched anywhere in the name, anything else is a glob over the
+// whole name.
+pub fn compile(pattern:&str) -> Result<Regex> {
+	let source = match pattern.strip_prefix('/').and_then(|pattern| pattern.strip_suffix('/')) {
+		Some(expression) => expression.to_string(),
+		None => format!("^{}$", glob(pattern)),
+	};
+
+	RegexBuilder::new(&source)
+		.case_insensitive(true)
+		.build()
+		.with_context(|| format!("Invalid pattern {}", pattern))
+}
+
+// `*` and `?` stay within one path segment, `**` crosses them.
+pub fn glob(pattern:&str) -> String {
+	let mut expression = String::new();
+
+	let mut character = pattern.chars().peekable();
+
+	while let Some(current) = character.next() {
+		match current {
+			'*' if character.peek() == Some(&'*') => {
+				character.next();
+
+				expression.push_str(".*");
+			},
+			'*' => expression.push_str("[^/]*"),
+			'?' => expression.push_str("[^/]"),
+			_ => expression.push_str(&regex::escape(&current.to_string())),
+		}
+	}
+
+	expression
+}
+
+#[cfg(test)]
+mod test {
+	use super::{compile, Exclude};
+
+	fn exclude(pattern:&[&str]) -> Exclude {
+		Exclude::new(&pattern.iter().map(ToString::to_string).collect::<Vec<_>>()).unwrap()
+	}
+
+	#[test]
+	fn keeps_a_single_star_within_one_segment() {
+		let star = exclude(&["owner/*"]);
+
+		assert!(star.matches("owner/name"));
+
+		assert!(!star.matches("owner/name/nested"));
+
+		assert!(!star.matches("other/owner/name"));
+
+		assert!(exclude(&["*/name"]).matches("owner/name"));
+
+		assert!(!exclude(&["*"]).matches("owner/name"));
+
+		assert!(exclude(&["**"]).matches("owner/name"));
+
+		assert!(exclude(&["owner/**"]).matches("owner/name/nested"));
+
+		assert!(exclude(&["owner/na?e"]).matches("owner/name"));
+
+		assert!(!exclude(&["owner/na?e"]).matches("owner/na/e"));
+
+		// Everything else is literal
+		assert!(!exclude(&["owner/name.js"]).matches("owner/name-js"));
+	}
+
+	#[test]
+	fn searches_a_regular_expression_anywhere() {
+		let expression = exclude(&["/^owner/(old|legacy)-/"]);
+
+		assert!(expression.matches("owner/old-editor"));
+
+		assert!(!expression.matches("owner/editor-old-"));
+
+		assert!(exclude(&["/fork/"]).matches("owner/name-fork-2"));
+
+		assert!(compile("/(unclosed/").is_err());
+	}
+
+	#[test]
+	fn ignores_case() {
+		assert!(exclude(&["CodeEditorLand/Babel"]).matches("codeeditorland/babel"));
+
+		assert!(exclude(&["/^codeeditorland/"]).matches("CodeEditorLand/Biome"));
+
+		assert!(!exclude(&[]).matches("owner/name"));
+	}
+}
@@ -1,64 +1,198 @@
-// use std::{
-// 	fs::File,
-// 	io::{self, Write},
-// 	path::PathBuf,
-// 	process::Command,
-// };
-
-pub fn Fn() {
+use std::fs;
+
+use anyhow::{Context, Result};
+
+use crate::Fn::{
+	Cache::{Exclude::Exclude, Snapshot},
+	Forge::Fleet::Fleet,
+	Manifest,
+	Task::Audit,
+	Workspace::Workspace,
+};
+
+// What `[retry."cache get"]` in the manifest tunes listing the fleet with.
+pub const TASK:&str = "cache get";
+
+// A dry run lists the fleet without touching the cache.
+pub fn Fn(workspace:&Workspace) -> Result<Snapshot::Snapshot> {
 	println!("Process: Cache/Get.rs");
 
-	// Contextless
-	let mut repository:Vec<String> = Vec::new();
-
-	// for page in 1..=10 {
-	// 	println!(
-	// 		"{:?}",
-	// 		String::from_utf8_lossy(
-	// 			&Command::new("gh")
-	// 				.args(&[
-	// 					"api",
-	// 					&format!("orgs/{}/repos?per_page=100&page={}", "CodeEditorLand",
-	// page), 					"-H",
-	// 					"Accept: application/vnd.github+json",
-	// 					"-H",
-	// 					"X-GitHub-Api-Version: 2022-11-28",
-	// 				])
-	// 				.output()
-	// 				.expect("Failed to execute command")
-	// 				.stdout
-	// 		)
-	// 		.lines()
-	// 		.map(|line| line.trim())
-	// 		.filter(|line| !line.is_empty())
-	// 		.filter(|line| ![
-	// 			"CodeEditorLand/.github",
-	// 			"CodeEditorLand/Babel",
-	// 			"CodeEditorLand/Biome",
-	// 			"CodeEditorLand/Build",
-	// 			"CodeEditorLand/Commonality",
-	// 			"CodeEditorLand/debugger-libs",
-	// 			"CodeEditorLand/node-gyp",
-	// 			"CodeEditorLand/NRefactory",
-	// 			"CodeEditorLand/Oniguruma",
-	// 			"CodeEditorLand/TypeScript",
-	// 			"CodeEditorLand/Wil",
-	// 		]
-	// 		.contains(&line))
-	// 		.collect::<Vec<_>>()
-	// 	)
-	// }
-
-	// repository.sort();
-
-	// let mut file = File::create(
-	// 	std::env::current_dir()
-	// 		.expect("Failed to get current directory")
-	// 		.join("Repository/Build.md"),
-	// )
-	// .expect("Failed to create file");
-
-	// for repo in &repository {
-	// 	writeln!(file, "{}", repo).expect("Failed to write to file");
-	// }
+	if workspace.dry_run {
+		let snapshot = list(workspace).context("Failed to list the fleet")?;
+
+		println!(
+			"Would cache {} repositories in {} and {}.",
+			snapshot.repository.len(),
+			workspace.cache.join("Build.md").display(),
+			workspace.cache.join(Snapshot::FILE).display()
+		);
+
+		return Ok(snapshot);
+	}
+
+	let snapshot = get(workspace).context("Failed to refresh the repository cache")?;
+
+	println!("Cached {} repositories.", snapshot.repository.len());
+
+	Ok(snapshot)
+}
+
+// Lists every owner of every forge in full and drops excluded repositories.
+pub fn list(workspace:&Workspace) -> Result<Snapshot::Snapshot> {
+	let manifest = match workspace.manifest() {
+		Ok(manifest) => manifest,
+		// The configuration alone says where the fleet is
+		Err(_) if !workspace.cache.join("Build.toml").exists() => {
+			let mut manifest = Manifest::Manifest {
+				owner:Some(workspace.config.workspace.owner.clone()),
+				..Default::default()
+			};
+
+			workspace.config.fleet(&mut manifest);
+
+			manifest
+		},
+		Err(error) => return Err(error),
+	};
+
+	let exclude = Exclude::new(&manifest.exclude)?;
+
+	let fleet = Fleet::new(&manifest)?;
+
+	let mut entry = Vec::new();
+
+	let retry = manifest.retry.get(TASK).cloned().unwrap_or_default();
+
+	for (name, forge, owner) in fleet.owners() {
+		let list = retry
+			.run(&format!("{} on {}", owner, name), || forge.list(owner))
+			.with_context(|| format!("Failed to list repositories of {} on {}", owner, name))?;
+
+		entry.extend(
+			list.into_iter()
+				.filter(|repository| !exclude.matches(&repository.full_name()))
+				.map(|repository| Snapshot::Entry { forge:name.to_string(), repository }),
+		);
+	}
+
+	entry.sort_by_key(|entry| entry.repository.full_name());
+
+	entry.dedup_by_key(|entry| entry.repository.full_name());
+
+	Ok(Snapshot::Snapshot::new(entry))
+}
+
+// `list`, written to Build.md alongside a metadata snapshot. Every file it changes is audited.
+pub fn get(workspace:&Workspace) -> Result<Snapshot::Snapshot> {
+	let snapshot = list(workspace)?;
+
+	let directory = &workspace.cache;
+
+	// A fresh workspace has no cache yet
+	fs::create_dir_all(directory)
+		.with_context(|| format!("Failed to create {}", directory.display()))?;
+
+	let audit = Audit::Audit::open(&workspace.state.join(Audit::FILE), &workspace.run, false)?;
+
+	let list = snapshot
+		.repository
+		.iter()
+		.map(|entry| entry.repository.full_name() + "\n")
+		.collect::<String>();
+
+	audit.write("", TASK, &directory.join("Build.md"), list.as_bytes())?;
+
+	let file = directory.join(Snapshot::FILE);
+
+	if file.exists() {
+		audit
+			.rename("", TASK, &file, &directory.join(Snapshot::PREVIOUS))
+			.context("Failed to keep the previous snapshot")?;
+	}
+
+	audit.write("", TASK, &file, (serde_json::to_string_pretty(&snapshot)? + "\n").as_bytes())?;
+
+	Ok(snapshot)
+}
+
+#[cfg(test)]
+mod test {
+	use std::fs;
+
+	use super::get;
+	use crate::Fn::{
+		Cache::Snapshot,
+		Task::{Audit::Event, Test::Scratch},
+	};
+
+	#[test]
+	fn audits_the_files_it_writes() {
+		let mut scratch = Scratch::new("cache-get");
+
+		scratch.forge();
+
+		get(&scratch.workspace).unwrap();
+
+		get(&scratch.workspace).unwrap();
+
+		let cache = &scratch.workspace.cache;
+
+		assert_eq!(fs::read_to_string(cache.join("Build.md")).unwrap(), "owner/name\n");
+
+		assert!(cache.join(Snapshot::PREVIOUS).exists());
+
+		let event:Vec<_> = scratch
+			.audited()
+			.into_iter()
+			.map(|record| {
+				assert_eq!((record.repository.as_str(), record.task.as_str()), ("", "cache get"));
+
+				match record.event {
+					Event::Write { path, before, .. } => {
+						format!(
+							"write {} {}",
+							path.strip_prefix(cache).unwrap().display(),
+							before.is_some()
+						)
+					},
+					Event::Rename { from, to, .. } => {
+						format!(
+							"rename {} {}",
+							from.strip_prefix(cache).unwrap().display(),
+							to.strip_prefix(cache).unwrap().display()
+						)
+					},
+					event => panic!("Unexpected {:?}", event),
+				}
+			})
+			.collect();
+
+		assert_eq!(
+			event,
+			[
+				"write Build.md true",
+				"write Build.json false",
+				"write Build.md true",
+				"rename Build.json Build.previous.json",
+				"write Build.json false",
+			]
+		);
+	}
+
+	#[test]
+	fn creates_the_cache_of_a_fresh_workspace() {
+		let mut scratch = Scratch::new("cache-get-fresh");
+
+		scratch.forge();
+
+		fs::remove_dir_all(&scratch.workspace.cache).unwrap();
+
+		let snapshot = get(&scratch.workspace).unwrap();
+
+		assert_eq!(snapshot.repository.len(), 1);
+
+		assert_eq!(snapshot.repository[0].forge, "github");
+
+		assert!(scratch.workspace.cache.join(Snapshot::FILE).exists());
+	}
 }
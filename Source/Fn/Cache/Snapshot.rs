@@ -0,0 +1,55 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result};
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::Fn::Forge;
+
+// Snapshot file next to Build.md.
+pub const FILE:&str = "Build.json";
+
+// The snapshot `FILE` replaced, kept for `Cache::Diff`.
+pub const PREVIOUS:&str = "Build.previous.json";
+
+// Everything the forges reported about the fleet at one point in time.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct Snapshot {
+	pub generated:String,
+
+	pub repository:Vec<Entry>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct Entry {
+	// Name of the `[forge.<name>]` the repository was listed from.
+	pub forge:String,
+
+	#[serde(flatten)]
+	pub repository:Forge::Repository,
+}
+
+impl Snapshot {
+	pub fn new(repository:Vec<Entry>) -> Self {
+		Self { generated:Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true), repository }
+	}
+
+	pub fn load(path:&Path) -> Result<Self> {
+		serde_json::from_str(
+			&fs::read_to_string(path)
+				.with_context(|| format!("Failed to read {}", path.display()))?,
+		)
+		.with_context(|| format!("Failed to parse {}", path.display()))
+	}
+
+	// An empty snapshot when `path` does not exist yet.
+	pub fn load_or_default(path:&Path) -> Result<Self> {
+		if path.exists() {
+			Self::load(path)
+		} else {
+			Ok(Self::default())
+		}
+	}
+}
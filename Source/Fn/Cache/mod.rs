@@ -1 +1,4 @@
+pub mod Diff;
+pub mod Exclude;
 pub mod Get;
+pub mod Snapshot;
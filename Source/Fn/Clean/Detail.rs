@@ -1,88 +1,46 @@
-use std::{error::Error, fs, process::Command};
+use anyhow::Result;
 
-use rayon::prelude::*;
+use crate::Fn::{
+	Action,
+	Task::{RepoContext, Task, TaskOutcome},
+};
 
-fn main() {
-	println!("Process: Clean/Detail.sh");
+#[derive(Default)]
+pub struct CleanDetail;
 
-	// Get the current directory
-	let directory = match std::env::current_dir() {
-		Ok(dir) => dir,
-		Err(e) => {
-			eprintln!("Error getting current directory: {}", e);
-			std::process::exit(1);
-		},
-	};
+impl Task for CleanDetail {
+	fn name(&self) -> &'static str {
+		"clean detail"
+	}
 
-	// Read the list of repositories
-	let repository_file = directory.join("../Cache/Repository/Build.md");
-	let repository_contents = match fs::read_to_string(&repository_file) {
-		Ok(contents) => contents,
-		Err(e) => {
-			eprintln!("Error reading repository file {:?}: {}", repository_file, e);
-			std::process::exit(1);
-		},
-	};
+	fn description(&self) -> &'static str {
+		"Strip linting and test configuration from package.json"
+	}
 
-	// Extract repository names
-	let repositories:Vec<String> = repository_contents.lines().map(String::from).collect();
+	fn applies(&self, context:&RepoContext) -> bool {
+		context.path.exists()
+	}
 
-	// Clean repositories in parallel
-	repositories.par_iter().for_each(|repo| {
-		if let Err(err) = clean_repository(&directory, repo) {
-			eprintln!("Error cleaning repository {}: {}", repo, err);
-		}
-	});
-}
+	fn parallel(&self) -> bool {
+		true
+	}
 
-fn clean_repository(directory:&std::path::Path, repository:&str) -> Result<(), Box<dyn Error>> {
-	let folder = repository.replace("CodeEditorLand/", "");
-	let folder_path = directory.join(&folder);
+	fn backup(&self) -> bool {
+		true
+	}
 
-	// Change directory to repository
-	let _output = Command::new("sh")
-		.arg("-c")
-		.arg(format!("cd {:?} || exit", folder_path))
-		.output()?;
+	fn run(&self, context:&RepoContext) -> Result<TaskOutcome> {
+		context.log(format!("Current directory: {:?}", context.path));
 
-	println!("Current directory: {:?}", folder_path);
+		let mut changed = false;
 
-	// Find and execute Detail.sh for each package.json file
-	let _output = Command::new("find")
-		.arg(".")
-		.arg("-type")
-		.arg("d")
-		.arg("(")
-		.arg("-iname")
-		.arg("node_modules")
-		.arg("-o")
-		.arg("-iname")
-		.arg("vendor")
-		.arg("-o")
-		.arg("-iname")
-		.arg("dist")
-		.arg("-o")
-		.arg("-iname")
-		.arg("target")
-		.arg("-o")
-		.arg("-iname")
-		.arg(".git")
-		.arg("-o")
-		.arg("-iname")
-		.arg(".next")
-		.arg(")")
-		.arg("-prune")
-		.arg("-false")
-		.arg("-o")
-		.arg("-iname")
-		.arg("package.json")
-		.arg("-type")
-		.arg("f")
-		.arg("-execdir")
-		.arg("sh")
-		.arg("-c")
-		.arg(format!("{}/../Action/Clean/Detail.sh", directory.display()))
-		.output()?;
+		// Clean every package.json of the repository
+		for package in Action::Find::Fn(&context.path, "package.json") {
+			if let Some(directory) = package.parent() {
+				changed |= Action::Clean::Detail::Fn(context, directory)?;
+			}
+		}
 
-	Ok(())
+		Ok(changed.into())
+	}
 }
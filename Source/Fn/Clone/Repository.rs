@@ -1,39 +1,79 @@
-use std::{error::Error, process::Command};
-
-use rayon::prelude::*;
-
-fn main() {
-	// Fetch the list of repositories
-	let repositories = match std::fs::read_to_string("../Cache/Repository/Build.md") {
-		Ok(contents) => contents.lines().map(|s| s.trim().to_string()).collect::<Vec<_>>(),
-		Err(e) => {
-			eprintln!("Error reading repository list: {}", e);
-			std::process::exit(1);
-		},
-	};
-
-	// Clone repositories in parallel
-	repositories.par_iter().for_each(|repo| {
-		if let Err(err) = clone_repository(repo) {
-			eprintln!("Error cloning repository {}: {}", repo, err);
-		}
-	});
+use std::{fs, process::Command};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::Fn::{
+	Task::{RepoContext, Retry::Policy, Task, TaskOutcome},
+	Workspace::Workspace,
+};
+
+// Clones every repository that is not checked out yet. `depth` of zero clones the full history.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CloneRepository {
+	pub depth:u32,
+}
+
+impl Default for CloneRepository {
+	fn default() -> Self {
+		Self { depth:1 }
+	}
 }
 
-fn clone_repository(repository:&str) -> Result<(), Box<dyn Error>> {
-	// Execute git clone command
-	let output = Command::new("git")
-		.args(&["clone", "--depth=1", "--recurse-submodules", "--shallow-submodules"])
-		.arg(format!("ssh://git@github.com/{}.git", repository))
-		.output()?;
+impl Task for CloneRepository {
+	fn name(&self) -> &'static str {
+		"clone"
+	}
+
+	fn description(&self) -> &'static str {
+		"Clone missing repositories"
+	}
+
+	fn applies(&self, context:&RepoContext) -> bool {
+		!context.path.exists()
+	}
+
+	fn parallel(&self) -> bool {
+		true
+	}
+
+	fn prepare(&self, workspace:&Workspace) -> Result<()> {
+		if workspace.dry_run {
+			return Ok(());
+		}
 
-	// Print the output
-	println!("{}", String::from_utf8_lossy(&output.stdout));
+		fs::create_dir_all(&workspace.checkout)
+			.with_context(|| format!("Failed to create {}", workspace.checkout.display()))
+	}
 
-	// Check if the command was successful
-	if !output.status.success() {
-		return Err(format!("Failed to clone repository {}", repository).into());
+	// A clone moves the most data of any task, so it gets the most patience
+	fn retry(&self) -> Policy {
+		Policy { attempts:5, ..Default::default() }
 	}
 
-	Ok(())
+	fn run(&self, context:&RepoContext) -> Result<TaskOutcome> {
+		let repository = context.repository;
+
+		let url = context.forge()?.url(&repository.owner, &repository.name);
+
+		// The checkout does not exist yet, so clone from the directory holding it
+		let mut command = Command::new("git");
+
+		command.current_dir(&context.workspace.checkout).args(["clone", "--recurse-submodules"]);
+
+		if self.depth > 0 {
+			command.arg(format!("--depth={}", self.depth)).arg("--shallow-submodules");
+		}
+
+		// Execute git clone command
+		let output = context
+			.execute(command.arg(url).arg(&context.path))
+			.with_context(|| format!("Failed to clone repository {}", repository.full_name()))?;
+
+		// Print the output
+		context.log(output.stdout);
+
+		Ok(TaskOutcome::Changed)
+	}
 }
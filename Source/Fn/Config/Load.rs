@@ -0,0 +1,226 @@
+use std::{
+	fs,
+	path::{Path, PathBuf},
+	time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use toml::{Table, Value};
+
+use crate::Fn::Config::{Config, ENVIRONMENT, FILE, FLEET};
+
+// Layers the sections of Build.toml in the cache directory that `Config::FLEET` names, then
+// `path`, else the `Maintain.toml` of `root` when there is one, and then the environment over the
+// defaults. Tables merge key by key, so a file only needs what it changes. `cache` from the
+// command line goes over all of them, and decides which Build.toml is read too.
+pub fn Fn(root:&Path, path:Option<&Path>, cache:Option<&Path>) -> Result<Config> {
+	let file = file(root, path);
+
+	let layer = if file.exists() {
+		fs::read_to_string(&file)
+			.with_context(|| format!("Failed to read {}", file.display()))?
+			.parse::<Table>()
+			.with_context(|| format!("Failed to parse {}", file.display()))?
+	} else if path.is_some() {
+		bail!("{} does not exist", file.display());
+	} else {
+		Table::new()
+	};
+
+	let variable:Vec<(String, String)> = std::env::vars().collect();
+
+	let layered = |fleet:Table| -> Result<Config> {
+		let mut config = Table::try_from(Config::default())?;
+
+		merge(&mut config, fleet);
+
+		merge(&mut config, layer.clone());
+
+		environment(&mut config, variable.iter().cloned());
+
+		let mut config = Value::Table(config).try_into::<Config>().with_context(|| {
+			format!("Invalid configuration in {} or the environment", file.display())
+		})?;
+
+		if let Some(cache) = cache {
+			config.workspace.cache = cache.to_path_buf();
+		}
+
+		Ok(config)
+	};
+
+	// Where Build.toml is depends on everything but Build.toml
+	let config = layered(Table::new())?;
+
+	let fleet = fleet(&root.join(&config.workspace.cache).join("Build.toml"))?;
+
+	let config = if fleet.is_empty() { config } else { layered(fleet)? };
+
+	let timeout = &config.timeout;
+
+	let mut seconds = vec![
+		("report.timeout".to_string(), config.report.timeout),
+		("timeout.command".to_string(), timeout.command),
+		("timeout.push".to_string(), timeout.push),
+		("timeout.fetch".to_string(), timeout.fetch),
+		("timeout.forge".to_string(), timeout.forge),
+	];
+
+	for (name, endpoint) in &config.forge {
+		if let Some(timeout) = endpoint.timeout {
+			seconds.push((format!("forge.{}.timeout", name), timeout));
+		}
+	}
+
+	// Each becomes a `Duration`, which holds neither a negative number, NaN nor infinity
+	for (name, seconds) in seconds {
+		if !(seconds > 0.0 && Duration::try_from_secs_f64(seconds).is_ok()) {
+			bail!("{} must be a positive number of seconds, not {}", name, seconds);
+		}
+	}
+
+	Ok(config)
+}
+
+// The sections of the manifest at `path` that belong to the configuration.
+fn fleet(path:&Path) -> Result<Table> {
+	if !path.exists() {
+		return Ok(Table::new());
+	}
+
+	let mut manifest = fs::read_to_string(path)
+		.with_context(|| format!("Failed to read {}", path.display()))?
+		.parse::<Table>()
+		.with_context(|| format!("Failed to parse {}", path.display()))?;
+
+	Ok(FLEET.iter().filter_map(|key| Some((key.to_string(), manifest.remove(*key)?))).collect())
+}
+
+// The configuration file a workspace at `root` reads.
+pub fn file(root:&Path, path:Option<&Path>) -> PathBuf {
+	path.map_or_else(|| root.join(FILE), Path::to_path_buf)
+}
+
+fn merge(target:&mut Table, layer:Table) {
+	for (key, value) in layer {
+		match (target.get_mut(&key), value) {
+			(Some(Value::Table(target)), Value::Table(value)) => merge(target, value),
+			(_, value) => {
+				target.insert(key, value);
+			},
+		}
+	}
+}
+
+// `MAINTAIN_<SECTION>_<KEY>` replaces `key` under `[section]`, read as a TOML value when it parses
+// as one, e.g. `MAINTAIN_WORKSPACE_JOBS=8`, and as a string otherwise. Only keys that hold a
+// single value can be set this way.
+fn environment(config:&mut Table, variable:impl Iterator<Item=(String, String)>) {
+	for (name, value) in variable {
+		let Some(name) = name.strip_prefix(ENVIRONMENT) else {
+			continue;
+		};
+
+		let name = name.to_lowercase();
+
+		for (section, table) in config.iter_mut() {
+			let Some(key) = name.strip_prefix(&format!("{}_", section)) else {
+				continue;
+			};
+
+			let Value::Table(table) = table else {
+				continue;
+			};
+
+			if table.get(key).is_some_and(|current| !current.is_table() && !current.is_array()) {
+				table.insert(key.to_string(), parse(&value));
+			}
+		}
+	}
+}
+
+fn parse(value:&str) -> Value {
+	format!("value = {}", value)
+		.parse::<Table>()
+		.ok()
+		.and_then(|mut table| table.remove("value"))
+		.unwrap_or_else(|| Value::String(value.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+	use std::{fs, path::Path};
+
+	use super::Fn;
+	use crate::Fn::{Forge::Kind, Task::Test::directory};
+
+	#[test]
+	fn reads_the_fleet_of_build_toml_beneath_maintain_toml() {
+		let root = directory("config");
+
+		fs::create_dir_all(root.join("Other")).unwrap();
+
+		let manifest = r#"
+			owner = "owner"
+			exclude = ["owner/old"]
+
+			[forge.github]
+			kind = "local"
+			url = "/srv/git"
+
+			[limit]
+			forge = 1
+			push = 1
+		"#;
+
+		fs::write(root.join("Other/Build.toml"), manifest).unwrap();
+
+		fs::write(root.join("Maintain.toml"), "[limit]\npush = 3\n").unwrap();
+
+		let config = Fn(&root, None, Some(Path::new("Other"))).unwrap();
+
+		fs::remove_dir_all(&root).unwrap();
+
+		assert_eq!(config.workspace.cache, Path::new("Other"));
+
+		assert_eq!(config.exclude, ["owner/old"]);
+
+		assert_eq!(config.forge["github"].kind, Kind::Local);
+
+		assert_eq!((config.limit.forge, config.limit.push), (1, 3));
+	}
+
+	#[test]
+	fn rejects_timeouts_a_duration_cannot_hold() {
+		let root = directory("config-timeout");
+
+		for (section, message) in [
+			(
+				"[timeout]\npush = inf\n",
+				"timeout.push must be a positive number of seconds, not inf",
+			),
+			("[timeout]\nfetch = 1e300\n", "timeout.fetch must be a positive number of seconds"),
+			("[timeout]\ncommand = 0\n", "timeout.command must be a positive number of seconds"),
+			("[forge.mirror]\ntimeout = -1.0\n", "forge.mirror.timeout must be a positive number"),
+			("[report]\ntimeout = nan\n", "report.timeout must be a positive number of seconds"),
+		] {
+			fs::write(root.join("Maintain.toml"), section).unwrap();
+
+			let error = Fn(&root, None, None).unwrap_err().to_string();
+
+			assert!(error.starts_with(message), "{}", error);
+		}
+
+		fs::write(
+			root.join("Maintain.toml"),
+			"[timeout]\npush = 0.5\n[forge.mirror]\ntimeout = 60\n",
+		)
+		.unwrap();
+
+		let config = Fn(&root, None, None).unwrap();
+
+		fs::remove_dir_all(&root).unwrap();
+
+		assert_eq!(config.timeout.push, 0.5);
+	}
+}
@@ -0,0 +1,271 @@
+pub mod Load;
+
+use std::{collections::BTreeMap, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Fn::{
+	Cron::Daily::PIPELINE,
+	Forge::Endpoint,
+	Manifest::{Manifest, Repository, OWNER},
+	Task::{Limit::Limit, Retry, Timeout::Timeout},
+};
+
+// Configuration file at the workspace root.
+pub const FILE:&str = "Maintain.toml";
+
+// Prefix of the environment variables that override single values, e.g. `MAINTAIN_WORKSPACE_OWNER`
+// for `owner` under `[workspace]`.
+pub const ENVIRONMENT:&str = "MAINTAIN_";
+
+// Sections that Build.toml of the cache directory used to hold. They are still read from there,
+// beneath `Maintain.toml`.
+pub const FLEET:[&str; 5] = ["forge", "exclude", "retry", "limit", "timeout"];
+
+// Everything about the fleet that is not a list of repositories: defaults, then `Maintain.toml`,
+// then the environment, then the command line, each replacing what the one before set.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct Config {
+	pub workspace:Workspace,
+
+	pub module:Module,
+
+	pub rename:Rename,
+
+	// Fields `append detail` merges into every package.json, replacing the built-in ones.
+	pub detail:Option<serde_json::Value>,
+
+	// Settings for one repository by `owner/name` or bare name, on top of its manifest entry.
+	// `folder`, `branch`, `parent`, `forge` and `tag` replace the entry's, anything else becomes
+	// an override such as `[repository."owner/name".import]`.
+	pub repository:BTreeMap<String, toml::Table>,
+
+	// Steps of `daily`, in order unless `depends_on` says otherwise. A file that has any replaces
+	// all of them.
+	pub pipeline:Vec<Step>,
+
+	// What `daemon` runs and when.
+	pub schedule:Vec<Schedule>,
+
+	pub report:Report,
+
+	// Named forges, e.g. `[forge.mirror]` for a Forgejo instance. `github` is always available.
+	pub forge:BTreeMap<String, Endpoint>,
+
+	// `owner/name` globs, or regular expressions between slashes, that `cache get` leaves out.
+	pub exclude:Vec<String>,
+
+	// Retry policy by task name, e.g. `[retry.clone]`, replacing the one the task ships with.
+	pub retry:BTreeMap<String, Retry::Policy>,
+
+	pub limit:Limit,
+
+	pub timeout:Timeout,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			workspace:Workspace::default(),
+			module:Module::default(),
+			rename:Rename::default(),
+			detail:None,
+			repository:BTreeMap::new(),
+			pipeline:PIPELINE
+				.iter()
+				.map(|task| Step {
+					task:task.to_string(),
+					continue_on_error:true,
+					..Default::default()
+				})
+				.collect(),
+			schedule:Vec::new(),
+			report:Report::default(),
+			forge:BTreeMap::new(),
+			exclude:Vec::new(),
+			retry:BTreeMap::new(),
+			limit:Limit::default(),
+			timeout:Timeout::default(),
+		}
+	}
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct Workspace {
+	// Organization of bare manifest entries, unless Build.toml names one.
+	pub owner:String,
+
+	// Relative to the workspace root, like every path below.
+	pub cache:PathBuf,
+
+	// Where repositories are cloned into their folders.
+	pub checkout:PathBuf,
+
+	pub state:PathBuf,
+
+	// Repositories a parallel task works on at once, 0 for one per CPU.
+	pub jobs:usize,
+}
+
+impl Default for Workspace {
+	fn default() -> Self {
+		Self {
+			owner:OWNER.to_string(),
+			cache:PathBuf::from("Cache/Repository"),
+			checkout:PathBuf::from("."),
+			state:PathBuf::from("Cache/Run"),
+			jobs:0,
+		}
+	}
+}
+
+// The superproject `module git` writes a `.gitmodules` for.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct Module {
+	pub output:PathBuf,
+
+	// Directory of the superproject the repositories are checked out under.
+	pub prefix:String,
+}
+
+impl Default for Module {
+	fn default() -> Self {
+		Self { output:PathBuf::from("../.gitmodules"), prefix:"Application".to_string() }
+	}
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct Rename {
+	// Replaced in the PascalCase name `rename repo` computes, e.g. `vscode = "Land"`.
+	pub replace:BTreeMap<String, String>,
+}
+
+impl Default for Rename {
+	fn default() -> Self {
+		Self { replace:BTreeMap::from([("vscode".to_string(), "Land".to_string())]) }
+	}
+}
+
+// One step of the pipeline: a registered task with its own arguments and selection.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct Step {
+	// What `depends_on`, `--from`, `--until` and `--only-step` call the step, the task by default.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub name:Option<String>,
+
+	pub task:String,
+
+	// Repositories a step before failed for are skipped by the steps depending on it.
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub depends_on:Vec<String>,
+
+	// Narrow the selection of the command line, like `--only`, `--except` and `--filter`.
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub only:Vec<String>,
+
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub except:Vec<String>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub filter:Option<String>,
+
+	// Whether the steps after it still run for a repository it failed for. Steps depending on it
+	// skip that repository either way.
+	pub continue_on_error:bool,
+
+	// Replace the defaults of the task, e.g. `args = { depth = 0 }` for `clone`.
+	#[serde(skip_serializing_if = "toml::Table::is_empty")]
+	pub args:toml::Table,
+}
+
+impl Step {
+	pub fn name(&self) -> &str {
+		self.name.as_deref().unwrap_or(&self.task)
+	}
+}
+
+// Where the report of a run goes besides `Report.md` and `Report.html` in the state directory.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct Report {
+	// Receives the report of every `daily` that is not a dry run as a JSON POST, with the Markdown
+	// as `text`. Empty for none.
+	pub webhook:String,
+
+	// Seconds the webhook has to answer.
+	pub timeout:f64,
+}
+
+impl Default for Report {
+	fn default() -> Self {
+		Self { webhook:String::new(), timeout:30.0 }
+	}
+}
+
+// A command line of the tool that `daemon` runs whenever `cron` comes due.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct Schedule {
+	pub name:String,
+
+	// `minute hour day month weekday` in local time, e.g. `0 3 * * *`, or with seconds in front.
+	pub cron:String,
+
+	// Arguments after `maintain`, e.g. `["daily", "--if-changed"]`.
+	pub command:Vec<String>,
+}
+
+impl Config {
+	// Hands the forges, limits and timeouts over to `manifest`, which tasks take them from.
+	pub fn fleet(&self, manifest:&mut Manifest) {
+		manifest.forge = self.forge.clone();
+
+		manifest.exclude = self.exclude.clone();
+
+		manifest.retry = self.retry.clone();
+
+		manifest.limit = self.limit.clone();
+
+		manifest.timeout = self.timeout.clone();
+	}
+
+	// Applies the `[repository."<name>"]` sections to the manifest entry they name.
+	pub fn apply(&self, repository:&mut Repository) {
+		let section = self
+			.repository
+			.get(&repository.full_name())
+			.or_else(|| self.repository.get(&repository.name));
+
+		let Some(section) = section else {
+			return;
+		};
+
+		for (key, value) in section {
+			let text = value.as_str().map(str::to_string);
+
+			match key.as_str() {
+				"folder" => repository.folder = text,
+				"branch" => repository.branch = text,
+				"parent" => repository.parent = text,
+				"forge" => repository.forge = text,
+				"tag" => {
+					let tag = value.as_array().into_iter().flatten().filter_map(|tag| tag.as_str());
+
+					for tag in tag {
+						if !repository.has_tag(tag) {
+							repository.tag.push(tag.to_string());
+						}
+					}
+				},
+				_ => {
+					repository.overrides.insert(key.clone(), value.clone());
+				},
+			}
+		}
+	}
+}
@@ -1,78 +1,102 @@
 use std::process::Command;
 
-fn main() {
-	// Repository list
-	let repositories = vec![
-		"microsoft/applicationinsights-vscode",
-		"microsoft/azure-boards-vscode",
-		// Add more repositories here...
-	];
-
-	// Process each repository
-	repositories.iter().for_each(|repository| {
-		// Run the script for each repository
-		if let Err(err) = script(repository) {
-			eprintln!("Error processing repository {}: {}", repository, err);
-		}
-	});
+use anyhow::Result;
+use serde::Deserialize;
+
+use crate::Fn::Task::{RepoContext, Task, TaskOutcome};
+
+// Points `origin` at an SSH URL and `remote` at the parent repository.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigureRepository {
+	pub remote:String,
 }
 
-fn script(repository:&str) -> Result<(), Box<dyn std::error::Error>> {
-	// Compute directory path
-	let directory = format!("/path/to/Cache/Repository/{}", repository);
+impl Default for ConfigureRepository {
+	fn default() -> Self {
+		Self { remote:"upstream".to_string() }
+	}
+}
+
+impl Task for ConfigureRepository {
+	fn name(&self) -> &'static str {
+		"configure"
+	}
+
+	fn description(&self) -> &'static str {
+		"Add the parent as a remote"
+	}
+
+	fn applies(&self, context:&RepoContext) -> bool {
+		context.is_cloned()
+	}
+
+	fn parallel(&self) -> bool {
+		true
+	}
+
+	fn run(&self, context:&RepoContext) -> Result<TaskOutcome> {
+		Ok(script(context, &self.remote)?.into())
+	}
+}
 
-	// Change directory
-	let output = Command::new("sh").arg("-c").arg(format!("cd {}", directory)).output()?;
+fn script(context:&RepoContext, remote:&str) -> Result<bool> {
+	let repository = context.repository;
 
 	// Print current directory
-	println!("{}", std::str::from_utf8(&output.stdout)?);
+	context.log(context.path.display());
+
+	let url = |name:&str| -> Result<Option<String>> {
+		let output = context.query(Command::new("git").args(["remote", "get-url", name]))?;
+
+		Ok(output.success().then(|| output.stdout.trim().to_string()))
+	};
+
+	let mut changed = false;
 
 	// Get origin URL
-	let output = Command::new("git").args(&["remote", "get-url", "origin"]).output()?;
-	let origin = String::from_utf8(output.stdout)?.trim().to_string();
-	let origin = origin.replace("git@github.com:", "ssh://git@github.com/");
-
-	// Set origin URL
-	Command::new("git").args(&["remote", "set-url", "origin", &origin]).output()?;
-
-	// Print origin URL
-	println!("Origin: {}", origin);
-
-	// Get upstream URL
-	let output = Command::new("gh").args(&["repo", "view", "--json", "parent"]).output()?;
-	let upstream_json = String::from_utf8(output.stdout)?;
-	let upstream = match serde_json::from_str::<serde_json::Value>(&upstream_json) {
-		Ok(json) => {
-			let owner = json.get("parent").and_then(|parent| {
-				parent
-					.get("owner")
-					.and_then(|owner| owner.get("login"))
-					.and_then(|login| login.as_str())
-			});
-			let name = json
-				.get("parent")
-				.and_then(|parent| parent.get("name").and_then(|name| name.as_str()));
-			match (owner, name) {
-				(Some(owner), Some(name)) => {
-					Some(format!("ssh://git@github.com/{}/{}.git", owner, name))
-				},
-				_ => None,
-			}
-		},
-		Err(_) => None,
+	if let Some(current) = url("origin")? {
+		let origin = current.replace("git@github.com:", "ssh://git@github.com/");
+
+		// Set origin URL
+		if origin != current {
+			context.execute(Command::new("git").args(["remote", "set-url", "origin", &origin]))?;
+
+			changed = true;
+		}
+
+		// Print origin URL
+		context.log(format!("Origin: {}", origin));
+	}
+
+	// Get upstream URL, preferring the parent recorded in the manifest
+	let upstream = match repository.parent() {
+		Some((owner, name)) => Some(context.forge()?.url(owner, name)),
+		None => context
+			.lookup(|forge| forge.parent(&repository.owner, &repository.name))?
+			.map(|parent| parent.url),
 	};
 
 	// Set upstream URL if exists
 	if let Some(upstream) = upstream {
 		// Print upstream URL
-		println!("Upstream: {}", upstream);
+		context.log(format!("Upstream: {}", upstream));
+
+		match url(remote)? {
+			Some(current) if current == upstream => {},
+			Some(_) => {
+				context
+					.execute(Command::new("git").args(["remote", "set-url", remote, &upstream]))?;
 
-		// Remove existing upstream remote
-		Command::new("git").args(&["remote", "remove", "upstream"]).output()?;
+				changed = true;
+			},
+			None => {
+				context.execute(Command::new("git").args(["remote", "add", remote, &upstream]))?;
 
-		// Add new upstream remote
-		Command::new("git").args(&["remote", "add", "upstream", &upstream]).output()?;
+				changed = true;
+			},
+		}
 	}
 
-	Ok(())
+	Ok(changed)
 }
@@ -0,0 +1,314 @@
+use std::{
+	collections::BTreeMap,
+	fs,
+	path::Path,
+	process::{Command, ExitStatus},
+	str::FromStr,
+	thread,
+	time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::Fn::{
+	Config::Schedule,
+	Task::Cancel,
+	Workspace::{Lock, Workspace},
+};
+
+// When every schedule last ran and runs next, inside the workspace state directory.
+pub const FILE:&str = "Schedule.json";
+
+// Held by the daemon for as long as it runs, so a workspace has one at most.
+pub const LOCK:&str = "Daemon.lock";
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Record {
+	// The expression `next` came from, a changed one starts over.
+	pub cron:String,
+
+	pub last:Option<DateTime<Local>>,
+
+	// How the last run ended, e.g. `exit 0` or `skipped, another run holds the workspace`.
+	pub outcome:Option<String>,
+
+	pub next:Option<DateTime<Local>>,
+}
+
+// Runs every `[[schedule]]` when it comes due, one at a time and each as a process of its own
+// with `argument` ahead of its command, until Ctrl-C. Times that pass while another run is going
+// are skipped; one missed while the daemon was down runs as soon as it starts.
+pub fn Fn(workspace:&Workspace, argument:&[String]) -> Result<()> {
+	if workspace.config.schedule.is_empty() {
+		bail!("Nothing to run, the configuration has no [[schedule]]");
+	}
+
+	let Some(_lock) = Lock::Lock::acquire(&workspace.state.join(LOCK))? else {
+		bail!("Another daemon runs this workspace");
+	};
+
+	let schedule = workspace
+		.config
+		.schedule
+		.iter()
+		.map(|schedule| {
+			parse(&schedule.cron)
+				.with_context(|| format!("Invalid cron expression for schedule {}", schedule.name))
+				.map(|cron| (schedule, cron))
+		})
+		.collect::<Result<Vec<_>>>()?;
+
+	let path = workspace.state.join(FILE);
+
+	let mut record = load(&path)?;
+
+	for (schedule, cron) in &schedule {
+		let entry = record.entry(schedule.name.clone()).or_default();
+
+		if entry.cron != schedule.cron || entry.next.is_none() {
+			entry.cron = schedule.cron.clone();
+
+			entry.next = cron.upcoming(Local).next();
+		}
+
+		println!("{}: next run {}", schedule.name, time(entry.next));
+	}
+
+	save(&path, &record)?;
+
+	while !Cancel::is_cancelled() {
+		for (schedule, cron) in &schedule {
+			let entry = record.get_mut(&schedule.name).expect("Cannot get the schedule record!");
+
+			let Some(due) = entry.next.filter(|next| *next <= Local::now()) else {
+				continue;
+			};
+
+			entry.last = Some(Local::now());
+
+			entry.outcome = Some(run(schedule, argument));
+
+			let now = Local::now();
+
+			let missed = cron.after(&due).take_while(|time| *time <= now).count();
+
+			if missed > 0 {
+				println!("{}: skipped {} times that came due while busy", schedule.name, missed);
+			}
+
+			entry.next = cron.after(&now).next();
+
+			println!(
+				"{}: {}, next run {}",
+				schedule.name,
+				entry.outcome.as_deref().unwrap_or_default(),
+				time(entry.next)
+			);
+
+			save(&path, &record)?;
+
+			if Cancel::is_cancelled() {
+				break;
+			}
+		}
+
+		thread::sleep(Duration::from_secs(1));
+	}
+
+	println!("Daemon stopped.");
+
+	Ok(())
+}
+
+// Crontab has five fields, the `cron` crate wants the seconds in front as well. It also counts
+// weekdays from 1 for Sunday where crontab counts from 0 or 7, so numeric weekdays of a crontab
+// expression become names. An expression with seconds is taken as the crate reads it.
+fn parse(expression:&str) -> Result<cron::Schedule> {
+	let field:Vec<&str> = expression.split_whitespace().collect();
+
+	let expression = if field.len() == 5 {
+		format!("0 {} {}", field[..4].join(" "), weekday(field[4])?)
+	} else {
+		expression.to_string()
+	};
+
+	Ok(cron::Schedule::from_str(&expression)?)
+}
+
+const WEEKDAY:[&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+// The crontab weekday field with every day given by number spelled out, e.g. `5-7` as
+// `FRI,SAT,SUN`. Items already given by name are left as they are.
+fn weekday(field:&str) -> Result<String> {
+	if field == "*" || field == "?" {
+		return Ok(field.to_string());
+	}
+
+	let mut item = Vec::new();
+
+	for current in field.split(',') {
+		if current.chars().any(|character| character.is_ascii_alphabetic()) {
+			item.push(current.to_string());
+
+			continue;
+		}
+
+		let (range, step) = match current.split_once('/') {
+			Some((range, step)) => (range, Some(step)),
+			None => (current, None),
+		};
+
+		let number = |value:&str| -> Result<usize> {
+			match value.parse::<usize>() {
+				Ok(value) if value <= 7 => Ok(value),
+				_ => bail!("Invalid weekday {} in {}", value, field),
+			}
+		};
+
+		let (start, end) = match (range, range.split_once('-')) {
+			("*", _) => (0, 6),
+			(_, Some((start, end))) => (number(start)?, number(end)?),
+			// `5/2` runs from 5 to the end of the week
+			(start, None) => (number(start)?, if step.is_some() { 7 } else { number(start)? }),
+		};
+
+		let step = match step.map(str::parse::<usize>) {
+			None => 1,
+			Some(Ok(step)) if step > 0 => step,
+			Some(_) => bail!("Invalid weekday step in {}", field),
+		};
+
+		if start > end {
+			bail!("Weekday range {} runs backwards", current);
+		}
+
+		for day in (start..=end).step_by(step) {
+			let name = WEEKDAY[day % 7].to_string();
+
+			if !item.contains(&name) {
+				item.push(name);
+			}
+		}
+	}
+
+	Ok(item.join(","))
+}
+
+// How the run ended, without ever failing the daemon.
+fn run(schedule:&Schedule, argument:&[String]) -> String {
+	println!("{}: maintain {}", schedule.name, schedule.command.join(" "));
+
+	let status = std::env::current_exe()
+		.and_then(|program| Command::new(program).args(argument).args(&schedule.command).status());
+
+	match status {
+		Ok(status) => outcome(status),
+		Err(error) => format!("failed to start, {}", error),
+	}
+}
+
+// The run takes the workspace itself rather than the daemon checking first, which would leave a
+// gap for a run started by hand between the check and the start. Finding it held, the run exits
+// with `Lock::BUSY` and this one waits for the next time.
+fn outcome(status:ExitStatus) -> String {
+	match status.code() {
+		Some(Lock::BUSY) => "skipped, another run holds the workspace".to_string(),
+		Some(code) => format!("exit {}", code),
+		None => "ended by a signal".to_string(),
+	}
+}
+
+fn time(time:Option<DateTime<Local>>) -> String {
+	time.map_or_else(|| "never".to_string(), |time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+fn load(path:&Path) -> Result<BTreeMap<String, Record>> {
+	if !path.exists() {
+		return Ok(BTreeMap::new());
+	}
+
+	let content =
+		fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+
+	serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+fn save(path:&Path, record:&BTreeMap<String, Record>) -> Result<()> {
+	fs::write(path, serde_json::to_string_pretty(record)? + "\n")
+		.with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod test {
+	use std::process::Command;
+
+	use chrono::{Datelike, Local, Weekday};
+
+	use super::{outcome, parse};
+	use crate::Fn::Workspace::Lock;
+
+	// The weekdays a schedule runs on over the next two weeks.
+	fn day(expression:&str) -> Vec<Weekday> {
+		let mut day:Vec<Weekday> = parse(expression)
+			.unwrap()
+			.upcoming(Local)
+			.take(14)
+			.map(|time| time.weekday())
+			.collect();
+
+		day.sort_by_key(Weekday::num_days_from_sunday);
+
+		day.dedup();
+
+		day
+	}
+
+	#[test]
+	fn counts_weekdays_like_crontab() {
+		assert_eq!(day("0 6 * * 0"), [Weekday::Sun]);
+
+		assert_eq!(day("0 6 * * 7"), [Weekday::Sun]);
+
+		assert_eq!(day("0 6 * * 1"), [Weekday::Mon]);
+
+		assert_eq!(day("0 6 * * 5-7"), [Weekday::Sun, Weekday::Fri, Weekday::Sat]);
+
+		assert_eq!(day("0 6 * * 1-5/2"), [Weekday::Mon, Weekday::Wed, Weekday::Fri]);
+
+		assert_eq!(day("0 6 * * 0,6"), [Weekday::Sun, Weekday::Sat]);
+
+		assert_eq!(day("0 6 * * */3"), [Weekday::Sun, Weekday::Wed, Weekday::Sat]);
+	}
+
+	#[test]
+	fn keeps_named_weekdays() {
+		assert_eq!(day("0 6 * * MON-WED"), [Weekday::Mon, Weekday::Tue, Weekday::Wed]);
+
+		assert_eq!(day("0 6 * * SUN,3"), [Weekday::Sun, Weekday::Wed]);
+
+		assert_eq!(day("0 0 6 * * *").len(), 7);
+	}
+
+	#[test]
+	fn rejects_invalid_weekdays() {
+		for expression in ["0 6 * * 8", "0 6 * * 5-2", "0 6 * * */0", "0 6 * * x1", "0 6 * *"] {
+			assert!(parse(expression).is_err(), "{} parsed", expression);
+		}
+	}
+
+	#[test]
+	fn skips_a_run_that_found_the_workspace_held() {
+		let status = |code:i32| Command::new("sh").arg("-c").arg(format!("exit {}", code)).status();
+
+		assert_eq!(
+			outcome(status(Lock::BUSY).unwrap()),
+			"skipped, another run holds the workspace"
+		);
+
+		assert_eq!(outcome(status(2).unwrap()), "exit 2");
+
+		assert_eq!(outcome(status(0).unwrap()), "exit 0");
+	}
+}
@@ -1,36 +1,335 @@
-use std::{env, fs, path::Path};
+use std::collections::{BTreeMap, BTreeSet};
 
-fn main() {
+use anyhow::{Context, Result};
+
+use crate::Fn::{
+	Cache::{self, Snapshot},
+	Config::Step,
+	Cron::Pipeline,
+	Select::Selector,
+	Task::{Journal, Registry::Registry, Run, Summary::Entry, Task, TaskOutcome},
+	Workspace::Workspace,
+};
+
+// Journal entry of the inventory refresh that opens every run.
+const INVENTORY:&str = "cache get";
+
+// Registered tasks of the default pipeline, in the order they depend on each other.
+pub const PIPELINE:[&str; 13] = [
+	"clone",
+	"module git",
+	"configure",
+	"setting",
+	"rename repo",
+	"rename branch",
+	"sync",
+	"clean detail",
+	"move license",
+	"move package",
+	"move src",
+	"append detail",
+	"sort detail",
+];
+
+// Runs the steps of `[[pipeline]]` that `selection` takes. With `resume`, work the journal of the
+// last run has as finished is skipped and its failures are retried; the inventory is only
+// refreshed when that run did not get past it.
+pub fn Fn(
+	workspace:&Workspace,
+	if_changed:bool,
+	resume:bool,
+	selection:&Pipeline::Selection,
+) -> Result<Vec<Entry>> {
 	println!("Process: Daily.rs");
 
-	// Context: CodeEditorLand/Environment/Stream
-	let current = env::current_dir().expect("Failed to get current directory");
-
-	run_script(&current.join("Cache").join("Get.sh"));
-	run_script(&current.join("Clone").join("Repository.sh"));
-	run_script(&current.join("Module").join("Git.sh"));
-	run_script(&current.join("Configure").join("Repository.sh"));
-	run_script(&current.join("Setting").join("Repository.sh"));
-	run_script(&current.join("Rename").join("Repository.sh"));
-	run_script(&current.join("Rename").join("Branch.sh"));
-	run_script(&current.join("Sync").join("Repository.sh"));
-	run_script(&current.join("Clean").join("Repository.sh"));
-	run_script(&current.join("Clean").join("Detail.sh"));
-	run_script(&current.join("Move").join("license.sh"));
-	run_script(&current.join("Move").join("package.sh"));
-	run_script(&current.join("Move").join("src.sh"));
-	run_script(&current.join("Append").join("Detail.sh"));
-	run_script(&current.join("Sort").join("Detail.sh"));
-
-	// TODO: Execute Maintain Workflow
-	// Maintain Workflow
-}
+	// A broken pipeline fails before the inventory is touched
+	let pipeline = Pipeline::order(&workspace.config.pipeline, selection)?;
 
-fn run_script(script_path:&Path) {
-	if script_path.exists() {
-		println!("Running script: {:?}", script_path);
-	// TODO: Execute the script here
+	let task = pipeline
+		.iter()
+		.map(|step| {
+			Registry::create(&step.task, &step.args)
+				.with_context(|| format!("Pipeline step {}", step.name()))
+		})
+		.collect::<Result<Vec<_>>>()?;
+
+	let path = workspace.state.join(Journal::FILE);
+
+	let journal = if resume {
+		Journal::Journal::resume(&path, workspace.dry_run)?
 	} else {
-		println!("Script not found: {:?}", script_path);
+		Journal::Journal::create(&path, workspace.dry_run)?
+	};
+
+	match journal.finished(INVENTORY, "") {
+		Some(TaskOutcome::Skipped(_)) => {
+			println!("The resumed run stopped at an unchanged inventory, nothing to do.");
+
+			return Ok(Vec::new());
+		},
+		Some(_) => println!("Resuming the run in {}", path.display()),
+		None => {
+			if !inventory(workspace, if_changed, &journal)? {
+				return Ok(Vec::new());
+			}
+		},
+	}
+
+	steps(workspace, &pipeline, &task, &journal)
+}
+
+// Runs every step of `pipeline` with its task. A repository a step fails for is skipped by the
+// steps depending on it and, unless the step has `continue_on_error`, by every step after it; the
+// other repositories carry on.
+fn steps(
+	workspace:&Workspace,
+	pipeline:&[&Step],
+	task:&[Box<dyn Task>],
+	journal:&Journal::Journal,
+) -> Result<Vec<Entry>> {
+	let manifest = workspace.manifest()?;
+
+	let mut entry = Vec::new();
+
+	// Repositories each step failed for, including the ones it skipped for a failed dependency
+	let mut failed:BTreeMap<&str, BTreeSet<String>> = BTreeMap::new();
+
+	// Repositories the rest of the pipeline skips, with the step that failed for them
+	let mut stopped:BTreeMap<String, &str> = BTreeMap::new();
+
+	for (step, task) in pipeline.iter().zip(task) {
+		// The step only narrows what the command line selected
+		let mut scope = workspace.clone();
+
+		scope.selector = workspace.selector.narrow(Selector::new(
+			&step.only,
+			&step.except,
+			step.filter.as_deref(),
+		)?);
+
+		let selected:BTreeSet<String> =
+			scope.select(&manifest)?.iter().map(|repository| repository.full_name()).collect();
+
+		// Why each repository the step leaves out is left out
+		let mut blocked:BTreeMap<String, String> = BTreeMap::new();
+
+		for (repository, name) in &stopped {
+			if selected.contains(repository) {
+				blocked.insert(repository.clone(), format!("{} failed", name));
+			}
+		}
+
+		for repository in step
+			.depends_on
+			.iter()
+			.filter_map(|dependency| failed.get(dependency.as_str()))
+			.flatten()
+			.filter(|repository| selected.contains(*repository))
+		{
+			blocked
+				.entry(repository.clone())
+				.or_insert_with(|| format!("{} did not succeed", step.depends_on.join(", ")));
+		}
+
+		for (repository, reason) in &blocked {
+			entry.push(Entry::new(
+				step.name(),
+				repository.clone(),
+				TaskOutcome::Skipped(reason.clone()),
+				Default::default(),
+			));
+		}
+
+		let blocked:Vec<String> = blocked.into_keys().collect();
+
+		scope.selector = scope.selector.narrow(Selector::new(&[], &blocked, None)?);
+
+		let done = Run::Fn(&scope, step.name(), task.as_ref(), journal)?;
+
+		let failure:BTreeSet<String> = done
+			.iter()
+			.filter(|done| matches!(done.outcome, TaskOutcome::Failed(_) | TaskOutcome::Cancelled))
+			.map(|done| done.repository.clone())
+			.collect();
+
+		entry.extend(done);
+
+		// A task failing for a repository only holds back the rest of its pipeline when the step
+		// says so
+		if !step.continue_on_error {
+			for repository in &failure {
+				stopped.entry(repository.clone()).or_insert(step.name());
+			}
+		}
+
+		failed.insert(step.name(), failure.into_iter().chain(blocked).collect());
+	}
+
+	Ok(entry)
+}
+
+// Refreshes the inventory and tells whether the rest of the run should happen.
+fn inventory(workspace:&Workspace, if_changed:bool, journal:&Journal::Journal) -> Result<bool> {
+	let current = Cache::Get::Fn(workspace)?;
+
+	// With `--if-changed`, the rest of the run only happens when the inventory moved
+	if if_changed {
+		// A dry run left the cache alone, so the snapshot on disk is still the previous one
+		let diff = if workspace.dry_run {
+			Cache::Diff::Diff::new(
+				&Snapshot::Snapshot::load_or_default(&workspace.cache.join(Snapshot::FILE))?,
+				&current,
+			)
+		} else {
+			Cache::Diff::diff(&workspace.cache)?
+		};
+
+		if diff.is_empty() {
+			println!("Inventory unchanged, skipping the rest of the run.");
+
+			journal.record(&Entry::new(
+				INVENTORY,
+				String::new(),
+				TaskOutcome::Skipped("inventory unchanged".to_string()),
+				Default::default(),
+			))?;
+
+			return Ok(false);
+		}
+
+		print!("{}", diff);
+	}
+
+	journal.record(&Entry::new(
+		INVENTORY,
+		String::new(),
+		TaskOutcome::Changed,
+		Default::default(),
+	))?;
+
+	Ok(true)
+}
+
+#[cfg(test)]
+mod test {
+	use std::{fs, sync::Mutex};
+
+	use anyhow::{bail, Result};
+
+	use super::steps;
+	use crate::Fn::{
+		Config::Step,
+		Task::{Journal::Journal, RepoContext, Task, TaskOutcome, Test::Scratch},
+	};
+
+	// Fails for `fail` and records every repository it ran for.
+	struct Probe {
+		fail:&'static str,
+
+		ran:Mutex<Vec<String>>,
+	}
+
+	impl Probe {
+		fn boxed(fail:&'static str) -> Box<dyn Task> {
+			Box::new(Self { fail, ran:Mutex::new(Vec::new()) })
+		}
+	}
+
+	impl Task for Probe {
+		fn name(&self) -> &'static str {
+			"probe"
+		}
+
+		fn description(&self) -> &'static str {
+			"Probe"
+		}
+
+		fn run(&self, context:&RepoContext) -> Result<TaskOutcome> {
+			let repository = context.repository.full_name();
+
+			self.ran.lock().unwrap().push(repository.clone());
+
+			if repository == self.fail {
+				bail!("Failed on purpose");
+			}
+
+			Ok(TaskOutcome::Changed)
+		}
+	}
+
+	fn step(name:&str, continue_on_error:bool) -> Step {
+		Step {
+			name:Some(name.to_string()),
+			task:"probe".to_string(),
+			continue_on_error,
+			..Default::default()
+		}
+	}
+
+	fn outcome(entry:&[crate::Fn::Task::Summary::Entry], step:&str) -> Vec<(String, String)> {
+		entry
+			.iter()
+			.filter(|entry| entry.task == step)
+			.map(|entry| (entry.repository.clone(), entry.outcome.to_string()))
+			.collect()
+	}
+
+	#[test]
+	fn stops_only_the_repositories_a_step_failed_for() {
+		let scratch = Scratch::new("daily-stop");
+
+		fs::write(scratch.workspace.cache.join("Build.md"), "owner/name\nowner/other\n").unwrap();
+
+		let pipeline = [step("first", false), step("second", true), step("third", true)];
+
+		let mut dependent = step("fourth", true);
+
+		dependent.depends_on = vec!["second".to_string()];
+
+		let pipeline = [&pipeline[0], &pipeline[1], &pipeline[2], &dependent];
+
+		let task = [
+			Probe::boxed("owner/other"),
+			Probe::boxed("owner/name"),
+			Probe::boxed(""),
+			Probe::boxed(""),
+		];
+
+		let entry = steps(&scratch.workspace, &pipeline, &task, &Journal::default()).unwrap();
+
+		assert_eq!(
+			outcome(&entry, "first"),
+			[
+				("owner/name".to_string(), "changed".to_string()),
+				("owner/other".to_string(), "failed, Failed on purpose".to_string())
+			]
+		);
+
+		// The first step stops owner/other for the rest of the pipeline, and nothing else
+		assert_eq!(
+			outcome(&entry, "second"),
+			[
+				("owner/other".to_string(), "skipped, first failed".to_string()),
+				("owner/name".to_string(), "failed, Failed on purpose".to_string())
+			]
+		);
+
+		// The second step continues on error, so owner/name goes on with the steps not depending
+		// on it
+		assert_eq!(
+			outcome(&entry, "third"),
+			[
+				("owner/other".to_string(), "skipped, first failed".to_string()),
+				("owner/name".to_string(), "changed".to_string())
+			]
+		);
+
+		assert_eq!(
+			outcome(&entry, "fourth"),
+			[
+				("owner/name".to_string(), "skipped, second did not succeed".to_string()),
+				("owner/other".to_string(), "skipped, first failed".to_string())
+			]
+		);
 	}
 }
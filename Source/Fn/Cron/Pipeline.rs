@@ -0,0 +1,171 @@
+use std::collections::BTreeSet;
+
+use anyhow::{bail, Result};
+
+use crate::Fn::Config::Step;
+
+// Which steps of the pipeline a run takes, by step name. Empty takes all of them.
+#[derive(Clone, Debug, Default)]
+pub struct Selection {
+	// The first step to run, the ones ordered before it are left out.
+	pub from:Option<String>,
+
+	// The last step to run.
+	pub until:Option<String>,
+
+	// Only these steps, within `from` and `until`.
+	pub only:Vec<String>,
+}
+
+// The steps in the order they run: as listed, except that a step moves after everything it
+// depends on. Unknown names and cycles are errors rather than silently skipped work.
+pub fn order<'a>(step:&'a [Step], selection:&Selection) -> Result<Vec<&'a Step>> {
+	let mut name = BTreeSet::new();
+
+	for current in step {
+		if !name.insert(current.name()) {
+			bail!("Pipeline step {} is defined twice", current.name());
+		}
+	}
+
+	for current in step {
+		for dependency in &current.depends_on {
+			if !name.contains(dependency.as_str()) {
+				bail!("Pipeline step {} depends on unknown step {}", current.name(), dependency);
+			}
+		}
+	}
+
+	for requested in selection.from.iter().chain(&selection.until).chain(&selection.only) {
+		if !name.contains(requested.as_str()) {
+			bail!("Unknown pipeline step {}", requested);
+		}
+	}
+
+	let mut ordered:Vec<&Step> = Vec::new();
+
+	while ordered.len() < step.len() {
+		// The first listed step whose dependencies all ran before it
+		let next = step.iter().find(|current| {
+			!ordered.iter().any(|done| done.name() == current.name())
+				&& current
+					.depends_on
+					.iter()
+					.all(|dependency| ordered.iter().any(|done| done.name() == dependency))
+		});
+
+		let Some(next) = next else {
+			let rest:Vec<&str> = step
+				.iter()
+				.map(Step::name)
+				.filter(|current| !ordered.iter().any(|done| done.name() == *current))
+				.collect();
+
+			bail!("Pipeline steps {} depend on each other", rest.join(", "));
+		};
+
+		ordered.push(next);
+	}
+
+	if ordered.is_empty() {
+		return Ok(ordered);
+	}
+
+	let position = |requested:&Option<String>| {
+		requested
+			.as_ref()
+			.and_then(|requested| ordered.iter().position(|current| current.name() == requested))
+	};
+
+	let start = position(&selection.from).unwrap_or(0);
+
+	let end = position(&selection.until).unwrap_or(ordered.len().saturating_sub(1));
+
+	if start > end {
+		bail!(
+			"Pipeline step {} runs after {}",
+			selection.from.as_deref().unwrap_or_default(),
+			selection.until.as_deref().unwrap_or_default()
+		);
+	}
+
+	Ok(ordered[start..=end]
+		.iter()
+		.filter(|current| {
+			selection.only.is_empty() || selection.only.iter().any(|only| only == current.name())
+		})
+		.copied()
+		.collect())
+}
+
+#[cfg(test)]
+mod test {
+	use super::{order, Selection};
+	use crate::Fn::Config::Step;
+
+	// `task` depending on `depends_on`.
+	fn step(task:&str, depends_on:&[&str]) -> Step {
+		Step {
+			task:task.to_string(),
+			depends_on:depends_on.iter().map(|name| name.to_string()).collect(),
+			..Default::default()
+		}
+	}
+
+	fn names(step:&[Step], selection:&Selection) -> Vec<String> {
+		order(step, selection).unwrap().iter().map(|current| current.name().to_string()).collect()
+	}
+
+	#[test]
+	fn moves_steps_after_their_dependencies() {
+		let step = [step("push", &["build"]), step("clone", &[]), step("build", &["clone"])];
+
+		assert_eq!(names(&step, &Selection::default()), ["clone", "build", "push"]);
+	}
+
+	#[test]
+	fn keeps_the_listed_order_otherwise() {
+		let step = [step("clone", &[]), step("sort", &[]), step("build", &["clone"])];
+
+		assert_eq!(names(&step, &Selection::default()), ["clone", "sort", "build"]);
+	}
+
+	#[test]
+	fn narrows_to_the_selection() {
+		let step = [step("clone", &[]), step("sort", &[]), step("build", &[]), step("push", &[])];
+
+		let selection = |from:Option<&str>, until:Option<&str>, only:&[&str]| Selection {
+			from:from.map(str::to_string),
+			until:until.map(str::to_string),
+			only:only.iter().map(|name| name.to_string()).collect(),
+		};
+
+		assert_eq!(names(&step, &selection(Some("sort"), None, &[])), ["sort", "build", "push"]);
+
+		assert_eq!(names(&step, &selection(None, Some("sort"), &[])), ["clone", "sort"]);
+
+		assert_eq!(
+			names(&step, &selection(Some("sort"), Some("push"), &["push", "clone"])),
+			["push"]
+		);
+
+		assert!(order(&step, &selection(Some("push"), Some("clone"), &[])).is_err());
+
+		assert!(order(&step, &selection(Some("lint"), None, &[])).is_err());
+	}
+
+	#[test]
+	fn rejects_broken_pipelines() {
+		let named = Step { name:Some("clone".to_string()), ..step("sort", &[]) };
+
+		assert!(order(&[step("clone", &[]), named], &Selection::default()).is_err());
+
+		assert!(order(&[step("build", &["lint"])], &Selection::default()).is_err());
+
+		let cycle = [step("clone", &[]), step("build", &["push"]), step("push", &["build"])];
+
+		let error = order(&cycle, &Selection::default()).unwrap_err().to_string();
+
+		assert_eq!(error, "Pipeline steps build, push depend on each other");
+	}
+}
@@ -1 +1,3 @@
+pub mod Daemon;
 pub mod Daily;
+pub mod Pipeline;
@@ -0,0 +1,81 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+
+use crate::Fn::{
+	Forge::{Endpoint, Forge},
+	Manifest::{Manifest, Repository},
+};
+
+// Name of the GitHub forge every manifest has, whether or not it declares one.
+pub const DEFAULT:&str = "github";
+
+// Every forge a manifest declares, opened once and looked up per repository.
+pub struct Fleet {
+	forge:BTreeMap<String, (Endpoint, Box<dyn Forge>)>,
+}
+
+impl Fleet {
+	pub fn new(manifest:&Manifest) -> Result<Self> {
+		let mut endpoint = manifest.forge.clone();
+
+		endpoint.entry(DEFAULT.to_string()).or_default();
+
+		let mut forge = BTreeMap::new();
+
+		for (name, mut endpoint) in endpoint {
+			if endpoint.owner.is_empty() {
+				endpoint.owner.push(manifest.owner().to_string());
+			}
+
+			endpoint.timeout.get_or_insert(manifest.timeout.forge);
+
+			let opened = endpoint.open().with_context(|| format!("Cannot open forge {}", name))?;
+
+			forge.insert(name, (endpoint, opened));
+		}
+
+		Ok(Self { forge })
+	}
+
+	// The explicit `forge` of the entry, else the forge hosting its owner, else GitHub.
+	pub fn name<'a>(&'a self, repository:&'a Repository) -> &'a str {
+		if let Some(name) = &repository.forge {
+			return name;
+		}
+
+		if self.forge[DEFAULT].0.owner.contains(&repository.owner) {
+			return DEFAULT;
+		}
+
+		self.forge
+			.iter()
+			.find(|(_, (endpoint, _))| endpoint.owner.contains(&repository.owner))
+			.map_or(DEFAULT, |(name, _)| name)
+	}
+
+	pub fn get(&self, repository:&Repository) -> Result<&dyn Forge> {
+		let name = self.name(repository);
+
+		self.named(name).with_context(|| {
+			format!("{} refers to an undeclared forge {}", repository.full_name(), name)
+		})
+	}
+
+	pub fn named(&self, name:&str) -> Option<&dyn Forge> {
+		self.forge.get(name).map(|(_, forge)| forge.as_ref())
+	}
+
+	// Every `(forge name, forge, owner)` whose repositories make up the fleet.
+	pub fn owners(&self) -> Vec<(&str, &dyn Forge, &str)> {
+		self.forge
+			.iter()
+			.flat_map(|(name, (endpoint, forge))| {
+				endpoint
+					.owner
+					.iter()
+					.map(move |owner| (name.as_str(), forge.as_ref(), owner.as_str()))
+			})
+			.collect()
+	}
+}
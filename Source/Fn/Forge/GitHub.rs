@@ -0,0 +1,366 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use crate::Fn::Forge::{Forge, Http::Http, Repository, Setting, Status};
+
+pub const API:&str = "https://api.github.com";
+
+pub const VERSION:&str = "2022-11-28";
+
+// GitHub REST backend. The API URL can point at any stand-in that speaks the same protocol.
+pub struct GitHub {
+	http:Http,
+
+	// Git host behind the API, e.g. `github.com` for `https://api.github.com`.
+	host:String,
+}
+
+#[derive(Deserialize)]
+struct Owner {
+	login:String,
+}
+
+#[derive(Deserialize)]
+struct Parent {
+	full_name:String,
+}
+
+#[derive(Deserialize)]
+struct Response {
+	id:u64,
+
+	name:String,
+
+	owner:Owner,
+
+	#[serde(default)]
+	default_branch:Option<String>,
+
+	#[serde(default)]
+	fork:bool,
+
+	#[serde(default)]
+	archived:bool,
+
+	#[serde(default)]
+	parent:Option<Parent>,
+
+	#[serde(default)]
+	topics:Vec<String>,
+
+	#[serde(default)]
+	size:u64,
+
+	#[serde(default)]
+	pushed_at:Option<String>,
+
+	#[serde(default)]
+	ssh_url:Option<String>,
+
+	#[serde(default)]
+	clone_url:Option<String>,
+}
+
+impl From<Response> for Repository {
+	fn from(response:Response) -> Self {
+		Self {
+			id:response.id,
+			owner:response.owner.login,
+			name:response.name,
+			default_branch:response.default_branch.unwrap_or_default(),
+			fork:response.fork,
+			archived:response.archived,
+			parent:response.parent.map(|parent| parent.full_name),
+			topics:response.topics,
+			size:response.size,
+			pushed_at:response.pushed_at,
+			url:response.ssh_url.or(response.clone_url).unwrap_or_default(),
+		}
+	}
+}
+
+impl GitHub {
+	pub fn new(api:&str, token:Option<String>, timeout:Duration) -> Self {
+		let mut header = vec![
+			("Accept".to_string(), "application/vnd.github+json".to_string()),
+			("X-GitHub-Api-Version".to_string(), VERSION.to_string()),
+		];
+
+		if let Some(token) = token.filter(|token| !token.is_empty()) {
+			header.push(("Authorization".to_string(), format!("Bearer {}", token)));
+		}
+
+		let host = api.split_once("://").map_or(api, |(_, rest)| rest);
+
+		let host = host.split('/').next().unwrap_or(host);
+
+		Self {
+			http:Http::new(api, header, timeout),
+			host:host.strip_prefix("api.").unwrap_or(host).to_string(),
+		}
+	}
+}
+
+impl Forge for GitHub {
+	fn list(&self, owner:&str) -> Result<Vec<Repository>> {
+		let repositories = match self
+			.http
+			.paginate::<Response>(&format!("orgs/{}/repos?per_page=100&type=all", owner))
+		{
+			Err(error) if error.downcast_ref::<Status>().is_some_and(|s| s.code == 404) => {
+				self.http.paginate::<Response>(&format!("users/{}/repos?per_page=100", owner))?
+			},
+			repositories => repositories?,
+		};
+
+		// Listings leave out the parent, only the repository itself names it
+		repositories
+			.into_iter()
+			.map(|response| {
+				let mut repository = Repository::from(response);
+
+				if repository.fork && repository.parent.is_none() {
+					repository.parent = self.get(&repository.owner, &repository.name)?.parent;
+				}
+
+				Ok(repository)
+			})
+			.collect()
+	}
+
+	fn get(&self, owner:&str, name:&str) -> Result<Repository> {
+		Ok(self.http.json::<Response>("GET", &format!("repos/{}/{}", owner, name), None)?.into())
+	}
+
+	fn set_default_branch(&self, owner:&str, name:&str, branch:&str) -> Result<()> {
+		self.http.call(
+			"PATCH",
+			&format!("repos/{}/{}", owner, name),
+			Some(json!({ "default_branch": branch })),
+		)?;
+
+		Ok(())
+	}
+
+	fn rename(&self, owner:&str, name:&str, rename:&str) -> Result<()> {
+		self.http.call(
+			"PATCH",
+			&format!("repos/{}/{}", owner, name),
+			Some(json!({ "name": rename })),
+		)?;
+
+		Ok(())
+	}
+
+	fn fork(&self, owner:&str, name:&str, organization:&str) -> Result<Repository> {
+		Ok(self
+			.http
+			.json::<Response>(
+				"POST",
+				&format!("repos/{}/{}/forks", owner, name),
+				Some(json!({ "organization": organization })),
+			)?
+			.into())
+	}
+
+	fn edit(&self, owner:&str, name:&str, setting:&Setting) -> Result<()> {
+		let mut body = Map::new();
+
+		if let Some(description) = &setting.description {
+			body.insert("description".into(), json!(description));
+		}
+
+		if let Some(homepage) = &setting.homepage {
+			body.insert("homepage".into(), json!(homepage));
+		}
+
+		if let Some(has_issues) = setting.has_issues {
+			body.insert("has_issues".into(), json!(has_issues));
+		}
+
+		if let Some(has_wiki) = setting.has_wiki {
+			body.insert("has_wiki".into(), json!(has_wiki));
+		}
+
+		if let Some(has_projects) = setting.has_projects {
+			body.insert("has_projects".into(), json!(has_projects));
+		}
+
+		if !body.is_empty() {
+			self.http.call(
+				"PATCH",
+				&format!("repos/{}/{}", owner, name),
+				Some(Value::Object(body)),
+			)?;
+		}
+
+		if let Some(access_level) = &setting.actions_access {
+			self.http.call(
+				"PUT",
+				&format!("repos/{}/{}/actions/permissions/access", owner, name),
+				Some(json!({ "access_level": access_level })),
+			)?;
+		}
+
+		if let Some(star) = setting.star {
+			self.http.call(
+				if star { "PUT" } else { "DELETE" },
+				&format!("user/starred/{}/{}", owner, name),
+				None,
+			)?;
+		}
+
+		Ok(())
+	}
+
+	fn url(&self, owner:&str, name:&str) -> String {
+		format!("ssh://git@{}/{}/{}.git", self.host, owner, name)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::{
+		sync::atomic::{AtomicUsize, Ordering},
+		time::Duration,
+	};
+
+	use super::{GitHub, VERSION};
+	use crate::Fn::{
+		Forge::Forge,
+		Task::{
+			Retry::Policy,
+			Test::{Request, Server},
+		},
+	};
+
+	fn repository(name:&str) -> String {
+		format!(r#"{{"id":1,"name":"{}","owner":{{"login":"owner"}},"fork":false}}"#, name)
+	}
+
+	#[test]
+	fn lists_every_page_with_the_api_headers() {
+		let server = Server::new(|request:&Request| {
+			if request.path.contains("page=2") {
+				return (200, Vec::new(), format!("[{}]", repository("second")));
+			}
+
+			let next = format!(
+				"<http://{}/orgs/owner/repos?page=2>; rel=\"next\"",
+				request.header["host"]
+			);
+
+			(200, vec![("Link".to_string(), next)], format!("[{}]", repository("first")))
+		});
+
+		let github = GitHub::new(&server.url, Some("secret".to_string()), Duration::from_secs(5));
+
+		let name:Vec<_> =
+			github.list("owner").unwrap().into_iter().map(|repository| repository.name).collect();
+
+		assert_eq!(name, ["first", "second"]);
+
+		let received = server.received();
+
+		assert_eq!(received.len(), 2);
+
+		for request in &received {
+			assert_eq!(request.header["authorization"], "Bearer secret");
+
+			assert_eq!(request.header["x-github-api-version"], VERSION);
+
+			assert_eq!(request.header["accept"], "application/vnd.github+json");
+		}
+	}
+
+	#[test]
+	fn fills_in_the_parent_of_forks() {
+		let server = Server::new(|request:&Request| {
+			if request.path == "/repos/owner/fork" {
+				return (
+					200,
+					Vec::new(),
+					r#"{"id":2,"name":"fork","owner":{"login":"owner"},"fork":true,"parent":{"full_name":"upstream/fork"}}"#
+						.to_string(),
+				);
+			}
+
+			let fork = repository("fork").replace(r#""fork":false"#, r#""fork":true"#);
+
+			(200, Vec::new(), format!("[{},{}]", repository("own"), fork))
+		});
+
+		let github = GitHub::new(&server.url, None, Duration::from_secs(5));
+
+		let parent:Vec<_> = github
+			.list("owner")
+			.unwrap()
+			.into_iter()
+			.map(|repository| (repository.name, repository.parent))
+			.collect();
+
+		assert_eq!(
+			parent,
+			[("own".to_string(), None), ("fork".to_string(), Some("upstream/fork".to_string()))]
+		);
+
+		// Only the fork is asked about
+		assert_eq!(server.received().len(), 2);
+	}
+
+	#[test]
+	fn lists_a_user_when_there_is_no_organization() {
+		let server = Server::new(|request:&Request| {
+			if request.path.starts_with("/orgs/") {
+				(404, Vec::new(), r#"{"message":"Not Found"}"#.to_string())
+			} else {
+				(200, Vec::new(), format!("[{}]", repository("personal")))
+			}
+		});
+
+		let github = GitHub::new(&server.url, None, Duration::from_secs(5));
+
+		assert_eq!(github.list("owner").unwrap()[0].name, "personal");
+
+		assert!(server
+			.received()
+			.iter()
+			.all(|request| !request.header.contains_key("authorization")));
+	}
+
+	#[test]
+	fn waits_as_long_as_the_rate_limit_says() {
+		let count = AtomicUsize::new(0);
+
+		let server = Server::new(move |_:&Request| match count.fetch_add(1, Ordering::SeqCst) {
+			0 => (503, vec![("Retry-After".to_string(), "1".to_string())], String::new()),
+			1 => (
+				403,
+				vec![
+					("X-RateLimit-Remaining".to_string(), "0".to_string()),
+					("X-RateLimit-Reset".to_string(), "0".to_string()),
+				],
+				String::new(),
+			),
+			_ => (200, Vec::new(), repository("name")),
+		});
+
+		let github = GitHub::new(&server.url, None, Duration::from_secs(5));
+
+		let policy = Policy { attempts:3, delay:0.0, limit:60.0, jitter:false };
+
+		let start = std::time::Instant::now();
+
+		let repository = policy.run("test", || github.get("owner", "name")).unwrap();
+
+		assert_eq!(repository.name, "name");
+
+		assert_eq!(server.received().len(), 3);
+
+		// Only `Retry-After` asked for a wait, the rate limit was already reset
+		assert!(start.elapsed() >= Duration::from_secs(1));
+	}
+}
@@ -0,0 +1,303 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use crate::Fn::Forge::{Forge, Http::Http, Repository, Setting, Status};
+
+// Gitea and Forgejo share the same `/api/v1` REST API.
+pub struct Gitea {
+	http:Http,
+
+	url:String,
+}
+
+#[derive(Deserialize)]
+struct Owner {
+	login:String,
+}
+
+#[derive(Deserialize)]
+struct Parent {
+	full_name:String,
+}
+
+#[derive(Deserialize)]
+struct Response {
+	id:u64,
+
+	name:String,
+
+	owner:Owner,
+
+	#[serde(default)]
+	default_branch:Option<String>,
+
+	#[serde(default)]
+	fork:bool,
+
+	#[serde(default)]
+	archived:bool,
+
+	#[serde(default)]
+	parent:Option<Parent>,
+
+	#[serde(default)]
+	topics:Option<Vec<String>>,
+
+	#[serde(default)]
+	size:u64,
+
+	// Gitea does not report the last push separately from other updates.
+	#[serde(default)]
+	updated_at:Option<String>,
+
+	#[serde(default)]
+	ssh_url:Option<String>,
+
+	#[serde(default)]
+	clone_url:Option<String>,
+}
+
+impl From<Response> for Repository {
+	fn from(response:Response) -> Self {
+		Self {
+			id:response.id,
+			owner:response.owner.login,
+			name:response.name,
+			default_branch:response.default_branch.unwrap_or_default(),
+			fork:response.fork,
+			archived:response.archived,
+			parent:response.parent.map(|parent| parent.full_name),
+			topics:response.topics.unwrap_or_default(),
+			size:response.size,
+			pushed_at:response.updated_at,
+			url:response
+				.ssh_url
+				.filter(|url| !url.is_empty())
+				.or(response.clone_url)
+				.unwrap_or_default(),
+		}
+	}
+}
+
+impl Gitea {
+	// `url` is the instance root, e.g. `https://forgejo.example.com`.
+	pub fn new(url:&str, token:Option<String>, timeout:Duration) -> Self {
+		let mut header = vec![("Accept".to_string(), "application/json".to_string())];
+
+		if let Some(token) = token.filter(|token| !token.is_empty()) {
+			header.push(("Authorization".to_string(), format!("token {}", token)));
+		}
+
+		let url = url.trim_end_matches('/');
+
+		Self { http:Http::new(&format!("{}/api/v1", url), header, timeout), url:url.to_string() }
+	}
+
+	fn patch(&self, owner:&str, name:&str, body:Value) -> Result<()> {
+		self.http.call("PATCH", &format!("repos/{}/{}", owner, name), Some(body))?;
+
+		Ok(())
+	}
+}
+
+impl Forge for Gitea {
+	fn list(&self, owner:&str) -> Result<Vec<Repository>> {
+		let repositories =
+			match self.http.paginate::<Response>(&format!("orgs/{}/repos?limit=50", owner)) {
+				Err(error) if error.downcast_ref::<Status>().is_some_and(|s| s.code == 404) => {
+					self.http.paginate::<Response>(&format!("users/{}/repos?limit=50", owner))?
+				},
+				repositories => repositories?,
+			};
+
+		Ok(repositories.into_iter().map(Repository::from).collect())
+	}
+
+	fn get(&self, owner:&str, name:&str) -> Result<Repository> {
+		Ok(self.http.json::<Response>("GET", &format!("repos/{}/{}", owner, name), None)?.into())
+	}
+
+	fn set_default_branch(&self, owner:&str, name:&str, branch:&str) -> Result<()> {
+		self.patch(owner, name, json!({ "default_branch": branch }))
+	}
+
+	fn rename(&self, owner:&str, name:&str, rename:&str) -> Result<()> {
+		self.patch(owner, name, json!({ "name": rename }))
+	}
+
+	fn fork(&self, owner:&str, name:&str, organization:&str) -> Result<Repository> {
+		Ok(self
+			.http
+			.json::<Response>(
+				"POST",
+				&format!("repos/{}/{}/forks", owner, name),
+				Some(json!({ "organization": organization })),
+			)?
+			.into())
+	}
+
+	// Gitea has no per-repository actions access level, so `actions_access` is ignored here.
+	fn edit(&self, owner:&str, name:&str, setting:&Setting) -> Result<()> {
+		let mut body = Map::new();
+
+		if let Some(description) = &setting.description {
+			body.insert("description".into(), json!(description));
+		}
+
+		if let Some(homepage) = &setting.homepage {
+			body.insert("website".into(), json!(homepage));
+		}
+
+		if let Some(has_issues) = setting.has_issues {
+			body.insert("has_issues".into(), json!(has_issues));
+		}
+
+		if let Some(has_wiki) = setting.has_wiki {
+			body.insert("has_wiki".into(), json!(has_wiki));
+		}
+
+		if let Some(has_projects) = setting.has_projects {
+			body.insert("has_projects".into(), json!(has_projects));
+		}
+
+		if !body.is_empty() {
+			self.patch(owner, name, Value::Object(body))?;
+		}
+
+		if let Some(star) = setting.star {
+			self.http.call(
+				if star { "PUT" } else { "DELETE" },
+				&format!("user/starred/{}/{}", owner, name),
+				None,
+			)?;
+		}
+
+		Ok(())
+	}
+
+	fn url(&self, owner:&str, name:&str) -> String {
+		format!("{}/{}/{}.git", self.url, owner, name)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::time::Duration;
+
+	use serde_json::{json, Value};
+
+	use super::Gitea;
+	use crate::Fn::{
+		Forge::{Forge, Setting},
+		Task::Test::{Request, Server},
+	};
+
+	fn repository(name:&str) -> Value {
+		json!({ "id": 1, "name": name, "owner": { "login": "owner" }, "clone_url": "" })
+	}
+
+	#[test]
+	fn lists_every_page_of_a_user_with_the_token() {
+		let server = Server::new(|request:&Request| {
+			// Not an organization, so the user is asked next
+			if request.path.starts_with("/api/v1/orgs/") {
+				return (404, Vec::new(), r#"{"message":"not found"}"#.to_string());
+			}
+
+			if request.path.contains("page=2") {
+				return (200, Vec::new(), json!([repository("second")]).to_string());
+			}
+
+			let next = format!(
+				"<http://{}/api/v1/users/owner/repos?limit=50&page=2>; rel=\"next\"",
+				request.header["host"]
+			);
+
+			(200, vec![("Link".to_string(), next)], json!([repository("first")]).to_string())
+		});
+
+		let gitea = Gitea::new(&server.url, Some("secret".to_string()), Duration::from_secs(5));
+
+		let name:Vec<_> =
+			gitea.list("owner").unwrap().into_iter().map(|repository| repository.name).collect();
+
+		assert_eq!(name, ["first", "second"]);
+
+		let received = server.received();
+
+		let path:Vec<_> = received.iter().map(|request| request.path.as_str()).collect();
+
+		assert_eq!(
+			path,
+			[
+				"/api/v1/orgs/owner/repos?limit=50",
+				"/api/v1/users/owner/repos?limit=50",
+				"/api/v1/users/owner/repos?limit=50&page=2",
+			]
+		);
+
+		for request in &received {
+			assert_eq!(request.header["authorization"], "token secret");
+		}
+	}
+
+	#[test]
+	fn sends_no_authorization_without_a_token() {
+		let server = Server::new(|_| (200, Vec::new(), json!([]).to_string()));
+
+		Gitea::new(&server.url, Some(String::new()), Duration::from_secs(5)).list("owner").unwrap();
+
+		assert!(!server.received()[0].header.contains_key("authorization"));
+	}
+
+	#[test]
+	fn maps_settings_to_the_gitea_names() {
+		let server = Server::new(|_| (200, Vec::new(), "{}".to_string()));
+
+		let gitea = Gitea::new(&server.url, None, Duration::from_secs(5));
+
+		gitea
+			.edit(
+				"owner",
+				"name",
+				&Setting {
+					description:Some("Editor".to_string()),
+					homepage:Some("https://editor.land".to_string()),
+					has_wiki:Some(false),
+					actions_access:Some("organization".to_string()),
+					..Default::default()
+				},
+			)
+			.unwrap();
+
+		// Settings Gitea has no counterpart for send nothing at all
+		gitea
+			.edit(
+				"owner",
+				"name",
+				&Setting { actions_access:Some("none".to_string()), ..Default::default() },
+			)
+			.unwrap();
+
+		let received = server.received();
+
+		assert_eq!(received.len(), 1);
+
+		assert_eq!(
+			(received[0].method.as_str(), received[0].path.as_str()),
+			("PATCH", "/api/v1/repos/owner/name")
+		);
+
+		assert_eq!(
+			serde_json::from_str::<Value>(&received[0].body).unwrap(),
+			json!({
+				"description": "Editor",
+				"website": "https://editor.land",
+				"has_wiki": false,
+			})
+		);
+	}
+}
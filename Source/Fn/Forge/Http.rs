@@ -0,0 +1,127 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::Fn::Forge::Status;
+
+// JSON-over-HTTP plumbing shared by the REST backends.
+pub struct Http {
+	api:String,
+
+	header:Vec<(String, String)>,
+
+	agent:ureq::Agent,
+}
+
+impl Http {
+	// `timeout` bounds every call, from connecting to the last byte of the answer.
+	pub fn new(api:&str, header:Vec<(String, String)>, timeout:Duration) -> Self {
+		Self {
+			api:api.trim_end_matches('/').to_string(),
+			header,
+			agent:ureq::AgentBuilder::new()
+				.timeout_connect(timeout.min(Duration::from_secs(30)))
+				.timeout(timeout)
+				.build(),
+		}
+	}
+
+	pub fn call(&self, method:&str, path:&str, body:Option<Value>) -> Result<ureq::Response> {
+		let url = if path.starts_with("http://") || path.starts_with("https://") {
+			path.to_string()
+		} else {
+			format!("{}/{}", self.api, path.trim_start_matches('/'))
+		};
+
+		let mut request = self
+			.agent
+			.request(method, &url)
+			.set("User-Agent", concat!("Maintain/", env!("CARGO_PKG_VERSION")));
+
+		for (name, value) in &self.header {
+			request = request.set(name, value);
+		}
+
+		let response = match body {
+			Some(body) => request.send_json(body),
+			None => request.call(),
+		};
+
+		match response {
+			Ok(response) => Ok(response),
+			Err(ureq::Error::Status(code, response)) => Err(Status {
+				code,
+				method:method.to_string(),
+				url,
+				retry_after:retry_after(&response),
+				body:response.into_string().unwrap_or_default(),
+			}
+			.into()),
+			Err(error) => Err(anyhow::Error::new(error).context(format!("{} {}", method, url))),
+		}
+	}
+
+	pub fn json<T:DeserializeOwned>(
+		&self,
+		method:&str,
+		path:&str,
+		body:Option<Value>,
+	) -> Result<T> {
+		self.call(method, path, body)?
+			.into_json::<T>()
+			.with_context(|| format!("Failed to parse the response of {} {}", method, path))
+	}
+
+	// Follows `Link: <…>; rel="next"` until the last page.
+	pub fn paginate<T:DeserializeOwned>(&self, path:&str) -> Result<Vec<T>> {
+		let mut items = Vec::new();
+
+		let mut next = Some(path.to_string());
+
+		while let Some(path) = next.take() {
+			let response = self.call("GET", &path, None)?;
+
+			next = response.header("Link").and_then(next_page);
+
+			items.extend(
+				response
+					.into_json::<Vec<T>>()
+					.with_context(|| format!("Failed to parse the response of GET {}", path))?,
+			);
+		}
+
+		Ok(items)
+	}
+}
+
+fn next_page(link:&str) -> Option<String> {
+	link.split(',').find_map(|part| {
+		let (url, relation) = part.split_once(';')?;
+
+		relation
+			.split(';')
+			.any(|parameter| parameter.trim() == "rel=\"next\"")
+			.then(|| url.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+	})
+}
+
+// `Retry-After` in seconds, else the reset of a rate limit with nothing left, as GitHub sends it.
+fn retry_after(response:&ureq::Response) -> Option<Duration> {
+	if let Some(seconds) =
+		response.header("Retry-After").and_then(|value| value.trim().parse().ok())
+	{
+		return Some(Duration::from_secs(seconds));
+	}
+
+	if response.header("X-RateLimit-Remaining").map(str::trim) != Some("0") {
+		return None;
+	}
+
+	let reset:u64 = response.header("X-RateLimit-Reset")?.trim().parse().ok()?;
+
+	let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+
+	Some(Duration::from_secs(reset.saturating_sub(now)))
+}
@@ -0,0 +1,560 @@
+use std::{
+	fs,
+	path::{Path, PathBuf},
+	sync::Mutex,
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, SecondsFormat};
+use serde::{Deserialize, Serialize};
+
+use crate::Fn::Forge::{Forge, Repository, Setting};
+
+// Metadata file at the root of a local forge.
+pub const METADATA:&str = "Forge.toml";
+
+// A directory of bare repositories laid out as `<root>/<owner>/<name>.git`. What git itself
+// cannot tell, such as parents, topics and settings, lives in `<root>/Forge.toml`.
+pub struct Local {
+	root:PathBuf,
+
+	// Serialises read-modify-write cycles of the metadata file.
+	lock:Mutex<()>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+struct Entry {
+	// Kept across renames so the repository stays recognisable; derived from the name if unset.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	id:Option<u64>,
+
+	owner:String,
+
+	name:String,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	parent:Option<String>,
+
+	archived:bool,
+
+	topics:Vec<String>,
+
+	setting:Setting,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+struct Metadata {
+	repository:Vec<Entry>,
+}
+
+impl Metadata {
+	fn find(&self, owner:&str, name:&str) -> Option<&Entry> {
+		self.repository.iter().find(|entry| entry.owner == owner && entry.name == name)
+	}
+
+	fn entry(&mut self, owner:&str, name:&str) -> &mut Entry {
+		match self.repository.iter().position(|entry| entry.owner == owner && entry.name == name) {
+			Some(position) => &mut self.repository[position],
+			None => {
+				self.repository.push(Entry {
+					owner:owner.to_string(),
+					name:name.to_string(),
+					..Default::default()
+				});
+
+				self.repository.last_mut().unwrap()
+			},
+		}
+	}
+}
+
+impl Local {
+	pub fn new(root:&Path) -> Result<Self> {
+		if !root.is_dir() {
+			bail!("Local forge root {} is not a directory", root.display());
+		}
+
+		Ok(Self { root:root.canonicalize()?, lock:Mutex::new(()) })
+	}
+
+	pub fn path(&self, owner:&str, name:&str) -> PathBuf {
+		self.root.join(owner).join(format!("{}.git", name))
+	}
+
+	fn read(&self) -> Result<Metadata> {
+		let path = self.root.join(METADATA);
+
+		if !path.exists() {
+			return Ok(Metadata::default());
+		}
+
+		toml::from_str(&fs::read_to_string(&path)?)
+			.with_context(|| format!("Failed to parse {}", path.display()))
+	}
+
+	fn write(&self, metadata:&Metadata) -> Result<()> {
+		fs::write(self.root.join(METADATA), toml::to_string_pretty(metadata)?)?;
+
+		Ok(())
+	}
+
+	// Runs `change` against the metadata file while holding the lock, then saves it.
+	fn update<T>(&self, change:impl FnOnce(&mut Metadata) -> Result<T>) -> Result<T> {
+		let _guard = self.lock.lock().unwrap_or_else(|poison| poison.into_inner());
+
+		let mut metadata = self.read()?;
+
+		let result = change(&mut metadata)?;
+
+		self.write(&metadata)?;
+
+		Ok(result)
+	}
+
+	fn describe(&self, owner:&str, name:&str, entry:Option<&Entry>) -> Result<Repository> {
+		let path = self.path(owner, name);
+
+		let repository = git2::Repository::open_bare(&path)
+			.with_context(|| format!("{}/{} is not a bare repository", owner, name))?;
+
+		let head = repository.find_reference("HEAD")?;
+
+		let default_branch = head
+			.symbolic_target()
+			.and_then(|target| target.strip_prefix("refs/heads/"))
+			.unwrap_or_default()
+			.to_string();
+
+		let pushed_at = repository
+			.find_reference(&format!("refs/heads/{}", default_branch))
+			.and_then(|reference| reference.peel_to_commit())
+			.ok()
+			.and_then(|commit| DateTime::from_timestamp(commit.time().seconds(), 0))
+			.map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true));
+
+		let entry = entry.cloned().unwrap_or_default();
+
+		Ok(Repository {
+			id:entry.id.unwrap_or_else(|| id(owner, name)),
+			owner:owner.to_string(),
+			name:name.to_string(),
+			default_branch,
+			fork:entry.parent.is_some(),
+			archived:entry.archived,
+			parent:entry.parent,
+			topics:entry.topics,
+			size:size(&path) / 1024,
+			pushed_at,
+			url:self.url(owner, name),
+		})
+	}
+}
+
+impl Forge for Local {
+	fn list(&self, owner:&str) -> Result<Vec<Repository>> {
+		let directory = self.root.join(owner);
+
+		if !directory.is_dir() {
+			bail!("No owner {} under {}", owner, self.root.display());
+		}
+
+		let metadata = self.read()?;
+
+		let mut name = Vec::new();
+
+		for entry in fs::read_dir(&directory)? {
+			let entry = entry?;
+
+			if let Some(stem) =
+				entry.file_name().to_str().and_then(|file| file.strip_suffix(".git"))
+			{
+				// Redirects left by renames are not repositories of their own
+				if !entry.file_type()?.is_symlink() && entry.path().join("HEAD").is_file() {
+					name.push(stem.to_string());
+				}
+			}
+		}
+
+		name.sort();
+
+		name.iter().map(|name| self.describe(owner, name, metadata.find(owner, name))).collect()
+	}
+
+	// A renamed repository answers to its old name as well, with the new one.
+	fn get(&self, owner:&str, name:&str) -> Result<Repository> {
+		let name = match fs::read_link(self.path(owner, name)) {
+			Ok(target) => target
+				.file_name()
+				.and_then(|file| file.to_str())
+				.and_then(|file| file.strip_suffix(".git"))
+				.map(str::to_string)
+				.with_context(|| format!("{}/{} redirects nowhere", owner, name))?,
+			Err(_) => name.to_string(),
+		};
+
+		self.describe(owner, &name, self.read()?.find(owner, &name))
+	}
+
+	fn set_default_branch(&self, owner:&str, name:&str, branch:&str) -> Result<()> {
+		let repository = git2::Repository::open_bare(self.path(owner, name))?;
+
+		let reference = format!("refs/heads/{}", branch);
+
+		if repository.find_reference(&reference).is_err() {
+			bail!("{}/{} has no branch {}", owner, name, branch);
+		}
+
+		repository.set_head(&reference)?;
+
+		Ok(())
+	}
+
+	fn rename(&self, owner:&str, name:&str, rename:&str) -> Result<()> {
+		let from = self.path(owner, name);
+
+		let to = self.path(owner, rename);
+
+		// The redirect of an earlier rename gives way, as it does on the hosted forges
+		if to.is_symlink() {
+			fs::remove_file(&to)
+				.with_context(|| format!("Failed to remove the redirect {}", to.display()))?;
+		} else if to.exists() {
+			bail!("{}/{} already exists", owner, rename);
+		}
+
+		self.update(|metadata| {
+			fs::rename(&from, &to)
+				.with_context(|| format!("Failed to rename {}/{}", owner, name))?;
+
+			// Checkouts still pointing at the old name keep working, as after a rename on GitHub
+			#[cfg(unix)]
+			std::os::unix::fs::symlink(format!("{}.git", rename), &from)
+				.with_context(|| format!("Failed to redirect {}/{}", owner, name))?;
+
+			let entry = metadata.entry(owner, name);
+
+			entry.id.get_or_insert_with(|| id(owner, name));
+
+			entry.name = rename.to_string();
+
+			let (old, new) = (format!("{}/{}", owner, name), format!("{}/{}", owner, rename));
+
+			for entry in &mut metadata.repository {
+				if entry.parent.as_deref() == Some(old.as_str()) {
+					entry.parent = Some(new.clone());
+				}
+			}
+
+			Ok(())
+		})
+	}
+
+	fn fork(&self, owner:&str, name:&str, organization:&str) -> Result<Repository> {
+		let source = self.path(owner, name);
+
+		let target = self.path(organization, name);
+
+		let parent = format!("{}/{}", owner, name);
+
+		// Forking twice hands back the existing fork, as the hosted forges do.
+		if target.exists() {
+			let fork = self.get(organization, name)?;
+
+			if fork.parent.as_deref() != Some(parent.as_str()) {
+				bail!("{}/{} already exists and is not a fork of {}", organization, name, parent);
+			}
+
+			return Ok(fork);
+		}
+
+		if !source.exists() {
+			bail!("No repository {}", parent);
+		}
+
+		let repository = git2::Repository::init_bare(&target)?;
+
+		repository
+			.remote_anonymous(&self.url(owner, name))?
+			.fetch(&["+refs/heads/*:refs/heads/*", "+refs/tags/*:refs/tags/*"], None, None)
+			.with_context(|| format!("Failed to fork {} into {}", parent, organization))?;
+
+		let branch = self.get(owner, name)?.default_branch;
+
+		if !branch.is_empty() {
+			repository.set_head(&format!("refs/heads/{}", branch))?;
+		}
+
+		self.update(|metadata| {
+			metadata.entry(organization, name).parent = Some(parent.clone());
+
+			Ok(())
+		})?;
+
+		self.get(organization, name)
+	}
+
+	// Settings are only recorded, there is nothing to enforce them. Stars do not apply.
+	fn edit(&self, owner:&str, name:&str, setting:&Setting) -> Result<()> {
+		if !self.path(owner, name).exists() {
+			bail!("No repository {}/{}", owner, name);
+		}
+
+		self.update(|metadata| {
+			let current = &mut metadata.entry(owner, name).setting;
+
+			let Setting {
+				description,
+				homepage,
+				has_issues,
+				has_wiki,
+				has_projects,
+				actions_access,
+				star: _,
+			} = setting.clone();
+
+			current.description = description.or(current.description.take());
+
+			current.homepage = homepage.or(current.homepage.take());
+
+			current.has_issues = has_issues.or(current.has_issues);
+
+			current.has_wiki = has_wiki.or(current.has_wiki);
+
+			current.has_projects = has_projects.or(current.has_projects);
+
+			current.actions_access = actions_access.or(current.actions_access.take());
+
+			Ok(())
+		})
+	}
+
+	// A `file://` URL rather than a bare path so that shallow clones stay shallow.
+	fn url(&self, owner:&str, name:&str) -> String {
+		format!("file://{}", self.path(owner, name).display())
+	}
+}
+
+// Stable FNV-1a hash of `owner/name`, standing in for a forge-assigned id. Halved so that it fits
+// a TOML integer.
+fn id(owner:&str, name:&str) -> u64 {
+	format!("{}/{}", owner, name)
+		.bytes()
+		.fold(0xcbf29ce484222325, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3))
+		>> 1
+}
+
+fn size(path:&Path) -> u64 {
+	walkdir::WalkDir::new(path)
+		.into_iter()
+		.filter_map(|entry| entry.ok())
+		.filter_map(|entry| entry.metadata().ok())
+		.filter(|metadata| metadata.is_file())
+		.map(|metadata| metadata.len())
+		.sum()
+}
+
+#[cfg(test)]
+mod test {
+	use std::{
+		fs,
+		path::{Path, PathBuf},
+	};
+
+	use super::{Local, METADATA};
+	use crate::Fn::{
+		Forge::{Forge, Setting},
+		Task::Test::{directory, git},
+	};
+
+	// A local forge holding `owner/name` with a commit on `main` and on `next`.
+	fn forge(name:&str) -> (PathBuf, Local) {
+		let root = directory(name);
+
+		let work = root.join("work");
+
+		fs::create_dir_all(&work).unwrap();
+
+		git(&work, &["init", "-q", "-b", "main"]);
+
+		fs::write(work.join("README.md"), "# name\n").unwrap();
+
+		git(&work, &["add", "."]);
+
+		git(&work, &["commit", "-q", "-m", "First"]);
+
+		git(&work, &["branch", "next"]);
+
+		fs::create_dir_all(root.join("owner")).unwrap();
+
+		git(&root.join("owner"), &["clone", "-q", "--bare", &work.to_string_lossy(), "name.git"]);
+
+		let local = Local::new(&root).unwrap();
+
+		(root, local)
+	}
+
+	#[test]
+	fn redirects_a_renamed_repository() {
+		let root = directory("local-rename");
+
+		let owner = root.join("owner");
+
+		std::fs::create_dir_all(&owner).unwrap();
+
+		git(&owner, &["init", "-q", "--bare", "-b", "main", "old.git"]);
+
+		let local = Local::new(&root).unwrap();
+
+		let old = local.url("owner", "old");
+
+		local.rename("owner", "old", "new").unwrap();
+
+		let name:Vec<_> =
+			local.list("owner").unwrap().into_iter().map(|repository| repository.name).collect();
+
+		assert_eq!(name, ["new"]);
+
+		assert_eq!(local.get("owner", "old").unwrap().name, "new");
+
+		// A checkout with the old URL still reaches it
+		git(Path::new(&root), &["ls-remote", &old]);
+
+		// And the old name can be taken back
+		local.rename("owner", "new", "old").unwrap();
+
+		assert_eq!(local.get("owner", "new").unwrap().name, "old");
+
+		std::fs::remove_dir_all(&root).unwrap();
+	}
+
+	#[test]
+	fn lists_and_gets_bare_repositories() {
+		let (root, local) = forge("local-list");
+
+		git(&root.join("owner"), &["init", "-q", "--bare", "-b", "main", "empty.git"]);
+
+		// Neither a plain directory nor a redirect is a repository of its own
+		fs::create_dir_all(root.join("owner/plain.git")).unwrap();
+
+		#[cfg(unix)]
+		std::os::unix::fs::symlink("name.git", root.join("owner/redirect.git")).unwrap();
+
+		let list = local.list("owner").unwrap();
+
+		let name:Vec<_> = list.iter().map(|repository| repository.name.as_str()).collect();
+
+		assert_eq!(name, ["empty", "name"]);
+
+		let repository = local.get("owner", "name").unwrap();
+
+		assert_eq!(repository, list[1]);
+
+		assert_eq!(repository.default_branch, "main");
+
+		assert!(repository.pushed_at.is_some());
+
+		assert!(!repository.fork);
+
+		assert_eq!(repository.url, format!("file://{}", local.path("owner", "name").display()));
+
+		assert!(local.list("nobody").is_err());
+
+		assert!(local.get("owner", "missing").is_err());
+
+		fs::remove_dir_all(&root).unwrap();
+	}
+
+	#[test]
+	fn sets_the_default_branch() {
+		let (root, local) = forge("local-branch");
+
+		local.set_default_branch("owner", "name", "next").unwrap();
+
+		assert_eq!(local.get("owner", "name").unwrap().default_branch, "next");
+
+		assert!(local.set_default_branch("owner", "name", "missing").is_err());
+
+		fs::remove_dir_all(&root).unwrap();
+	}
+
+	#[test]
+	fn records_settings_on_top_of_earlier_ones() {
+		let (root, local) = forge("local-edit");
+
+		local
+			.edit(
+				"owner",
+				"name",
+				&Setting {
+					description:Some("Editor".to_string()),
+					has_wiki:Some(false),
+					..Default::default()
+				},
+			)
+			.unwrap();
+
+		local
+			.edit(
+				"owner",
+				"name",
+				&Setting { has_issues:Some(true), star:Some(true), ..Default::default() },
+			)
+			.unwrap();
+
+		let metadata = local.read().unwrap();
+
+		let setting = &metadata.find("owner", "name").unwrap().setting;
+
+		assert_eq!(setting.description.as_deref(), Some("Editor"));
+
+		assert_eq!(
+			(setting.has_wiki, setting.has_issues, setting.star),
+			(Some(false), Some(true), None)
+		);
+
+		assert!(local.edit("owner", "missing", &Setting::default()).is_err());
+
+		fs::remove_dir_all(&root).unwrap();
+	}
+
+	#[test]
+	fn forks_into_an_organization_once() {
+		let (root, local) = forge("local-fork");
+
+		let fork = local.fork("owner", "name", "organization").unwrap();
+
+		assert_eq!(fork.full_name(), "organization/name");
+
+		assert_eq!(fork.parent.as_deref(), Some("owner/name"));
+
+		assert!(fork.fork);
+
+		assert_eq!(fork.default_branch, "main");
+
+		// Every branch came along
+		let branch =
+			git(&local.path("organization", "name"), &["branch", "--format=%(refname:short)"]);
+
+		assert_eq!(branch.lines().collect::<Vec<_>>(), ["main", "next"]);
+
+		assert!(fs::read_to_string(root.join(METADATA))
+			.unwrap()
+			.contains("parent = \"owner/name\""));
+
+		// A second fork hands back the first
+		assert_eq!(local.fork("owner", "name", "organization").unwrap(), fork);
+
+		// A repository of the same name that is no fork is not taken for one
+		git(&root.join("organization"), &["init", "-q", "--bare", "-b", "main", "other.git"]);
+
+		git(&root.join("owner"), &["init", "-q", "--bare", "-b", "main", "other.git"]);
+
+		assert!(local.fork("owner", "other", "organization").is_err());
+
+		assert!(local.fork("owner", "missing", "organization").is_err());
+
+		fs::remove_dir_all(&root).unwrap();
+	}
+}
@@ -0,0 +1,217 @@
+pub mod Fleet;
+pub mod GitHub;
+pub mod Gitea;
+pub mod Http;
+pub mod Local;
+
+use std::{fmt, path::Path, time::Duration};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+// What a forge knows about one hosted repository.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct Repository {
+	pub id:u64,
+
+	pub owner:String,
+
+	pub name:String,
+
+	pub default_branch:String,
+
+	pub fork:bool,
+
+	pub archived:bool,
+
+	// `owner/name` of the upstream repository, when the forge reports one.
+	pub parent:Option<String>,
+
+	pub topics:Vec<String>,
+
+	pub size:u64,
+
+	pub pushed_at:Option<String>,
+
+	// Clone URL, preferring SSH where the forge offers it.
+	pub url:String,
+}
+
+impl Repository {
+	pub fn full_name(&self) -> String {
+		format!("{}/{}", self.owner, self.name)
+	}
+}
+
+// Repository settings a task wants applied. Fields left as `None` are not touched.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct Setting {
+	pub description:Option<String>,
+
+	pub homepage:Option<String>,
+
+	pub has_issues:Option<bool>,
+
+	pub has_wiki:Option<bool>,
+
+	pub has_projects:Option<bool>,
+
+	// Who may use this repository's actions and reusable workflows, e.g. `organization`.
+	pub actions_access:Option<String>,
+
+	pub star:Option<bool>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+	#[default]
+	GitHub,
+
+	#[serde(alias = "forgejo")]
+	Gitea,
+
+	// A directory of bare repositories, for offline fleets.
+	Local,
+}
+
+// One `[forge.<name>]` table of the manifest.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct Endpoint {
+	pub kind:Kind,
+
+	// API root for GitHub, instance root for Gitea and Forgejo, root directory for a local forge.
+	pub url:Option<String>,
+
+	// Name of the environment variable holding the access token.
+	pub token:Option<String>,
+
+	// Organizations and users whose repositories live on this forge.
+	pub owner:Vec<String>,
+
+	// Seconds one API call may take, `[timeout] forge` when left out.
+	pub timeout:Option<f64>,
+}
+
+impl Endpoint {
+	pub fn open(&self) -> Result<Box<dyn Forge>> {
+		let token = |fallback:&[&str]| match &self.token {
+			Some(variable) => std::env::var(variable).ok(),
+			None => fallback.iter().find_map(|variable| std::env::var(variable).ok()),
+		};
+
+		let timeout = Duration::from_secs_f64(self.timeout.unwrap_or(300.0).max(1.0));
+
+		Ok(match self.kind {
+			Kind::GitHub => Box::new(GitHub::GitHub::new(
+				&self
+					.url
+					.clone()
+					.or_else(|| std::env::var("GITHUB_API_URL").ok())
+					.unwrap_or_else(|| GitHub::API.to_string()),
+				token(&["GH_TOKEN", "GITHUB_TOKEN"]),
+				timeout,
+			)),
+			Kind::Gitea => {
+				let Some(url) = &self.url else {
+					bail!("A Gitea or Forgejo forge needs a url");
+				};
+
+				Box::new(Gitea::Gitea::new(url, token(&["GITEA_TOKEN", "FORGEJO_TOKEN"]), timeout))
+			},
+			Kind::Local => {
+				let Some(url) = &self.url else {
+					bail!("A local forge needs a url naming its root directory");
+				};
+
+				Box::new(Local::Local::new(Path::new(url.trim_start_matches("file://")))?)
+			},
+		})
+	}
+}
+
+pub trait Forge: Send+Sync {
+	// Every repository owned by an organization or user, across all pages.
+	fn list(&self, owner:&str) -> Result<Vec<Repository>>;
+
+	fn get(&self, owner:&str, name:&str) -> Result<Repository>;
+
+	fn parent(&self, owner:&str, name:&str) -> Result<Option<Repository>> {
+		match self.get(owner, name)?.parent {
+			Some(parent) => {
+				let (owner, name) = split(&parent)?;
+
+				Ok(Some(self.get(owner, name)?))
+			},
+			None => Ok(None),
+		}
+	}
+
+	fn set_default_branch(&self, owner:&str, name:&str, branch:&str) -> Result<()>;
+
+	fn rename(&self, owner:&str, name:&str, rename:&str) -> Result<()>;
+
+	// Forks `owner/name` into `organization` and returns the new fork.
+	fn fork(&self, owner:&str, name:&str, organization:&str) -> Result<Repository>;
+
+	fn edit(&self, owner:&str, name:&str, setting:&Setting) -> Result<()>;
+
+	// Where `git clone` finds `owner/name` on this forge, without asking the forge.
+	fn url(&self, owner:&str, name:&str) -> String;
+}
+
+// Non-success HTTP answer from a forge API, kept typed so callers can tell 404 from 5xx.
+#[derive(Debug)]
+pub struct Status {
+	pub code:u16,
+
+	pub method:String,
+
+	pub url:String,
+
+	pub body:String,
+
+	// How long the forge asked to be left alone, from `Retry-After` or an exhausted rate limit.
+	pub retry_after:Option<Duration>,
+}
+
+impl fmt::Display for Status {
+	fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{} {} returned {}: {}", self.method, self.url, self.code, self.body.trim())
+	}
+}
+
+impl std::error::Error for Status {}
+
+pub fn split(full_name:&str) -> Result<(&str, &str)> {
+	full_name
+		.split_once('/')
+		.filter(|(owner, name)| !owner.is_empty() && !name.is_empty())
+		.ok_or_else(|| anyhow::anyhow!("Expected owner/name, got {}", full_name))
+}
+
+// Extracts `owner/name` from `ssh://git@host/owner/name.git`, `git@host:owner/name.git`, an
+// HTTPS URL or a local path such as `/srv/git/owner/name.git`.
+pub fn parse_url(url:&str) -> Option<(String, String)> {
+	let path = url.trim().trim_end_matches('/').trim_end_matches(".git");
+
+	let path = match path.split_once("://") {
+		Some((_, rest)) => rest.split_once('/')?.1,
+		None => path.split_once(':').map_or(path, |(_, rest)| rest),
+	};
+
+	let mut segment = path.rsplit('/');
+
+	let name = segment.next()?;
+
+	let owner = segment.next()?;
+
+	if owner.is_empty() || name.is_empty() {
+		return None;
+	}
+
+	Some((owner.to_string(), name.to_string()))
+}
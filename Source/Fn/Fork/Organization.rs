@@ -1,32 +1,51 @@
-use std::process::Command;
-
-fn main() {
-	println!("Process: Fork/Organization.sh");
-
-	// List of repositories
-	let repositories = vec![
-		"microsoft/applicationinsights-vscode",
-		"microsoft/azure-boards-vscode",
-		// Add all repositories here...
-		"microsoft/vscodespaces-quickstart",
-		"microsoft/vscodetestcover",
-	];
-
-	// Fork each repository in parallel
-	repositories.par_iter().for_each(|repository| {
-		script(repository);
-	});
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::Fn::Task::{RepoContext, Task, TaskOutcome};
+
+// Forks the parent recorded in the manifest into the repository's own owner, unless
+// `organization` says otherwise.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ForkOrganization {
+	pub organization:Option<String>,
 }
 
-fn script(repository:&str) {
-	let output = Command::new("gh")
-		.arg("repo")
-		.arg("fork")
-		.arg(repository)
-		.arg("--org")
-		.arg("CodeEditorLand")
-		.output()
-		.expect("Failed to execute gh repo fork command");
-
-	println!("{}", String::from_utf8_lossy(&output.stdout));
+impl Task for ForkOrganization {
+	fn name(&self) -> &'static str {
+		"fork"
+	}
+
+	fn description(&self) -> &'static str {
+		"Fork parents into the organization"
+	}
+
+	fn applies(&self, context:&RepoContext) -> bool {
+		context.repository.parent().is_some()
+	}
+
+	fn parallel(&self) -> bool {
+		true
+	}
+
+	fn run(&self, context:&RepoContext) -> Result<TaskOutcome> {
+		let Some((owner, name)) = context.repository.parent() else {
+			return Ok(TaskOutcome::Skipped("no parent".to_string()));
+		};
+
+		let organization = self.organization.as_deref().unwrap_or(&context.repository.owner);
+
+		// Fork on the forge that hosts the fork
+		let fork = context
+			.remote(format!("fork {}/{} into {}", owner, name, organization), |forge| {
+				forge.fork(owner, name, organization)
+			})
+			.with_context(|| format!("Failed to fork {}/{}", owner, name))?;
+
+		if !context.is_dry_run() {
+			context.log(format!("Fork: {}", fork.full_name()));
+		}
+
+		Ok(TaskOutcome::Changed)
+	}
 }
@@ -0,0 +1,219 @@
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	fmt::Write,
+	mem,
+};
+
+use anyhow::Result;
+
+use crate::Fn::{
+	History::Store,
+	Task::Summary::{Entry, Summary},
+	Workspace::Workspace,
+};
+
+// Repositories listed under the slowest changes.
+const SLOWEST:usize = 10;
+
+// How the run `after` differs from the run `before`: time per task, the repositories that got
+// slowest, and every outcome that changed.
+pub fn Fn(workspace:&Workspace, before:&str, after:&str) -> Result<()> {
+	let before = Store::get(&workspace.state, before)?;
+
+	let after = Store::get(&workspace.state, after)?;
+
+	print!("{}", compare(&before, &after)?);
+
+	Ok(())
+}
+
+// What `Fn` prints, with columns as wide as what they hold.
+fn compare(before:&Summary, after:&Summary) -> Result<String> {
+	let mut text = String::new();
+
+	writeln!(text, "Comparing run {} with run {}\n", before.run, after.run)?;
+
+	let (earlier, later) = (total(before), total(after));
+
+	let mut time:Vec<(&str, Option<f64>, Option<f64>)> = earlier
+		.keys()
+		.chain(later.keys())
+		.copied()
+		.collect::<BTreeSet<_>>()
+		.into_iter()
+		.map(|task| (task, earlier.get(task).copied(), later.get(task).copied()))
+		.collect();
+
+	// Slower first
+	time.sort_by(|a, b| change(b.1, b.2).total_cmp(&change(a.1, a.2)));
+
+	let (earlier, later) = (index(before), index(after));
+
+	let mut slower:Vec<(&(&str, &str), f64, f64)> = later
+		.iter()
+		.filter_map(|(key, entry)| Some((key, earlier.get(key)?.duration, entry.duration)))
+		.filter(|(_, earlier, later)| later > earlier)
+		.collect();
+
+	slower.sort_by(|a, b| (b.2 - b.1).total_cmp(&(a.2 - a.1)));
+
+	slower.truncate(SLOWEST);
+
+	let outcome:Vec<_> = later
+		.iter()
+		.filter_map(|(key, entry)| Some((key, earlier.get(key)?, entry)))
+		.filter(|(_, earlier, later)| {
+			mem::discriminant(&earlier.outcome) != mem::discriminant(&later.outcome)
+		})
+		.collect();
+
+	// One width for the columns of every section, so they line up
+	let key:Vec<&(&str, &str)> =
+		slower.iter().map(|(key, ..)| *key).chain(outcome.iter().map(|(key, ..)| *key)).collect();
+
+	let task = time
+		.iter()
+		.map(|(task, ..)| task.len())
+		.chain(key.iter().map(|(task, _)| task.len()))
+		.chain(["Task".len()])
+		.max()
+		.unwrap_or_default();
+
+	let repository = key.iter().map(|(_, repository)| repository.len()).max().unwrap_or_default();
+
+	writeln!(text, "{:<task$}  {:>9}  {:>9}  Change", "Task", "Before", "After")?;
+
+	for (name, earlier, later) in time {
+		writeln!(
+			text,
+			"{:<task$}  {:>9}  {:>9}  {}",
+			name,
+			seconds(earlier),
+			seconds(later),
+			delta(earlier, later)
+		)?;
+	}
+
+	if !slower.is_empty() {
+		writeln!(text, "\nSlowest changes")?;
+
+		for (key, earlier, later) in slower {
+			writeln!(
+				text,
+				"{:<task$}  {:<repository$}  {:>7.1}s  {:>7.1}s  {}",
+				key.0,
+				key.1,
+				earlier,
+				later,
+				delta(Some(earlier), Some(later))
+			)?;
+		}
+	}
+
+	if !outcome.is_empty() {
+		writeln!(text, "\nOutcomes that changed")?;
+
+		for (key, earlier, later) in outcome {
+			writeln!(
+				text,
+				"{:<task$}  {:<repository$}  {} -> {}",
+				key.0,
+				key.1,
+				first(&earlier.outcome.to_string()),
+				first(&later.outcome.to_string())
+			)?;
+		}
+	}
+
+	Ok(text)
+}
+
+// Seconds every task took over all repositories.
+fn total(summary:&Summary) -> BTreeMap<&str, f64> {
+	let mut total = BTreeMap::new();
+
+	for entry in &summary.entry {
+		*total.entry(entry.task.as_str()).or_default() += entry.duration;
+	}
+
+	total
+}
+
+fn index(summary:&Summary) -> BTreeMap<(&str, &str), &Entry> {
+	summary
+		.entry
+		.iter()
+		.map(|entry| ((entry.task.as_str(), entry.repository.as_str()), entry))
+		.collect()
+}
+
+fn change(earlier:Option<f64>, later:Option<f64>) -> f64 {
+	later.unwrap_or_default() - earlier.unwrap_or_default()
+}
+
+fn seconds(time:Option<f64>) -> String {
+	time.map_or_else(|| "-".to_string(), |time| format!("{:.1}s", time))
+}
+
+fn delta(earlier:Option<f64>, later:Option<f64>) -> String {
+	match (earlier, later) {
+		(Some(earlier), Some(later)) if earlier > 0.0 => {
+			format!("{:+.1}s ({:+.0}%)", later - earlier, (later - earlier) / earlier * 100.0)
+		},
+		(Some(earlier), Some(later)) => format!("{:+.1}s", later - earlier),
+		(None, Some(_)) => "new".to_string(),
+		(Some(_), None) => "gone".to_string(),
+		(None, None) => String::new(),
+	}
+}
+
+fn first(text:&str) -> &str {
+	text.lines().next().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+	use super::compare;
+	use crate::Fn::{History::List::test::history, Task::TaskOutcome};
+
+	#[test]
+	fn lines_up_every_section() {
+		let history = history();
+
+		assert_eq!(
+			compare(&history[0], &history[1]).unwrap(),
+			"Comparing run 20261016-030000-000-41234 with run 20261017-030000-000-51234\n\
+			 \n\
+			 Task      Before      After  Change\n\
+			 sync        7.0s      15.5s  +8.5s (+121%)\n\
+			 clone      12.0s       2.0s  -10.0s (-83%)\n\
+			 \n\
+			 Slowest changes\n\
+			 sync   owner/Editor                 3.0s      9.0s  +6.0s (+200%)\n\
+			 sync   CodeEditorLand/SideView      4.0s      6.5s  +2.5s (+62%)\n\
+			 \n\
+			 Outcomes that changed\n\
+			 clone  owner/Editor             changed -> unchanged\n\
+			 sync   owner/Editor             unchanged -> failed, Failed to merge: conflict\n"
+		);
+	}
+
+	#[test]
+	fn tells_new_and_gone_tasks() {
+		let mut history = history();
+
+		history[1].entry.retain(|entry| entry.task == "sync");
+
+		history[1].entry[0].task = "build".to_string();
+
+		history[1].entry[0].outcome = TaskOutcome::Changed;
+
+		let text = compare(&history[0], &history[1]).unwrap();
+
+		assert!(text.contains("build          -       9.0s  new\n"), "{}", text);
+
+		assert!(text.contains("clone      12.0s          -  gone\n"), "{}", text);
+
+		assert!(!text.contains("Outcomes that changed"), "{}", text);
+	}
+}
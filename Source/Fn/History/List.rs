@@ -0,0 +1,294 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+use regex::Regex;
+
+use crate::Fn::{
+	Cache::Exclude,
+	History::Store,
+	Task::{
+		Summary::{Entry, Summary},
+		TaskOutcome,
+	},
+	Workspace::Workspace,
+};
+
+// Which recorded runs, or with a repository or task which of their entries, `list` prints.
+#[derive(Clone, Debug, Default)]
+pub struct Filter {
+	// Glob over `owner/name` or the bare name.
+	pub repository:Option<String>,
+
+	pub task:Option<String>,
+
+	// Only entries that changed the repository or found nothing to change.
+	pub succeeded:bool,
+
+	// Most recent first, at most this many.
+	pub limit:usize,
+}
+
+impl Filter {
+	fn is_entry(&self) -> bool {
+		self.repository.is_some() || self.task.is_some() || self.succeeded
+	}
+}
+
+// Lists recorded runs, most recent first, e.g. to find when a repository last synced with
+// `--repo owner/name --task sync --succeeded --limit 1`.
+pub fn Fn(workspace:&Workspace, filter:&Filter) -> Result<()> {
+	print!("{}", list(&Store::all(&workspace.state)?, filter)?);
+
+	Ok(())
+}
+
+// The table `Fn` prints for `history`, oldest run first. Columns are as wide as what they hold.
+fn list(history:&[Summary], filter:&Filter) -> Result<String> {
+	let mut table = String::new();
+
+	if history.is_empty() {
+		writeln!(table, "No runs recorded yet.")?;
+
+		return Ok(table);
+	}
+
+	if !filter.is_entry() {
+		let run:Vec<_> = history
+			.iter()
+			.rev()
+			.take(filter.limit)
+			.map(|summary| {
+				let outcome = summary.to_string().lines().last().unwrap_or_default().to_string();
+
+				(summary, outcome)
+			})
+			.collect();
+
+		let width = |column:fn(&(&Summary, String)) -> usize, title:&str| {
+			run.iter().map(column).chain([title.len()]).max().unwrap_or_default()
+		};
+
+		let id = width(|(summary, _)| summary.run.len(), "Run");
+
+		let started = width(|(summary, _)| summary.started.len(), "Started");
+
+		let outcome = width(|(_, outcome)| outcome.len(), "Outcome");
+
+		writeln!(
+			table,
+			"{:<id$}  {:<started$}  {:>8}  {:<outcome$}  Command",
+			"Run", "Started", "Time", "Outcome"
+		)?;
+
+		for (summary, text) in &run {
+			writeln!(
+				table,
+				"{:<id$}  {:<started$}  {:>7.1}s  {:<outcome$}  {}",
+				summary.run,
+				summary.started,
+				summary.duration(),
+				text,
+				summary.command
+			)?;
+		}
+
+		return Ok(table);
+	}
+
+	let repository:Option<Regex> =
+		filter.repository.as_deref().map(Exclude::compile).transpose()?;
+
+	let entry:Vec<(&Summary, &Entry)> = history
+		.iter()
+		.rev()
+		.flat_map(|summary| summary.entry.iter().rev().map(move |entry| (summary, entry)))
+		.filter(|(_, entry)| {
+			repository.as_ref().is_none_or(|pattern| {
+				pattern.is_match(&entry.repository)
+					|| entry
+						.repository
+						.split_once('/')
+						.is_some_and(|(_, name)| pattern.is_match(name))
+			})
+		})
+		.filter(|(_, entry)| filter.task.as_ref().is_none_or(|task| *task == entry.task))
+		.filter(|(_, entry)| {
+			!filter.succeeded
+				|| matches!(entry.outcome, TaskOutcome::Changed | TaskOutcome::Unchanged)
+		})
+		.take(filter.limit)
+		.collect();
+
+	let width = |column:fn(&(&Summary, &Entry)) -> usize, title:&str| {
+		entry.iter().map(column).chain([title.len()]).max().unwrap_or_default()
+	};
+
+	let id = width(|(summary, _)| summary.run.len(), "Run");
+
+	let started = width(|(summary, _)| summary.started.len(), "Started");
+
+	let task = width(|(_, entry)| entry.task.len(), "Task");
+
+	let name = width(|(_, entry)| entry.repository.len(), "Repository");
+
+	writeln!(
+		table,
+		"{:<id$}  {:<started$}  {:<task$}  {:<name$}  {:>8}  Outcome",
+		"Run", "Started", "Task", "Repository", "Time"
+	)?;
+
+	for (summary, entry) in entry {
+		let outcome = entry.outcome.to_string();
+
+		writeln!(
+			table,
+			"{:<id$}  {:<started$}  {:<task$}  {:<name$}  {:>7.1}s  {}",
+			summary.run,
+			summary.started,
+			entry.task,
+			entry.repository,
+			entry.duration,
+			outcome.lines().next().unwrap_or_default()
+		)?;
+	}
+
+	Ok(table)
+}
+
+#[cfg(test)]
+pub mod test {
+	use std::time::Duration;
+
+	use super::{list, Filter};
+	use crate::Fn::Task::{
+		Summary::{Entry, Summary},
+		TaskOutcome,
+	};
+
+	// Two daily runs a day apart, the later one failing to sync `owner/Editor`.
+	pub fn history() -> Vec<Summary> {
+		let entry = |task:&str, repository:&str, outcome:TaskOutcome, seconds:f64| {
+			Entry::new(task, repository.to_string(), outcome, Duration::from_secs_f64(seconds))
+		};
+
+		let mut first = Summary::new("20261016-030000-000-41234", "daily".to_string(), false);
+
+		first.started = "2026-10-16T03:00:00Z".to_string();
+
+		first.finished = "2026-10-16T03:02:00Z".to_string();
+
+		first.entry = vec![
+			entry("clone", "owner/Editor", TaskOutcome::Changed, 12.0),
+			entry("sync", "owner/Editor", TaskOutcome::Unchanged, 3.0),
+			entry("sync", "CodeEditorLand/SideView", TaskOutcome::Changed, 4.0),
+		];
+
+		let mut second =
+			Summary::new("20261017-030000-000-51234", "daily --if-changed".to_string(), false);
+
+		second.started = "2026-10-17T03:00:00Z".to_string();
+
+		second.finished = "2026-10-17T03:01:30Z".to_string();
+
+		second.entry = vec![
+			entry("clone", "owner/Editor", TaskOutcome::Unchanged, 2.0),
+			entry(
+				"sync",
+				"owner/Editor",
+				TaskOutcome::Failed(vec!["Failed to merge".to_string(), "conflict".to_string()]),
+				9.0,
+			),
+			entry("sync", "CodeEditorLand/SideView", TaskOutcome::Changed, 6.5),
+		];
+
+		vec![first, second]
+	}
+
+	fn filter(repository:Option<&str>, task:Option<&str>, succeeded:bool, limit:usize) -> Filter {
+		Filter {
+			repository:repository.map(str::to_string),
+			task:task.map(str::to_string),
+			succeeded,
+			limit,
+		}
+	}
+
+	// Where every line of `table` has `column` start, the title in the first and the value in the
+	// others.
+	fn start(table:&str, column:&str, value:&str) -> Vec<Option<usize>> {
+		table
+			.lines()
+			.enumerate()
+			.map(|(index, line)| line.find(if index == 0 { column } else { value }))
+			.collect()
+	}
+
+	#[test]
+	fn sizes_the_columns_to_the_runs() {
+		let table = list(&history(), &filter(None, None, false, 10)).unwrap();
+
+		// Run ids are longer than any fixed width would have had them
+		let after = "20261017-030000-000-51234  ".len();
+
+		assert_eq!(start(&table, "Started", "2026-10-1"), [Some(after); 3]);
+
+		let command = table.lines().next().unwrap().find("Command");
+
+		assert_eq!(start(&table, "Command", "daily"), [command; 3]);
+
+		assert!(table
+			.lines()
+			.nth(1)
+			.unwrap()
+			.ends_with("1 failed, 0 cancelled  daily --if-changed"));
+
+		assert_eq!(list(&history(), &filter(None, None, false, 1)).unwrap().lines().count(), 2);
+
+		assert_eq!(list(&[], &Filter::default()).unwrap(), "No runs recorded yet.\n");
+	}
+
+	#[test]
+	fn filters_the_entries_of_every_run() {
+		let found = |filter:&Filter| -> Vec<String> {
+			list(&history(), filter)
+				.unwrap()
+				.lines()
+				.skip(1)
+				.map(|line| line.split_whitespace().take(4).collect::<Vec<_>>().join(" "))
+				.collect()
+		};
+
+		let table = list(&history(), &filter(Some("*/SideView"), None, false, 10)).unwrap();
+
+		let repository = table.lines().next().unwrap().find("Repository");
+
+		assert_eq!(start(&table, "Repository", "CodeEditorLand/SideView"), [repository; 3]);
+
+		let outcome = table.lines().next().unwrap().find("Outcome");
+
+		assert_eq!(start(&table, "Outcome", "changed"), [outcome; 3]);
+
+		// The bare name, in any case, most recent first
+		assert_eq!(
+			found(&filter(Some("editor"), None, false, 10)),
+			[
+				"20261017-030000-000-51234 2026-10-17T03:00:00Z sync owner/Editor",
+				"20261017-030000-000-51234 2026-10-17T03:00:00Z clone owner/Editor",
+				"20261016-030000-000-41234 2026-10-16T03:00:00Z sync owner/Editor",
+				"20261016-030000-000-41234 2026-10-16T03:00:00Z clone owner/Editor",
+			]
+		);
+
+		// When `owner/Editor` last synced
+		assert_eq!(
+			found(&filter(Some("owner/Editor"), Some("sync"), true, 1)),
+			["20261016-030000-000-41234 2026-10-16T03:00:00Z sync owner/Editor"]
+		);
+
+		assert_eq!(found(&filter(None, Some("clone"), false, 10)).len(), 2);
+
+		assert!(found(&filter(Some("owner/Missing"), None, false, 10)).is_empty());
+
+		assert!(list(&history(), &filter(Some("/(unclosed/"), None, false, 10)).is_err());
+	}
+}
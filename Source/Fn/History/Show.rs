@@ -0,0 +1,36 @@
+use anyhow::Result;
+
+use crate::Fn::{History::Store, Workspace::Workspace};
+
+// Prints everything recorded about the run `run`.
+pub fn Fn(workspace:&Workspace, run:&str) -> Result<()> {
+	let summary = Store::get(&workspace.state, run)?;
+
+	println!("Run       {}{}", summary.run, if summary.dry_run { " (dry run)" } else { "" });
+
+	println!("Command   {}", summary.command);
+
+	println!("Pipeline  {}", summary.pipeline.join(", "));
+
+	println!("Started   {}", summary.started);
+
+	println!("Finished  {} after {:.1}s", summary.finished, summary.duration());
+
+	print!("\n{}", summary);
+
+	let pushed:Vec<_> = summary
+		.entry
+		.iter()
+		.flat_map(|entry| entry.pushed.iter().map(move |(name, id)| (entry, name, id)))
+		.collect();
+
+	if !pushed.is_empty() {
+		println!("\nPushed");
+
+		for (entry, name, id) in pushed {
+			println!("{}  {}  {}  {}", entry.repository, entry.task, name, id);
+		}
+	}
+
+	Ok(())
+}
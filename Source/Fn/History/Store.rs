@@ -0,0 +1,58 @@
+use std::{
+	fs,
+	path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::Fn::Task::Summary::Summary;
+
+// One summary per run inside the workspace state directory, named after the run.
+pub const DIRECTORY:&str = "History";
+
+// Keeps `summary` as the record of its run.
+pub fn save(state:&Path, summary:&Summary) -> Result<()> {
+	summary.save(&file(state, &summary.run))
+}
+
+pub fn file(state:&Path, run:&str) -> PathBuf {
+	state.join(DIRECTORY).join(format!("{}.json", run))
+}
+
+// Every recorded run, oldest first, as run ids sort by time.
+pub fn all(state:&Path) -> Result<Vec<Summary>> {
+	let directory = state.join(DIRECTORY);
+
+	if !directory.exists() {
+		return Ok(Vec::new());
+	}
+
+	let mut path:Vec<PathBuf> = fs::read_dir(&directory)
+		.with_context(|| format!("Failed to read {}", directory.display()))?
+		.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+		.filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+		.collect();
+
+	path.sort();
+
+	path.iter().map(|path| Summary::load(path)).collect()
+}
+
+// The run `run`, or the latest one for `last`.
+pub fn get(state:&Path, run:&str) -> Result<Summary> {
+	if run == "last" {
+		let Some(summary) = all(state)?.pop() else {
+			bail!("No run is recorded in {}", state.join(DIRECTORY).display());
+		};
+
+		return Ok(summary);
+	}
+
+	let path = file(state, run);
+
+	if !path.exists() {
+		bail!("No run {} is recorded, {} does not exist", run, path.display());
+	}
+
+	Summary::load(&path)
+}
@@ -0,0 +1,4 @@
+pub mod Compare;
+pub mod List;
+pub mod Show;
+pub mod Store;
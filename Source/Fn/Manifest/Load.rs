@@ -0,0 +1,129 @@
+use std::{fs, path::Path};
+
+use anyhow::{bail, Context, Result};
+
+use crate::Fn::Manifest::{Manifest, Repository};
+
+// Reads `Build.toml` and `Build.md` from the cache directory (e.g. `../Cache/Repository`). Bare
+// entries belong to `owner` unless `Build.toml` names another.
+//
+// `Build.toml` carries the typed entries with their per-repository settings. `Build.md` is the
+// flat `owner/name` inventory written by Cache::Get; every entry there that the TOML manifest
+// does not already describe is appended with default settings.
+pub fn Fn(directory:&Path, owner:&str) -> Result<Manifest> {
+	let toml_path = directory.join("Build.toml");
+
+	let list_path = directory.join("Build.md");
+
+	if !toml_path.exists() && !list_path.exists() {
+		bail!("No repository manifest found in {}", directory.display());
+	}
+
+	let mut manifest = if toml_path.exists() {
+		toml::from_str::<Manifest>(
+			&fs::read_to_string(&toml_path)
+				.with_context(|| format!("Failed to read {}", toml_path.display()))?,
+		)
+		.with_context(|| format!("Failed to parse {}", toml_path.display()))?
+	} else {
+		Manifest::default()
+	};
+
+	let owner = manifest.owner.get_or_insert_with(|| owner.to_string()).clone();
+
+	for repository in &mut manifest.repository {
+		if repository.owner.is_empty() {
+			repository.owner = owner.clone();
+		}
+
+		if repository.name.is_empty() {
+			bail!("Repository entry without a name in {}", toml_path.display());
+		}
+	}
+
+	if list_path.exists() {
+		for repository in list(
+			&fs::read_to_string(&list_path)
+				.with_context(|| format!("Failed to read {}", list_path.display()))?,
+			&owner,
+		) {
+			if manifest.find(&repository.full_name()).is_none() {
+				manifest.repository.push(repository);
+			}
+		}
+	}
+
+	Ok(manifest)
+}
+
+// Build.md is wrapped by the formatter, so one line may hold several whitespace-separated entries.
+pub fn list(content:&str, owner:&str) -> Vec<Repository> {
+	content.split_whitespace().filter_map(|entry| Repository::parse(entry, owner)).collect()
+}
+
+#[cfg(test)]
+mod test {
+	use std::fs;
+
+	use crate::Fn::{Manifest::Repository, Task::Test::directory};
+
+	#[test]
+	fn adds_the_entries_of_build_md_build_toml_lacks() {
+		let cache = directory("manifest");
+
+		fs::write(
+			cache.join("Build.toml"),
+			"owner = \"CodeEditorLand\"\n\n[[repository]]\nname = \"Editor\"\nfolder = \
+			 \"Land/Editor\"\ntag = [\"land\"]\n\n[repository.override]\ncopyright = \"Land\"\n\n\
+			 [[repository]]\nowner = \"microsoft\"\nname = \"vscode\"\n",
+		)
+		.unwrap();
+
+		// Wrapped by the formatter, with one entry Build.toml has already in another case
+		fs::write(cache.join("Build.md"), "codeeditorland/editor SideView\nowner/Thing.git a//b\n")
+			.unwrap();
+
+		let manifest = super::Fn(&cache, "Fallback").unwrap();
+
+		assert_eq!(manifest.owner(), "CodeEditorLand");
+
+		let name:Vec<_> = manifest.repository.iter().map(Repository::full_name).collect();
+
+		assert_eq!(
+			name,
+			["CodeEditorLand/Editor", "microsoft/vscode", "CodeEditorLand/SideView", "owner/Thing"]
+		);
+
+		let editor = manifest.find("codeeditorland/EDITOR").unwrap();
+
+		assert_eq!(editor.folder(), "Land/Editor");
+
+		assert!(editor.has_tag("land"));
+
+		assert_eq!(editor.get("copyright").and_then(toml::Value::as_str), Some("Land"));
+
+		assert_eq!(manifest.find("Thing").unwrap().folder(), "Thing");
+
+		fs::remove_dir_all(&cache).unwrap();
+	}
+
+	#[test]
+	fn reads_build_md_alone_for_the_owner_given() {
+		let cache = directory("manifest-list");
+
+		assert!(super::Fn(&cache, "owner").is_err());
+
+		fs::write(cache.join("Build.md"), "Editor\n").unwrap();
+
+		let manifest = super::Fn(&cache, "owner").unwrap();
+
+		assert_eq!(manifest.repository, [Repository::new("owner", "Editor")]);
+
+		// An entry has to have a name
+		fs::write(cache.join("Build.toml"), "[[repository]]\nfolder = \"Editor\"\n").unwrap();
+
+		assert!(super::Fn(&cache, "owner").is_err());
+
+		fs::remove_dir_all(&cache).unwrap();
+	}
+}
@@ -0,0 +1,112 @@
+pub mod Load;
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Fn::{
+	Forge::Endpoint,
+	Task::{Limit::Limit, Retry, Timeout::Timeout},
+};
+
+// Organization every bare `name` entry belongs to when the manifest does not say otherwise.
+pub const OWNER:&str = "CodeEditorLand";
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Manifest {
+	pub owner:Option<String>,
+
+	// Set from the configuration by `Config::fleet`, down to `timeout`.
+	pub forge:BTreeMap<String, Endpoint>,
+
+	pub exclude:Vec<String>,
+
+	pub retry:BTreeMap<String, Retry::Policy>,
+
+	pub limit:Limit,
+
+	pub timeout:Timeout,
+
+	pub repository:Vec<Repository>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct Repository {
+	pub owner:String,
+
+	pub name:String,
+
+	// Folder the repository is cloned into, relative to the workspace. Defaults to `name`.
+	pub folder:Option<String>,
+
+	// Upstream `owner/name` this repository is a fork of.
+	pub parent:Option<String>,
+
+	pub branch:Option<String>,
+
+	pub tag:Vec<String>,
+
+	// Name of the `[forge.<name>]` hosting this repository.
+	pub forge:Option<String>,
+
+	#[serde(rename = "override")]
+	pub overrides:toml::Table,
+}
+
+impl Repository {
+	pub fn new(owner:&str, name:&str) -> Self {
+		Self { owner:owner.to_string(), name:name.to_string(), ..Default::default() }
+	}
+
+	// Accepts both `owner/name` and a bare `name`, which falls back to `owner`.
+	pub fn parse(entry:&str, owner:&str) -> Option<Self> {
+		let entry = entry.trim().trim_end_matches(".git");
+
+		match entry.split_once('/') {
+			Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/') => {
+				Some(Self::new(owner, name))
+			},
+			None if !entry.is_empty() => Some(Self::new(owner, entry)),
+			_ => None,
+		}
+	}
+
+	pub fn full_name(&self) -> String {
+		format!("{}/{}", self.owner, self.name)
+	}
+
+	pub fn folder(&self) -> &str {
+		self.folder.as_deref().unwrap_or(&self.name)
+	}
+
+	pub fn branch(&self) -> &str {
+		self.branch.as_deref().unwrap_or("main")
+	}
+
+	pub fn parent(&self) -> Option<(&str, &str)> {
+		self.parent.as_deref().and_then(|parent| parent.split_once('/'))
+	}
+
+	pub fn has_tag(&self, tag:&str) -> bool {
+		self.tag.iter().any(|t| t == tag)
+	}
+
+	pub fn get(&self, key:&str) -> Option<&toml::Value> {
+		self.overrides.get(key)
+	}
+}
+
+impl Manifest {
+	pub fn owner(&self) -> &str {
+		self.owner.as_deref().unwrap_or(OWNER)
+	}
+
+	pub fn find(&self, name:&str) -> Option<&Repository> {
+		self.repository.iter().find(|repository| {
+			repository.full_name().eq_ignore_ascii_case(name)
+				|| repository.name.eq_ignore_ascii_case(name)
+		})
+	}
+}
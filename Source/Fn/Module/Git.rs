@@ -1,71 +1,138 @@
-use std::{fs::File, io::prelude::*, process::Command};
+use std::{
+	path::{Path, PathBuf},
+	process::Command,
+};
+
+use anyhow::Result;
+use serde::Deserialize;
+
+use crate::Fn::{
+	Task::{Audit, RepoContext, Task, TaskOutcome},
+	Workspace::Workspace,
+};
+
+// Writes a `.gitmodules` at `output` listing every repository as `<prefix>/<folder>`, with the
+// prefix `[module]` configures.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModuleGit {
+	// Defaults to `[module] output` under the workspace root.
+	pub output:Option<PathBuf>,
+}
+
+impl ModuleGit {
+	fn gitmodules(&self, workspace:&Workspace) -> PathBuf {
+		self.output.clone().unwrap_or_else(|| workspace.root.join(&workspace.config.module.output))
+	}
+}
 
-fn main() {
-	println!("Process: Module/Git.sh");
+impl Task for ModuleGit {
+	fn name(&self) -> &'static str {
+		"module git"
+	}
 
-	// Context: CodeEditorLand/Environment/Stream
-	let directory = std::env::current_dir().expect("Failed to get current directory");
-	let cache_path = directory.join("../Cache/Repository/Build.md");
+	fn description(&self) -> &'static str {
+		"Write .gitmodules"
+	}
 
-	let repositories = read_array(&cache_path).expect("Failed to read repositories");
+	fn applies(&self, context:&RepoContext) -> bool {
+		context.is_cloned()
+	}
 
 	// Remove existing .gitmodules file
-	let _ = std::fs::remove_file(directory.join("../../.gitmodules"));
+	fn prepare(&self, workspace:&Workspace) -> Result<()> {
+		let gitmodules = self.gitmodules(workspace);
 
-	for repository in repositories {
-		let folder = repository.replace("CodeEditorLand/", "");
+		if workspace.dry_run {
+			println!("Would replace {}", gitmodules.display());
 
-		let output =
-			Command::new("cd").arg(&folder).output().expect("Failed to execute cd command");
+			return Ok(());
+		}
 
-		println!("{}", String::from_utf8_lossy(&output.stdout));
+		let audit = Audit::Audit::open(&workspace.state.join(Audit::FILE), &workspace.run, false)?;
 
-		// Execute script here
-		script(&folder, &repository);
+		// It belongs to the superproject rather than to a repository of the fleet
+		audit.remove("", self.name(), &gitmodules)
+	}
 
-		let output = Command::new("cd").arg("-").output().expect("Failed to execute cd - command");
+	fn run(&self, context:&RepoContext) -> Result<TaskOutcome> {
+		script(context, &self.gitmodules(context.workspace))?;
 
-		println!("{}", String::from_utf8_lossy(&output.stdout));
+		Ok(TaskOutcome::Changed)
 	}
 }
 
-fn read_array(file_path:&std::path::Path) -> Result<Vec<String>, std::io::Error> {
-	let content = std::fs::read_to_string(file_path)?;
-	let repositories:Vec<String> = content.lines().map(|s| s.to_string()).collect();
-	Ok(repositories)
-}
+fn script(context:&RepoContext, gitmodules:&Path) -> Result<()> {
+	let folder = context.repository.folder();
+
+	let origin = context
+		.query(Command::new("git").arg("remote").arg("get-url").arg("origin"))?
+		.stdout
+		.trim()
+		.to_string();
+
+	context.log(format!("Folder: {}", folder));
+	context.log(format!("Origin: {}", origin));
+
+	let prefix = &context.workspace.config.module.prefix;
 
-fn script(folder:&str, repository:&str) {
-	let output = Command::new("cd").arg(folder).output().expect("Failed to execute cd command");
-
-	println!("{}", String::from_utf8_lossy(&output.stdout));
-
-	let origin = String::from_utf8_lossy(
-		&Command::new("git")
-			.arg("remote")
-			.arg("get-url")
-			.arg("origin")
-			.output()
-			.expect("Failed to execute git remote get-url origin command")
-			.stdout,
-	)
-	.trim()
-	.to_string();
-
-	println!("Folder: {}", folder);
-	println!("Origin: {}", origin);
-
-	let mut submodule = format!(
-		"[submodule \"Application/{}\"]\npath = Application/{}\nurl = {}\n",
-		folder, folder, origin
+	let submodule = format!(
+		"[submodule \"{}/{}\"]\npath = {}/{}\nurl = {}\n",
+		prefix, folder, prefix, folder, origin
 	);
 
 	// Append submodule entry to .gitmodules
-	let mut file = File::create("../../.gitmodules").expect("Failed to create .gitmodules file");
-	file.write_all(submodule.as_bytes())
-		.expect("Failed to write to .gitmodules file");
+	context.append(gitmodules, submodule)?;
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod test {
+	use std::fs;
 
-	let output = Command::new("cd").arg("-").output().expect("Failed to execute cd - command");
+	use super::ModuleGit;
+	use crate::Fn::Task::{Audit::Event, Task, Test::Scratch};
 
-	println!("{}", String::from_utf8_lossy(&output.stdout));
+	#[test]
+	fn audits_removing_the_old_gitmodules() {
+		let scratch = Scratch::new("module-prepare");
+
+		let gitmodules = scratch.workspace.root.join(".gitmodules");
+
+		fs::write(&gitmodules, "[submodule \"old\"]\n").unwrap();
+
+		let task = ModuleGit { output:Some(gitmodules.clone()) };
+
+		task.prepare(&scratch.workspace).unwrap();
+
+		assert!(!gitmodules.exists());
+
+		// Nothing left to remove is not an error, and not recorded either
+		task.prepare(&scratch.workspace).unwrap();
+
+		let removed:Vec<_> = scratch
+			.audited()
+			.into_iter()
+			.filter_map(|record| match record.event {
+				Event::Remove { path, hash } => Some((record.task, path, hash.is_some())),
+				_ => None,
+			})
+			.collect();
+
+		assert_eq!(removed, [("module git".to_string(), gitmodules, true)]);
+	}
+
+	#[test]
+	fn fails_when_the_old_gitmodules_stays() {
+		let scratch = Scratch::new("module-prepare-fail");
+
+		let file = scratch.workspace.root.join("file");
+
+		fs::write(&file, "").unwrap();
+
+		let task = ModuleGit { output:Some(file.join(".gitmodules")) };
+
+		assert!(task.prepare(&scratch.workspace).is_err());
+	}
 }
@@ -1 +1 @@
-pub mod Maintain;
+pub mod Git;
@@ -1,3 +1,3 @@
-pub mod Maintain;
-pub mod Maintain;
-pub mod license2;
+pub mod license;
+pub mod package;
+pub mod src;
@@ -1,82 +1,85 @@
-use std::{fs, process::Command};
+use anyhow::Result;
 
-fn main() {
-	println!("Process: Move/src.sh");
+use crate::Fn::Task::{RepoContext, Task, TaskOutcome};
 
-	// Context: CodeEditorLand/Environment/Stream
-	let directory = std::env::current_dir().expect("Failed to get current directory");
-	let cache_path = directory.join("../Cache/Repository/Build.md");
+#[derive(Default)]
+pub struct MoveSrc;
 
-	let repositories = read_array(&cache_path).expect("Failed to read repositories");
-
-	for repository in repositories {
-		let folder = repository.replace("CodeEditorLand/", "");
-
-		let output =
-			Command::new("cd").arg(&folder).output().expect("Failed to execute cd command");
+impl Task for MoveSrc {
+	fn name(&self) -> &'static str {
+		"move src"
+	}
 
-		println!("{}", String::from_utf8_lossy(&output.stdout));
+	fn description(&self) -> &'static str {
+		"Move src into Source"
+	}
 
-		// Execute script here
-		script(&folder);
+	fn applies(&self, context:&RepoContext) -> bool {
+		context.path.exists()
+	}
 
-		let output = Command::new("cd").arg("-").output().expect("Failed to execute cd - command");
+	fn parallel(&self) -> bool {
+		true
+	}
 
-		println!("{}", String::from_utf8_lossy(&output.stdout));
+	fn backup(&self) -> bool {
+		true
 	}
-}
 
-fn read_array(file_path:&std::path::Path) -> Result<Vec<String>, std::io::Error> {
-	let content = std::fs::read_to_string(file_path)?;
-	let repositories:Vec<String> = content.lines().map(|s| s.to_string()).collect();
-	Ok(repositories)
-}
+	fn run(&self, context:&RepoContext) -> Result<TaskOutcome> {
+		let src = context.path.join("src");
 
-fn script(folder:&str) {
-	let output = Command::new("cd").arg(folder).output().expect("Failed to execute cd command");
-
-	println!("{}", String::from_utf8_lossy(&output.stdout));
-
-	if let Ok(entries) = fs::read_dir("src") {
-		for entry in entries {
-			if let Ok(entry) = entry {
-				let path = entry.path();
-				if path.is_dir() {
-					fs::create_dir_all("Source").expect("Failed to create Source directory");
-					fs::copy(&path, "Source").expect("Failed to copy src directory to Source");
-					fs::remove_dir_all("src").expect("Failed to remove src directory");
-				}
-			}
+		if !src.is_dir() {
+			return Ok(TaskOutcome::Unchanged);
 		}
-	}
 
-	// Find all 'src' directories and move their contents to 'Source'
-	if let Ok(entries) = fs::read_dir(".") {
-		for entry in entries {
-			if let Ok(entry) = entry {
-				let path = entry.path();
-				if let Some(file_name) = path.file_name() {
-					if file_name == "src" {
-						if let Ok(src_entries) = fs::read_dir(&path) {
-							for src_entry in src_entries {
-								if let Ok(src_entry) = src_entry {
-									let src_path = src_entry.path();
-									if src_path.is_dir() {
-										fs::copy(&src_path, "Source")
-											.expect("Failed to copy src directory to Source");
-										fs::remove_dir_all("src")
-											.expect("Failed to remove src directory");
-									}
-								}
-							}
-						}
-					}
-				}
-			}
+		let source = context.path.join("Source");
+
+		// Moving into an existing Source would mean merging two trees file by file
+		if source.exists() {
+			return Ok(TaskOutcome::Skipped("Source exists already".to_string()));
 		}
+
+		context.rename(&src, &source)?;
+
+		Ok(TaskOutcome::Changed)
 	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::fs;
+
+	use super::MoveSrc;
+	use crate::Fn::Task::{Audit::Event, Task, TaskOutcome, Test::Scratch};
+
+	#[test]
+	fn moves_a_directory() {
+		let scratch = Scratch::new("move-src");
+
+		let src = scratch.checkout().join("src");
+
+		fs::create_dir_all(src.join("lib")).unwrap();
 
-	let output = Command::new("cd").arg("-").output().expect("Failed to execute cd - command");
+		fs::write(src.join("lib").join("index.ts"), "export {};\n").unwrap();
 
-	println!("{}", String::from_utf8_lossy(&output.stdout));
+		let outcome = MoveSrc.run(&scratch.context("move src")).unwrap();
+
+		assert_eq!(outcome, TaskOutcome::Changed);
+
+		assert!(!src.exists());
+
+		assert!(scratch.checkout().join("Source/lib/index.ts").is_file());
+
+		let rename = scratch
+			.audited()
+			.into_iter()
+			.find_map(|record| match record.event {
+				Event::Rename { to, hash, .. } => Some((to, hash)),
+				_ => None,
+			})
+			.expect("The rename is not audited");
+
+		assert_eq!(rename, (scratch.checkout().join("Source"), None));
+	}
 }
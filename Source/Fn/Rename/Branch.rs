@@ -1,70 +1,79 @@
 use std::process::Command;
 
-fn main() {
-	println!("Process: Rename/Branch.sh");
+use anyhow::{Context, Result};
+use serde::Deserialize;
 
-	// Context: CodeEditorLand/Environment/Stream
-	let directory = std::env::current_dir().expect("Failed to get current directory");
-	let cache_path = directory.join("../Cache/Repository/Build.md");
+use crate::Fn::Task::{RepoContext, Task, TaskOutcome};
 
-	let repositories = read_array(&cache_path).expect("Failed to read repositories");
+// Makes `branch` the default branch, creating it from the current checkout.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RenameBranch {
+	pub branch:String,
+}
 
-	for repository in repositories {
-		let folder = repository.replace("CodeEditorLand/", "");
+impl Default for RenameBranch {
+	fn default() -> Self {
+		Self { branch:"main".to_string() }
+	}
+}
 
-		let output =
-			Command::new("cd").arg(&folder).output().expect("Failed to execute cd command");
+impl Task for RenameBranch {
+	fn name(&self) -> &'static str {
+		"rename branch"
+	}
 
-		println!("{}", String::from_utf8_lossy(&output.stdout));
+	fn description(&self) -> &'static str {
+		"Make one branch the default"
+	}
 
-		// Execute script here
-		script(&folder);
+	fn applies(&self, context:&RepoContext) -> bool {
+		context.is_cloned()
+	}
 
-		let output = Command::new("cd").arg("-").output().expect("Failed to execute cd - command");
+	fn parallel(&self) -> bool {
+		true
+	}
 
-		println!("{}", String::from_utf8_lossy(&output.stdout));
+	fn backup(&self) -> bool {
+		true
 	}
-}
 
-fn read_array(file_path:&std::path::Path) -> Result<Vec<String>, std::io::Error> {
-	let content = std::fs::read_to_string(file_path)?;
-	let repositories:Vec<String> = content.lines().map(|s| s.to_string()).collect();
-	Ok(repositories)
+	fn run(&self, context:&RepoContext) -> Result<TaskOutcome> {
+		script(context, &self.branch)
+	}
 }
 
-fn script(folder:&str) {
-	let output = Command::new("cd").arg(folder).output().expect("Failed to execute cd command");
-
-	println!("{}", String::from_utf8_lossy(&output.stdout));
-
-	// Execute commands using git and gh
-	let output = Command::new("git")
-		.arg("switch")
-		.arg("-c")
-		.arg("main")
-		.output()
-		.expect("Failed to execute git switch -c main command");
-
-	println!("{}", String::from_utf8_lossy(&output.stdout));
-
-	let output = Command::new("git")
-		.arg("push")
-		.arg("-f")
-		.arg("--set-upstream")
-		.arg("origin")
-		.arg("main")
-		.output()
-		.expect("Failed to execute git push -f --set-upstream origin main command");
-
-	println!("{}", String::from_utf8_lossy(&output.stdout));
-
-	let output = Command::new("gh")
-		.arg("repo")
-		.arg("edit")
-		.arg("--default-branch")
-		.arg("main")
-		.output()
-		.expect("Failed to execute gh repo edit --default-branch main command");
-
-	println!("{}", String::from_utf8_lossy(&output.stdout));
+fn script(context:&RepoContext, branch:&str) -> Result<TaskOutcome> {
+	let repository = context.repository;
+
+	// Create the branch from the current checkout unless it exists already
+	let exists = git2::Repository::open(&context.path)
+		.is_ok_and(|checkout| checkout.find_branch(branch, git2::BranchType::Local).is_ok());
+
+	// Execute commands using git and the forge
+	let output = if exists {
+		context.execute(Command::new("git").arg("switch").arg(branch))?
+	} else {
+		context.execute(Command::new("git").arg("switch").arg("-c").arg(branch))?
+	};
+
+	context.log(output.stdout);
+
+	let output = context.execute(
+		Command::new("git").arg("push").arg("-f").arg("--set-upstream").arg("origin").arg(branch),
+	)?;
+
+	context.log(output.stdout);
+
+	context
+		.remote(
+			format!("set the default branch of {} to {}", repository.full_name(), branch),
+			|forge| forge.set_default_branch(&repository.owner, &repository.name, branch),
+		)
+		.with_context(|| {
+			format!("Failed to set the default branch of {}", repository.full_name())
+		})?;
+
+	Ok(TaskOutcome::Changed)
 }
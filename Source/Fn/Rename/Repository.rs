@@ -1,41 +1,39 @@
-use std::process::Command;
+use anyhow::{bail, Context, Result};
 
-fn main() {
-	println!("Process: Rename/Repository.sh");
+use crate::Fn::Task::{RepoContext, Task, TaskOutcome};
 
-	// Context: CodeEditorLand/Environment/Stream
-	let directory = std::env::current_dir().expect("Failed to get current directory");
-	let cache_path = directory.join("../Cache/Repository/Build.md");
+#[derive(Default)]
+pub struct RenameRepository;
 
-	let repositories = read_array(&cache_path).expect("Failed to read repositories");
-
-	for repository in repositories {
-		let folder = repository.replace("CodeEditorLand/", "");
-
-		let output =
-			Command::new("cd").arg(&folder).output().expect("Failed to execute cd command");
-
-		println!("{}", String::from_utf8_lossy(&output.stdout));
+impl Task for RenameRepository {
+	fn name(&self) -> &'static str {
+		"rename repo"
+	}
 
-		// Execute script here
-		script(&folder);
+	fn description(&self) -> &'static str {
+		"Rename repositories to their PascalCase name"
+	}
 
-		let output = Command::new("cd").arg("-").output().expect("Failed to execute cd - command");
+	fn parallel(&self) -> bool {
+		true
+	}
 
-		println!("{}", String::from_utf8_lossy(&output.stdout));
+	fn run(&self, context:&RepoContext) -> Result<TaskOutcome> {
+		script(context)
 	}
 }
 
-fn read_array(file_path:&std::path::Path) -> Result<Vec<String>, std::io::Error> {
-	let content = std::fs::read_to_string(file_path)?;
-	let repositories:Vec<String> = content.lines().map(|s| s.to_string()).collect();
-	Ok(repositories)
-}
+fn script(context:&RepoContext) -> Result<TaskOutcome> {
+	let repository = context.repository;
+
+	let folder = repository.folder();
 
-fn script(folder:&str) {
 	let mut rename = String::new();
 
-	let first_char = folder.chars().next().unwrap();
+	let Some(first_char) = folder.chars().next() else {
+		bail!("{} has no folder name", repository.full_name());
+	};
+
 	rename.push_str(&first_char.to_uppercase().to_string());
 
 	for (i, c) in folder.chars().enumerate() {
@@ -45,27 +43,31 @@ fn script(folder:&str) {
 					if next_char.is_lowercase() {
 						rename.push_str(&next_char.to_uppercase().to_string());
 					} else {
-						rename.push_str(&c.to_string());
+						rename.push(c);
 					}
 				}
 			} else {
-				rename.push_str(&c.to_string());
+				rename.push(c);
 			}
 		}
 	}
 
-	rename = rename.replace("vscode", "Land");
+	for (from, to) in &context.workspace.config.rename.replace {
+		rename = rename.replace(from, to);
+	}
+
+	context.log("Rename: ");
+	context.log(&rename);
 
-	println!("Rename: ");
-	println!("{}", rename);
+	if rename == repository.name {
+		return Ok(TaskOutcome::Unchanged);
+	}
 
-	let output = Command::new("gh")
-		.arg("repo")
-		.arg("rename")
-		.arg(&rename)
-		.arg("--yes")
-		.output()
-		.expect("Failed to execute gh repo rename command");
+	context
+		.remote(format!("rename {} to {}", repository.full_name(), rename), |forge| {
+			forge.rename(&repository.owner, &repository.name, &rename)
+		})
+		.with_context(|| format!("Failed to rename {}", repository.full_name()))?;
 
-	println!("{}", String::from_utf8_lossy(&output.stdout));
+	Ok(TaskOutcome::Changed)
 }
@@ -1,69 +1,202 @@
-use std::process::Command;
+use std::fs;
 
-fn main() {
-	println!("Process: Replace/Copyright.sh");
+use anyhow::Result;
+use walkdir::WalkDir;
 
-	// Context: CodeEditorLand/Environment/Stream
+use crate::Fn::{
+	Action::Find::PRUNE,
+	Replace::Import::EXTENSION,
+	Task::{RepoContext, Task, TaskOutcome},
+};
 
-	for repository in read_array(
-		&std::env::current_dir()
-			.expect("Failed to get current directory")
-			.join("../Cache/Repository/Build.md"),
-	)
-	.expect("Failed to read repositories")
+// Replaces the copyright comment sources open with the repository's
+// `[repository.override] copyright`, the whole header including its comment markers.
+#[derive(Default)]
+pub struct ReplaceCopyright;
+
+impl Task for ReplaceCopyright {
+	fn name(&self) -> &'static str {
+		"replace copyright"
+	}
+
+	fn description(&self) -> &'static str {
+		"Replace copyright headers following [repository.override] copyright"
+	}
+
+	fn applies(&self, context:&RepoContext) -> bool {
+		context.path.exists() && context.repository.get("copyright").is_some()
+	}
+
+	fn parallel(&self) -> bool {
+		true
+	}
+
+	fn backup(&self) -> bool {
+		true
+	}
+
+	fn run(&self, context:&RepoContext) -> Result<TaskOutcome> {
+		let count = replace(context)?;
+
+		if count == 0 {
+			return Ok(TaskOutcome::Unchanged);
+		}
+
+		context.log(format!("Replaced copyright headers in {} files", count));
+
+		Ok(TaskOutcome::Changed)
+	}
+}
+
+// The number of files changed in the checkout.
+pub fn replace(context:&RepoContext) -> Result<usize> {
+	let Some(header) = context.repository.get("copyright").and_then(toml::Value::as_str) else {
+		return Ok(0);
+	};
+
+	let header = header.trim_end();
+
+	if header.is_empty() {
+		return Ok(0);
+	}
+
+	let mut count = 0;
+
+	for entry in WalkDir::new(&context.path)
+		.into_iter()
+		.filter_entry(|entry| {
+			!(entry.file_type().is_dir()
+				&& entry.depth() > 0
+				&& PRUNE.iter().any(|prune| entry.file_name().eq_ignore_ascii_case(prune)))
+		})
+		.filter_map(|entry| entry.ok())
 	{
-		println!(
-			"{}",
-			String::from_utf8_lossy(
-				&Command::new("cd")
-					.arg(&folder)
-					.output()
-					.expect("Failed to execute cd command")
-					.stdout
-			)
-		);
+		let path = entry.path();
 
-		script(&repository.replace("CodeEditorLand/", ""));
-
-		println!(
-			"{}",
-			String::from_utf8_lossy(
-				&Command::new("cd")
-					.arg("-")
-					.output()
-					.expect("Failed to execute cd - command")
-					.stdout
-			)
-		);
+		if !entry.file_type().is_file()
+			|| !path
+				.extension()
+				.is_some_and(|extension| EXTENSION.iter().any(|candidate| extension == *candidate))
+		{
+			continue;
+		}
+
+		let Ok(source) = fs::read_to_string(path) else {
+			continue;
+		};
+
+		let Some(result) = rewrite(&source, header) else {
+			continue;
+		};
+
+		if result != source {
+			context.write(path, result)?;
+
+			count += 1;
+		}
 	}
+
+	Ok(count)
 }
 
-fn read_array(file_path:&std::path::Path) -> Result<Vec<String>, std::io::Error> {
-	Ok(std::fs::read_to_string(file_path)?.lines().map(|s| s.to_string()).collect())
+// `source` with `header` in place of its leading comment, when that comment is a copyright notice.
+// Sources without one are left alone rather than given a header they never had.
+pub fn rewrite(source:&str, header:&str) -> Option<String> {
+	// A shebang has to stay on the first line
+	let start = if source.starts_with("#!") {
+		source.find('\n').map_or(source.len(), |end| end + 1)
+	} else {
+		0
+	};
+
+	let rest = &source[start..];
+
+	let body = rest.trim_start();
+
+	let length = if body.starts_with("/*") {
+		body.find("*/")? + 2
+	} else if body.starts_with("//") {
+		let mut length = 0;
+
+		for line in body.split_inclusive('\n') {
+			if !line.trim_start().starts_with("//") {
+				break;
+			}
+
+			length += line.len();
+		}
+
+		body[..length].trim_end().len()
+	} else {
+		return None;
+	};
+
+	if !body[..length].to_lowercase().contains("copyright") {
+		return None;
+	}
+
+	let end = start + (rest.len() - body.len()) + length;
+
+	Some(format!("{}{}{}", &source[..start], header, &source[end..]))
 }
 
-fn script(folder:&str) {
-	println!(
-		"{}",
-		String::from_utf8_lossy(
-			&Command::new("cd")
-				.arg(folder)
-				.output()
-				.expect("Failed to execute cd command")
-				.stdout
-		)
-	);
-
-	// Add your script commands here
-
-	println!(
-		"{}",
-		String::from_utf8_lossy(
-			&Command::new("cd")
-				.arg("-")
-				.output()
-				.expect("Failed to execute cd - command")
-				.stdout
-		)
-	);
+#[cfg(test)]
+mod test {
+	use std::fs;
+
+	use super::rewrite;
+	use crate::Fn::Task::Test::Scratch;
+
+	const HEADER:&str = "/*\n * Copyright (c) CodeEditorLand. All rights reserved.\n */";
+
+	#[test]
+	fn replaces_leading_copyright_comments() {
+		assert_eq!(
+			rewrite("/* Copyright Microsoft */\nexport {};\n", HEADER).unwrap(),
+			format!("{}\nexport {{}};\n", HEADER)
+		);
+
+		assert_eq!(
+			rewrite("// Copyright Microsoft\n// Licensed under MIT\n\nexport {};\n", HEADER)
+				.unwrap(),
+			format!("{}\n\nexport {{}};\n", HEADER)
+		);
+
+		assert_eq!(
+			rewrite("#!/usr/bin/env node\n/* Copyright Microsoft */\nrun();\n", HEADER).unwrap(),
+			format!("#!/usr/bin/env node\n{}\nrun();\n", HEADER)
+		);
+	}
+
+	#[test]
+	fn leaves_other_sources_alone() {
+		assert_eq!(rewrite("export {};\n", HEADER), None);
+
+		assert_eq!(rewrite("// Helpers for the editor\nexport {};\n", HEADER), None);
+
+		assert_eq!(rewrite("/* Copyright, never closed\n", HEADER), None);
+	}
+
+	#[test]
+	fn rewrites_the_checkout_once() {
+		let mut scratch = Scratch::new("replace-copyright");
+
+		scratch.manifest.repository[0]
+			.overrides
+			.insert("copyright".to_string(), toml::Value::String(HEADER.to_string()));
+
+		let source = scratch.checkout().join("Source").join("index.ts");
+
+		fs::create_dir_all(source.parent().unwrap()).unwrap();
+
+		fs::write(&source, "/* Copyright Microsoft */\nexport {};\n").unwrap();
+
+		fs::write(scratch.checkout().join("plain.js"), "export {};\n").unwrap();
+
+		assert_eq!(super::replace(&scratch.context("replace copyright")).unwrap(), 1);
+
+		assert_eq!(fs::read_to_string(&source).unwrap(), format!("{}\nexport {{}};\n", HEADER));
+
+		assert_eq!(super::replace(&scratch.context("replace copyright")).unwrap(), 0);
+	}
 }
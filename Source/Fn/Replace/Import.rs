@@ -1,123 +1,113 @@
-use std::process::Command;
+use std::fs;
 
-fn main() {
-	println!("Process: Replace/Import.sh");
+use anyhow::Result;
+use walkdir::WalkDir;
 
-	// Context: CodeEditorLand/Environment/Stream
+use crate::Fn::{
+	Action::Find::PRUNE,
+	Task::{RepoContext, Task, TaskOutcome},
+};
 
-	for repository in read_array(
-		&std::env::current_dir()
-			.expect("Failed to get current directory")
-			.join("../Cache/Repository/Build.md"),
-	)
-	.expect("Failed to read repositories")
-	{
-		println!(
-			"{}",
-			String::from_utf8_lossy(
-				&Command::new("cd")
-					.arg(&folder)
-					.output()
-					.expect("Failed to execute cd command")
-					.stdout
-			)
-		);
-
-		script(&repository.replace("CodeEditorLand/", ""));
-
-		println!(
-			"{}",
-			String::from_utf8_lossy(
-				&Command::new("cd")
-					.arg("-")
-					.output()
-					.expect("Failed to execute cd - command")
-					.stdout
-			)
-		);
+// Sources whose import specifiers are rewritten.
+pub const EXTENSION:[&str; 6] = ["ts", "tsx", "mts", "js", "jsx", "mjs"];
+
+// Rewrites import specifiers following the repository's `[repository.override.import]` table,
+// e.g. `"vs/platform/userDataSync/common/userDataSyncLocalStoreService" = "@codeeditorland/sync"`.
+#[derive(Default)]
+pub struct ReplaceImport;
+
+impl Task for ReplaceImport {
+	fn name(&self) -> &'static str {
+		"replace import"
+	}
+
+	fn description(&self) -> &'static str {
+		"Rewrite imports following [repository.override.import]"
+	}
+
+	fn applies(&self, context:&RepoContext) -> bool {
+		context.path.exists() && context.repository.get("import").is_some()
 	}
-}
 
-pub fn replace() {
-	// Read TypeScript code from a file
-	let ts_code = fs::read_to_string("example.ts").expect("Error reading file");
-
-	// Initialize the source map and session
-	let cm = SourceMap::default();
-	let handler = Box::new(Session { handler:&swc_ecma_parser::Handler::new(&cm) });
-
-	// Initialize the parser
-	let comments = SingleThreadedComments::default();
-	let lexer = Lexer::new(
-		Syntax::Typescript(TsConfig {
-			tsx:false, // Set to true if you want to support JSX
-			dynamic_import:true,
-			decorators:true,
-			import_assertions:true,
-			no_early_errors:false,
-		}),
-		Default::default(),
-		SourceFileInput::from(
-			SourceMap::new().new_source_file(FileName::Custom("example.ts".into()), ts_code.into()),
-		),
-		Some(&comments),
-	);
-	let mut parser = Parser::new_from(lexer);
-
-	// Parse the TypeScript code
-	let mut module = parser.parse_typescript_module().expect("Failed to parse TypeScript code");
-
-	// Replace import statements
-	module.body.retain(|item| {
-		match item {
-			ModuleItem::Stmt(Stmt::Import(import_stmt)) => {
-				// Check if it's an import statement you want to replace
-				let src_str = cm.span_to_snippet(import_stmt.src.value.span).unwrap();
-				if src_str == "'vs/platform/userDataSync/common/userDataSyncLocalStoreService'" {
-					// Replace the import with your desired import
-					*item = ModuleItem::Stmt(Stmt::Decl(Decl::Var(VarDecl {
-						span:import_stmt.span,
-						kind:VarDeclKind::Const,
-						declare:false,
-						decls:once(VarDeclarator {
-							span:DUMMY_SP,
-							name:Pat::Ident(Ident::new(
-								js_word!("IUserDataSyncLocalStoreService"),
-								DUMMY_SP,
-							)),
-							init:Some(Box::new(Expr::Ident(Ident::new(
-								js_word!("UserDataSyncLocalStoreService"),
-								DUMMY_SP,
-							)))),
-							definite:false,
-						})
-						.collect(),
-					})));
-				}
-				true // Keep the item
-			},
-			_ => true, // Keep other items
+	fn parallel(&self) -> bool {
+		true
+	}
+
+	fn backup(&self) -> bool {
+		true
+	}
+
+	fn run(&self, context:&RepoContext) -> Result<TaskOutcome> {
+		let count = replace(context)?;
+
+		if count == 0 {
+			return Ok(TaskOutcome::Unchanged);
 		}
-	});
 
-	// Write the modified code back to the file
-	let mut src = Vec::new();
+		context.log(format!("Rewrote imports in {} files", count));
+
+		Ok(TaskOutcome::Changed)
+	}
+}
+
+// The number of files changed in the checkout.
+pub fn replace(context:&RepoContext) -> Result<usize> {
+	let repository = context.repository;
+
+	let Some(table) = repository.get("import").and_then(toml::Value::as_table) else {
+		return Ok(0);
+	};
+
+	let specifier = table
+		.iter()
+		.filter_map(|(from, to)| Some((from.as_str(), to.as_str()?)))
+		.collect::<Vec<_>>();
+
+	if specifier.is_empty() {
+		return Ok(0);
+	}
+
+	let mut count = 0;
+
+	for entry in WalkDir::new(&context.path)
+		.into_iter()
+		.filter_entry(|entry| {
+			!(entry.file_type().is_dir()
+				&& entry.depth() > 0
+				&& PRUNE.iter().any(|prune| entry.file_name().eq_ignore_ascii_case(prune)))
+		})
+		.filter_map(|entry| entry.ok())
 	{
-		let mut emitter = Emitter {
-			cfg:EmitterConfig {
-				indent:None,
-				minify:false,
-				cm:cm.clone(),
-				config:Default::default(),
-			},
-			comments:Some(&comments),
-			cm:cm.clone(),
-			wr:Box::new(JsWriter::new(cm.clone(), "\n", &mut src, None)),
+		let path = entry.path();
+
+		if !entry.file_type().is_file()
+			|| !path
+				.extension()
+				.is_some_and(|extension| EXTENSION.iter().any(|candidate| extension == *candidate))
+		{
+			continue;
+		}
+
+		let Ok(source) = fs::read_to_string(path) else {
+			continue;
 		};
 
-		emitter.emit_module(&module).expect("Failed to emit module");
+		let mut result = source.clone();
+
+		// Only whole quoted specifiers are touched, so `vs/base` does not rewrite `vs/base/common`.
+		for (from, to) in &specifier {
+			for quote in ['"', '\''] {
+				result = result
+					.replace(&format!("{0}{1}{0}", quote, from), &format!("{0}{1}{0}", quote, to));
+			}
+		}
+
+		if result != source {
+			context.write(path, result)?;
+
+			count += 1;
+		}
 	}
 
-	// Write the modified code back to the file
-	fs::write("modified_example.ts", &src).expect("Error writing file");
+	Ok(count)
 }
@@ -0,0 +1,139 @@
+use std::fmt::Write;
+
+use crate::Fn::Report::Digest;
+
+const STYLE:&str = "body { font-family: sans-serif; max-width: 60em; margin: 2em auto; } pre { \
+                    background: #f4f4f4; padding: 0.5em; overflow-x: auto; } code { background: \
+                    #f4f4f4; }";
+
+// A page of its own, without anything to load.
+pub fn Fn(digest:&Digest) -> String {
+	let summary = digest.summary;
+
+	let mut page = String::new();
+
+	let _ = writeln!(
+		page,
+		"<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+		 <style>{STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>",
+		title = escape(&digest.title())
+	);
+
+	let _ = writeln!(
+		page,
+		"<p><code>{}</code>, {} to {}, {:.1}s</p>\n<p>{}</p>",
+		escape(&summary.command),
+		escape(&summary.started),
+		escape(&summary.finished),
+		summary.duration(),
+		escape(&digest.total())
+	);
+
+	if !digest.changed.is_empty() {
+		let _ = writeln!(page, "<h2>Changed</h2>\n<ul>");
+
+		for (repository, task) in &digest.changed {
+			let _ = writeln!(
+				page,
+				"<li><b>{}</b>: {}</li>",
+				escape(repository),
+				escape(&task.join(", "))
+			);
+		}
+
+		let _ = writeln!(page, "</ul>");
+	}
+
+	if !digest.failed.is_empty() {
+		let _ = writeln!(page, "<h2>Failed</h2>");
+
+		for (repository, task, excerpt) in &digest.failed {
+			let _ = writeln!(
+				page,
+				"<h3>{}: {}</h3>\n<pre>{}</pre>",
+				escape(repository),
+				escape(task),
+				escape(excerpt)
+			);
+		}
+	}
+
+	if !digest.merged.is_empty() {
+		let _ = writeln!(page, "<h2>Upstream commits merged</h2>");
+
+		for (repository, commit) in &digest.merged {
+			let _ = writeln!(page, "<h3>{}</h3>\n<ul>", escape(repository));
+
+			for commit in commit {
+				let _ = writeln!(page, "<li><code>{}</code></li>", escape(commit));
+			}
+
+			let _ = writeln!(page, "</ul>");
+		}
+	}
+
+	if !digest.package.is_empty() {
+		let _ = writeln!(page, "<h2>package.json edits</h2>\n<ul>");
+
+		for (repository, path) in &digest.package {
+			for (path, task) in path {
+				let _ = writeln!(
+					page,
+					"<li><b>{}</b>: <code>{}</code> by {}</li>",
+					escape(repository),
+					escape(&path.display().to_string()),
+					escape(&task.join(", "))
+				);
+			}
+		}
+
+		let _ = writeln!(page, "</ul>");
+	}
+
+	let _ = writeln!(page, "</body>\n</html>");
+
+	page
+}
+
+fn escape(text:&str) -> String {
+	text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod test {
+	use super::Fn;
+	use crate::Fn::Report::{Digest, Markdown::test::summary};
+
+	#[test]
+	fn renders_every_section_escaped() {
+		let summary = summary();
+
+		let page = Fn(&Digest::new(&summary));
+
+		assert!(page.starts_with("<!DOCTYPE html>"));
+
+		assert!(page.contains("<title>Maintain run 20261017-093000-042-4711</title>"));
+
+		assert!(page.contains("<li><b>owner/name</b>: sync, sort detail</li>"));
+
+		assert!(page.contains("<h3>owner/other: push</h3>\n<pre>Failed to push\nhook said:"));
+
+		assert!(page.contains("<li><code>1a2b3c4 Fix the &lt;editor&gt; &amp; tests</code></li>"));
+
+		assert!(
+			page.contains("<li><b>owner/name</b>: <code>package.json</code> by sort detail</li>")
+		);
+
+		assert!(page.trim_end().ends_with("</body>\n</html>"));
+	}
+
+	#[test]
+	fn marks_a_dry_run() {
+		let mut summary = summary();
+
+		summary.dry_run = true;
+
+		assert!(Fn(&Digest::new(&summary))
+			.contains("<h1>Maintain run 20261017-093000-042-4711 (dry run)</h1>"));
+	}
+}
@@ -0,0 +1,172 @@
+use std::fmt::Write;
+
+use crate::Fn::Report::Digest;
+
+pub fn Fn(digest:&Digest) -> String {
+	let summary = digest.summary;
+
+	let mut text = String::new();
+
+	let _ = writeln!(text, "# {}\n", digest.title());
+
+	let _ = writeln!(
+		text,
+		"`{}`, {} to {}, {:.1}s\n",
+		summary.command,
+		summary.started,
+		summary.finished,
+		summary.duration()
+	);
+
+	let _ = writeln!(text, "{}\n", digest.total());
+
+	if !digest.changed.is_empty() {
+		let _ = writeln!(text, "## Changed\n");
+
+		for (repository, task) in &digest.changed {
+			let _ = writeln!(text, "- **{}**: {}", repository, task.join(", "));
+		}
+
+		text.push('\n');
+	}
+
+	if !digest.failed.is_empty() {
+		let _ = writeln!(text, "## Failed\n");
+
+		for (repository, task, excerpt) in &digest.failed {
+			let fence = fence(excerpt);
+
+			let _ = writeln!(text, "### {repository}: {task}\n\n{fence}\n{excerpt}\n{fence}\n");
+		}
+	}
+
+	if !digest.merged.is_empty() {
+		let _ = writeln!(text, "## Upstream commits merged\n");
+
+		for (repository, commit) in &digest.merged {
+			let _ = writeln!(text, "### {}\n", repository);
+
+			for commit in commit {
+				let _ = writeln!(text, "- {}", commit);
+			}
+
+			text.push('\n');
+		}
+	}
+
+	if !digest.package.is_empty() {
+		let _ = writeln!(text, "## package.json edits\n");
+
+		for (repository, path) in &digest.package {
+			for (path, task) in path {
+				let _ = writeln!(
+					text,
+					"- **{}**: `{}` by {}",
+					repository,
+					path.display(),
+					task.join(", ")
+				);
+			}
+		}
+
+		text.push('\n');
+	}
+
+	text
+}
+
+// Backticks one longer than the longest run in `text`, so errors quoting Markdown cannot close
+// the block early.
+fn fence(text:&str) -> String {
+	let longest = text.split(|character| character != '`').map(str::len).max().unwrap_or(0);
+
+	"`".repeat(longest.max(2) + 1)
+}
+
+#[cfg(test)]
+pub mod test {
+	use std::{path::PathBuf, time::Duration};
+
+	use super::Fn;
+	use crate::Fn::{
+		Report::Digest,
+		Task::{
+			Summary::{Entry, Summary},
+			TaskOutcome,
+		},
+	};
+
+	pub fn summary() -> Summary {
+		let mut summary = Summary::new("20261017-093000-042-4711", "daily".to_string(), false);
+
+		let mut sync =
+			Entry::new("sync", "owner/name".to_string(), TaskOutcome::Changed, Duration::ZERO);
+
+		sync.merged = vec!["1a2b3c4 Fix the <editor> & tests".to_string()];
+
+		let mut sort = Entry::new(
+			"sort detail",
+			"owner/name".to_string(),
+			TaskOutcome::Changed,
+			Duration::ZERO,
+		);
+
+		sort.written = vec![PathBuf::from("package.json"), PathBuf::from("Source/index.ts")];
+
+		summary.entry = vec![
+			sync,
+			sort,
+			Entry::new("sync", "owner/same".to_string(), TaskOutcome::Unchanged, Duration::ZERO),
+			Entry::new(
+				"push",
+				"owner/other".to_string(),
+				TaskOutcome::Failed(vec![
+					"Failed to push".to_string(),
+					"hook said:\n```js\nthrow 1\n```".to_string(),
+				]),
+				Duration::ZERO,
+			),
+		];
+
+		summary.finish();
+
+		summary
+	}
+
+	#[test]
+	fn renders_every_section() {
+		let summary = summary();
+
+		let text = Fn(&Digest::new(&summary));
+
+		assert!(text.starts_with("# Maintain run 20261017-093000-042-4711\n\n`daily`, "));
+
+		assert!(text.contains("\n2 changed, 1 unchanged, 0 skipped, 1 failed"), "{}", text);
+
+		assert!(text.contains("## Changed\n\n- **owner/name**: sync, sort detail\n"));
+
+		assert!(text.contains("## Upstream commits merged\n\n### owner/name\n\n- 1a2b3c4 Fix"));
+
+		assert!(text.contains("## package.json edits\n\n- **owner/name**: `package.json` by sort"));
+
+		assert!(!text.contains("index.ts"));
+	}
+
+	#[test]
+	fn fences_errors_longer_than_the_backticks_they_quote() {
+		let summary = summary();
+
+		let text = Fn(&Digest::new(&summary));
+
+		assert!(
+			text.contains(
+				"### owner/other: push\n\n````\nFailed to push\nhook said:\n```js\nthrow \
+				 1\n```\n````\n"
+			),
+			"{}",
+			text
+		);
+
+		assert_eq!(super::fence("no backticks"), "```");
+	}
+}
@@ -0,0 +1,108 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde_json::json;
+
+use crate::Fn::{
+	Forge::Http::Http,
+	Report::Digest,
+	Task::{Retry::Policy, TaskOutcome},
+};
+
+// Posts the report to `url` as `text`, the field chat webhooks show, next to the counts for
+// anything that would rather not parse Markdown.
+pub fn Fn(url:&str, timeout:Duration, digest:&Digest, markdown:&str) -> Result<()> {
+	let summary = digest.summary;
+
+	let body = json!({
+		"text": markdown,
+		"run": summary.run,
+		"command": summary.command,
+		"started": summary.started,
+		"finished": summary.finished,
+		"dry_run": summary.dry_run,
+		"changed": summary.count(|outcome| *outcome == TaskOutcome::Changed),
+		"failed": summary.failed(),
+		"cancelled": summary.cancelled(),
+	});
+
+	let http = Http::new(url, Vec::new(), timeout);
+
+	Policy::default()
+		.run("webhook", || http.call("POST", url, Some(body.clone())).map(|_| ()))
+		.context("Failed to post the run report")
+}
+
+#[cfg(test)]
+mod test {
+	use std::time::Duration;
+
+	use serde_json::Value;
+
+	use super::Fn;
+	use crate::Fn::{
+		Report::Digest,
+		Task::{
+			Summary::{Entry, Summary},
+			TaskOutcome,
+			Test::Server,
+		},
+	};
+
+	fn summary() -> Summary {
+		let mut summary = Summary::new("20261017-093000-042-4711", "daily".to_string(), false);
+
+		summary.entry = vec![
+			Entry::new("sync", "owner/name".to_string(), TaskOutcome::Changed, Duration::ZERO),
+			Entry::new(
+				"push",
+				"owner/other".to_string(),
+				TaskOutcome::Failed(vec!["rejected".to_string()]),
+				Duration::ZERO,
+			),
+		];
+
+		summary.finish();
+
+		summary
+	}
+
+	#[test]
+	fn posts_the_report_as_json() {
+		let server = Server::new(|_| (200, Vec::new(), "ok".to_string()));
+
+		let summary = summary();
+
+		Fn(&server.url, Duration::from_secs(5), &Digest::new(&summary), "# Report\n").unwrap();
+
+		let received = server.received();
+
+		assert_eq!(received.len(), 1);
+
+		assert_eq!(received[0].method, "POST");
+
+		assert!(received[0].header["content-type"].starts_with("application/json"));
+
+		let body:Value = serde_json::from_str(&received[0].body).unwrap();
+
+		assert_eq!(body["text"], "# Report\n");
+
+		assert_eq!(body["run"], "20261017-093000-042-4711");
+
+		assert_eq!((body["changed"].as_u64(), body["failed"].as_u64()), (Some(1), Some(1)));
+	}
+
+	#[test]
+	fn fails_when_the_webhook_refuses() {
+		let server = Server::new(|_| (404, Vec::new(), "no such hook".to_string()));
+
+		let summary = summary();
+
+		let error = Fn(&server.url, Duration::from_secs(5), &Digest::new(&summary), "# Report\n")
+			.unwrap_err();
+
+		assert!(format!("{:#}", error).starts_with("Failed to post the run report"));
+
+		assert_eq!(server.received().len(), 1);
+	}
+}
@@ -0,0 +1,116 @@
+pub mod Html;
+pub mod Markdown;
+pub mod Webhook;
+
+use std::{collections::BTreeMap, path::Path};
+
+use crate::Fn::Task::{Summary::Summary, TaskOutcome};
+
+// Reports of the last `daily` inside the workspace state directory.
+pub const MARKDOWN:&str = "Report.md";
+
+pub const HTML:&str = "Report.html";
+
+// Lines of an error the report keeps, the summary has all of them.
+const EXCERPT:usize = 6;
+
+// Characters of one such line.
+const WIDTH:usize = 200;
+
+// What the report of a run tells, gathered from its summary.
+pub struct Digest<'a> {
+	pub summary:&'a Summary,
+
+	// Repositories a task changed, with the tasks that did.
+	pub changed:BTreeMap<&'a str, Vec<&'a str>>,
+
+	// Repository, task and the start of the error.
+	pub failed:Vec<(&'a str, &'a str, String)>,
+
+	// Upstream commits merged into every repository.
+	pub merged:BTreeMap<&'a str, Vec<&'a str>>,
+
+	// package.json files every repository had written, with the tasks that wrote them.
+	pub package:BTreeMap<&'a str, BTreeMap<&'a Path, Vec<&'a str>>>,
+}
+
+impl<'a> Digest<'a> {
+	pub fn new(summary:&'a Summary) -> Self {
+		let mut digest = Self {
+			summary,
+			changed:BTreeMap::new(),
+			failed:Vec::new(),
+			merged:BTreeMap::new(),
+			package:BTreeMap::new(),
+		};
+
+		for entry in &summary.entry {
+			let repository = entry.repository.as_str();
+
+			match &entry.outcome {
+				TaskOutcome::Changed => {
+					digest.changed.entry(repository).or_default().push(&entry.task);
+				},
+				TaskOutcome::Failed(chain) => {
+					digest.failed.push((repository, &entry.task, excerpt(chain)));
+				},
+				_ => {},
+			}
+
+			if !entry.merged.is_empty() {
+				digest
+					.merged
+					.entry(repository)
+					.or_default()
+					.extend(entry.merged.iter().map(String::as_str));
+			}
+
+			for path in entry.written.iter().filter(|path| is_package(path)) {
+				digest
+					.package
+					.entry(repository)
+					.or_default()
+					.entry(path.as_path())
+					.or_default()
+					.push(&entry.task);
+			}
+		}
+
+		digest
+	}
+
+	// The counts line of the summary, e.g. `2 changed, 5 unchanged, …`.
+	pub fn total(&self) -> String {
+		self.summary.to_string().lines().last().unwrap_or_default().to_string()
+	}
+
+	pub fn title(&self) -> String {
+		format!(
+			"Maintain run {}{}",
+			self.summary.run,
+			if self.summary.dry_run { " (dry run)" } else { "" }
+		)
+	}
+}
+
+fn is_package(path:&Path) -> bool {
+	path.file_name().is_some_and(|name| name == "package.json")
+}
+
+// The first lines of the error and its causes, each cut short.
+fn excerpt(chain:&[String]) -> String {
+	chain
+		.join("\n")
+		.lines()
+		.filter(|line| !line.trim().is_empty())
+		.take(EXCERPT)
+		.map(|line| {
+			if line.chars().count() > WIDTH {
+				format!("{}…", line.chars().take(WIDTH).collect::<String>())
+			} else {
+				line.to_string()
+			}
+		})
+		.collect::<Vec<_>>()
+		.join("\n")
+}
@@ -0,0 +1,319 @@
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	path::Path,
+	process::Command,
+};
+
+use anyhow::Result;
+
+use crate::Fn::{
+	Task::{
+		Audit::{self, Event},
+		Backup::{self, Record},
+		RepoContext, Task, TaskOutcome,
+	},
+	Workspace::Workspace,
+};
+
+// Puts every checkout the run `run` backed up back the way it was before the run, and with
+// `push` the branches of every remote the run pushed to.
+pub struct RollbackRepository {
+	pub run:String,
+
+	pub push:bool,
+
+	record:BTreeMap<String, Record>,
+
+	// Files the run wrote and moved, by `owner/name`, in the order it did.
+	event:BTreeMap<String, Vec<Event>>,
+
+	// Remotes the run pushed to, by `owner/name`.
+	pushed:BTreeMap<String, BTreeSet<String>>,
+}
+
+impl RollbackRepository {
+	pub fn new(workspace:&Workspace, run:&str, push:bool) -> Result<Self> {
+		let mut event:BTreeMap<String, Vec<Event>> = BTreeMap::new();
+
+		let mut pushed:BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+		for record in Audit::load(&workspace.state.join(Audit::FILE), run)? {
+			match record.event {
+				Event::Write { .. } | Event::Rename { .. } => {
+					event.entry(record.repository).or_default().push(record.event);
+				},
+				Event::Push { remote, .. } => {
+					pushed.entry(record.repository).or_default().insert(remote);
+				},
+				_ => {},
+			}
+		}
+
+		Ok(Self {
+			run:run.to_string(),
+			push,
+			record:Backup::load(&workspace.state, run)?,
+			event,
+			pushed,
+		})
+	}
+}
+
+impl Task for RollbackRepository {
+	fn name(&self) -> &'static str {
+		"rollback"
+	}
+
+	fn description(&self) -> &'static str {
+		"Restore what a run backed up"
+	}
+
+	fn applies(&self, context:&RepoContext) -> bool {
+		context.is_cloned() && self.record.contains_key(&context.repository.full_name())
+	}
+
+	fn parallel(&self) -> bool {
+		true
+	}
+
+	fn run(&self, context:&RepoContext) -> Result<TaskOutcome> {
+		let Some(record) = self.record.get(&context.repository.full_name()) else {
+			return Ok(TaskOutcome::Skipped(format!("not backed up in run {}", self.run)));
+		};
+
+		// Uncommitted changes made since are dropped, the backup has the ones from before
+		match &record.branch {
+			Some(branch) => context.execute(Command::new("git").args([
+				"checkout",
+				"-f",
+				"-B",
+				branch,
+				&record.head,
+			]))?,
+			None => context.execute(Command::new("git").args([
+				"checkout",
+				"-f",
+				"--detach",
+				&record.head,
+			]))?,
+		};
+
+		if let Some(worktree) = &record.worktree {
+			context.execute(Command::new("git").args(["stash", "apply", worktree]))?;
+		}
+
+		if let Some(event) = self.event.get(&context.repository.full_name()) {
+			clean(context, &record.head, event)?;
+		}
+
+		// Remotes the run never pushed to are left as they are, whoever pushed to them since
+		let pushed = self.pushed.get(&context.repository.full_name());
+
+		if self.push {
+			for remote in pushed.into_iter().flatten() {
+				match record.remote.get(remote) {
+					Some(Some(branch)) => push(context, remote, branch)?,
+					Some(None) => context
+						.log(format!("Branches of {} were not backed up, left alone", remote)),
+					None => context
+						.log(format!("{} was not a remote before the run, left alone", remote)),
+				}
+			}
+		}
+
+		Ok(TaskOutcome::Changed)
+	}
+}
+
+// The checkout is back at `head`, but what the run added outside of what it tracks is still there.
+// Files the run created go and what it moved goes back where it came from, latest first.
+fn clean(context:&RepoContext, head:&str, event:&[Event]) -> Result<()> {
+	for event in event.iter().rev() {
+		match event {
+			Event::Write { path, before, .. } => {
+				if !path.exists() || tracked(context, head, path)? {
+					continue;
+				}
+
+				match before {
+					None => context.remove(path)?,
+					Some(_) => {
+						context.log(format!("{} is not tracked, left as written", path.display()))
+					},
+				}
+			},
+			Event::Rename { from, to, .. } => {
+				if !to.exists() || tracked(context, head, to)? {
+					continue;
+				}
+
+				// What was tracked is back in place already, only the copy moved away is left
+				if from.exists() {
+					context.remove(to)?;
+				} else {
+					context.rename(to, from)?;
+				}
+			},
+			_ => {},
+		}
+	}
+
+	Ok(())
+}
+
+// Whether `head` has `path`, or any file below it for a directory. A path outside the checkout
+// counts as tracked, so it is left alone.
+fn tracked(context:&RepoContext, head:&str, path:&Path) -> Result<bool> {
+	let Ok(relative) = path.strip_prefix(&context.path) else {
+		return Ok(true);
+	};
+
+	let tree = context.query(
+		Command::new("git").args(["ls-tree", "-r", "--name-only", head, "--"]).arg(relative),
+	)?;
+
+	Ok(!tree.success() || !tree.stdout.trim().is_empty())
+}
+
+fn push(context:&RepoContext, remote:&str, branch:&BTreeMap<String, String>) -> Result<()> {
+	if !branch.is_empty() {
+		let mut command = Command::new("git");
+
+		command.args(["push", "-f", remote]);
+
+		for (branch, id) in branch {
+			command.arg(format!("{}:refs/heads/{}", id, branch));
+		}
+
+		context.execute(&mut command)?;
+	}
+
+	// Branches made since are left alone, one of them may be the default branch by now
+	let current = context.query(Command::new("git").args(["ls-remote", "--heads", remote]))?;
+
+	for name in
+		current.stdout.lines().filter_map(|line| line.split_once('\t')).map(|(_, name)| name)
+	{
+		if !branch.contains_key(name.strip_prefix("refs/heads/").unwrap_or(name)) {
+			context.log(format!("{} of {} did not exist before, left alone", name, remote));
+		}
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod test {
+	use std::{fs, process::Command};
+
+	use super::RollbackRepository;
+	use crate::Fn::{
+		Move::src::MoveSrc,
+		Task::{
+			Backup::Backup,
+			Task,
+			Test::{git, Scratch},
+		},
+	};
+
+	#[test]
+	fn takes_away_what_the_run_added() {
+		let scratch = Scratch::new("rollback");
+
+		let checkout = scratch.checkout();
+
+		fs::create_dir_all(checkout.join("src")).unwrap();
+
+		fs::write(checkout.join("src/index.ts"), "export {};\n").unwrap();
+
+		git(&checkout, &["add", "."]);
+
+		git(&checkout, &["commit", "-q", "-m", "Source"]);
+
+		// Never tracked, so only moving it back restores it
+		fs::create_dir_all(checkout.join("notes")).unwrap();
+
+		fs::write(checkout.join("notes/todo.md"), "- ship\n").unwrap();
+
+		let context = scratch.context("move src");
+
+		Backup::open(&scratch.workspace.state, &scratch.workspace.run, false)
+			.unwrap()
+			.save(&context)
+			.unwrap();
+
+		MoveSrc.run(&context).unwrap();
+
+		context.write(&checkout.join("LICENSE"), "MIT\n").unwrap();
+
+		context.rename(&checkout.join("notes"), &checkout.join("Notes")).unwrap();
+
+		let rollback =
+			RollbackRepository::new(&scratch.workspace, &scratch.workspace.run, false).unwrap();
+
+		rollback.run(&scratch.context("rollback")).unwrap();
+
+		assert!(checkout.join("src/index.ts").is_file());
+
+		assert!(checkout.join("notes/todo.md").is_file());
+
+		assert!(!checkout.join("Source").exists());
+
+		assert!(!checkout.join("Notes").exists());
+
+		assert!(!checkout.join("LICENSE").exists());
+
+		assert_eq!(git(&checkout, &["status", "--porcelain"]), "?? notes/");
+	}
+
+	#[test]
+	fn pushes_back_the_remotes_the_run_pushed_to() {
+		let scratch = Scratch::new("rollback-push");
+
+		let checkout = scratch.checkout();
+
+		let head = git(&checkout, &["rev-parse", "HEAD"]);
+
+		for remote in ["origin", "fork"] {
+			let bare = format!("{}.git", remote);
+
+			git(
+				&scratch.workspace.root,
+				&["clone", "-q", "--bare", &checkout.to_string_lossy(), &bare],
+			);
+
+			let url = scratch.workspace.root.join(&bare).to_string_lossy().into_owned();
+
+			git(&checkout, &["remote", "add", remote, &url]);
+		}
+
+		let context = scratch.context("sync");
+
+		Backup::open(&scratch.workspace.state, &scratch.workspace.run, false)
+			.unwrap()
+			.save(&context)
+			.unwrap();
+
+		git(&checkout, &["commit", "-q", "--allow-empty", "-m", "Synced"]);
+
+		context.execute(Command::new("git").args(["push", "-q", "fork", "main"])).unwrap();
+
+		// Someone else's push, which the run had nothing to do with
+		git(&checkout, &["push", "-q", "origin", "main"]);
+
+		let synced = git(&checkout, &["rev-parse", "HEAD"]);
+
+		let remote = |name:&str| git(&scratch.workspace.root.join(name), &["rev-parse", "main"]);
+
+		let rollback =
+			RollbackRepository::new(&scratch.workspace, &scratch.workspace.run, true).unwrap();
+
+		rollback.run(&scratch.context("rollback")).unwrap();
+
+		assert_eq!(remote("fork.git"), head);
+
+		assert_eq!(remote("origin.git"), synced);
+
+		assert_eq!(git(&checkout, &["rev-parse", "HEAD"]), head);
+	}
+}
@@ -0,0 +1,345 @@
+use anyhow::{bail, Result};
+use regex::Regex;
+
+use crate::Fn::{Cache::Exclude, Select::Subject};
+
+// A parsed `--filter`, e.g. `tag:extension && !archived && parent.owner:microsoft`.
+//
+//	expression := and ( "||" and )*
+//	and        := unary ( "&&" unary )*
+//	unary      := "!" unary | "(" expression ")" | key [ ":" value ]
+//
+// Values are globs, or regular expressions between slashes, and may be double-quoted.
+#[derive(Clone, Debug)]
+pub enum Expression {
+	And(Box<Expression>, Box<Expression>),
+
+	Or(Box<Expression>, Box<Expression>),
+
+	Not(Box<Expression>),
+
+	Test(Key, Option<Regex>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+	// `owner/name`
+	Repository,
+
+	Owner,
+
+	Name,
+
+	Tag,
+
+	Topic,
+
+	Forge,
+
+	Branch,
+
+	Archived,
+
+	Fork,
+
+	// `owner/name` of the parent
+	Parent,
+
+	ParentOwner,
+
+	ParentName,
+}
+
+impl Key {
+	fn parse(key:&str) -> Result<Self> {
+		Ok(match key {
+			"repo" | "repository" => Self::Repository,
+			"owner" => Self::Owner,
+			"name" => Self::Name,
+			"tag" => Self::Tag,
+			"topic" => Self::Topic,
+			"forge" => Self::Forge,
+			"branch" => Self::Branch,
+			"archived" => Self::Archived,
+			"fork" => Self::Fork,
+			"parent" => Self::Parent,
+			"parent.owner" => Self::ParentOwner,
+			"parent.name" => Self::ParentName,
+			_ => bail!("Unknown filter key {}", key),
+		})
+	}
+
+	// Flags only ever test presence.
+	fn is_flag(self) -> bool {
+		matches!(self, Self::Archived | Self::Fork)
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+	And,
+
+	Or,
+
+	Not,
+
+	Open,
+
+	Close,
+
+	Word(String),
+}
+
+impl Expression {
+	pub fn parse(source:&str) -> Result<Self> {
+		let token = tokenize(source)?;
+
+		let mut position = 0;
+
+		let expression = or(&token, &mut position)?;
+
+		if let Some(token) = token.get(position) {
+			bail!("Unexpected {:?} in filter {}", token, source);
+		}
+
+		Ok(expression)
+	}
+
+	pub fn matches(&self, subject:&Subject) -> bool {
+		match self {
+			Self::And(left, right) => left.matches(subject) && right.matches(subject),
+			Self::Or(left, right) => left.matches(subject) || right.matches(subject),
+			Self::Not(expression) => !expression.matches(subject),
+			Self::Test(key, value) => {
+				let value =
+					|candidate:&str| value.as_ref().is_none_or(|value| value.is_match(candidate));
+
+				match key {
+					Key::Repository => value(&subject.repository.full_name()),
+					Key::Owner => value(&subject.repository.owner),
+					Key::Name => value(&subject.repository.name),
+					Key::Tag => subject.repository.tag.iter().any(|tag| value(tag)),
+					Key::Topic => subject.topics().iter().any(|topic| value(topic)),
+					Key::Forge => value(subject.forge()),
+					Key::Branch => value(subject.branch()),
+					Key::Archived => subject.archived(),
+					Key::Fork => subject.fork(),
+					Key::Parent => subject
+						.parent()
+						.is_some_and(|(owner, name)| value(&format!("{}/{}", owner, name))),
+					Key::ParentOwner => subject.parent().is_some_and(|(owner, _)| value(owner)),
+					Key::ParentName => subject.parent().is_some_and(|(_, name)| value(name)),
+				}
+			},
+		}
+	}
+}
+
+fn or(token:&[Token], position:&mut usize) -> Result<Expression> {
+	let mut left = and(token, position)?;
+
+	while token.get(*position) == Some(&Token::Or) {
+		*position += 1;
+
+		left = Expression::Or(Box::new(left), Box::new(and(token, position)?));
+	}
+
+	Ok(left)
+}
+
+fn and(token:&[Token], position:&mut usize) -> Result<Expression> {
+	let mut left = unary(token, position)?;
+
+	while token.get(*position) == Some(&Token::And) {
+		*position += 1;
+
+		left = Expression::And(Box::new(left), Box::new(unary(token, position)?));
+	}
+
+	Ok(left)
+}
+
+fn unary(token:&[Token], position:&mut usize) -> Result<Expression> {
+	let Some(current) = token.get(*position) else {
+		bail!("Filter ends too early");
+	};
+
+	*position += 1;
+
+	match current {
+		Token::Not => Ok(Expression::Not(Box::new(unary(token, position)?))),
+		Token::Open => {
+			let expression = or(token, position)?;
+
+			if token.get(*position) != Some(&Token::Close) {
+				bail!("Missing ) in filter");
+			}
+
+			*position += 1;
+
+			Ok(expression)
+		},
+		Token::Word(word) => {
+			let (key, value) = match word.split_once(':') {
+				Some((key, value)) => (Key::parse(key)?, Some(value)),
+				None => (Key::parse(word)?, None),
+			};
+
+			if key.is_flag() && value.is_some() {
+				bail!("{} takes no value, negate it with ! instead", word);
+			}
+
+			Ok(Expression::Test(key, value.map(Exclude::compile).transpose()?))
+		},
+		token => bail!("Unexpected {:?} in filter", token),
+	}
+}
+
+fn tokenize(source:&str) -> Result<Vec<Token>> {
+	let mut token = Vec::new();
+
+	let mut character = source.chars().peekable();
+
+	while let Some(&current) = character.peek() {
+		match current {
+			_ if current.is_whitespace() => {
+				character.next();
+			},
+			'(' | ')' | '!' => {
+				character.next();
+
+				token.push(match current {
+					'(' => Token::Open,
+					')' => Token::Close,
+					_ => Token::Not,
+				});
+			},
+			'&' | '|' => {
+				character.next();
+
+				if character.next() != Some(current) {
+					bail!("Expected {0}{0} in filter {1}", current, source);
+				}
+
+				token.push(if current == '&' { Token::And } else { Token::Or });
+			},
+			_ => {
+				let mut word = String::new();
+
+				while let Some(&current) = character.peek() {
+					if current.is_whitespace() || "()!&|".contains(current) {
+						break;
+					}
+
+					character.next();
+
+					if current == '"' {
+						loop {
+							match character.next() {
+								Some('"') => break,
+								Some(quoted) => word.push(quoted),
+								None => bail!("Unterminated quote in filter {}", source),
+							}
+						}
+					} else {
+						word.push(current);
+					}
+				}
+
+				token.push(Token::Word(word));
+			},
+		}
+	}
+
+	Ok(token)
+}
+
+#[cfg(test)]
+mod test {
+	use super::Expression;
+	use crate::Fn::{Cache::Snapshot::Entry, Forge, Manifest::Repository, Select::Subject};
+
+	// `owner/name` tagged `tag`, listed as a fork of `parent` and archived or not.
+	fn entry(name:&str, tag:&[&str], parent:Option<&str>, archived:bool) -> (Repository, Entry) {
+		let (owner, name) = name.split_once('/').unwrap();
+
+		let repository = Repository {
+			tag:tag.iter().map(|tag| tag.to_string()).collect(),
+			..Repository::new(owner, name)
+		};
+
+		let entry = Entry {
+			forge:"github".to_string(),
+			repository:Forge::Repository {
+				owner:owner.to_string(),
+				name:name.to_string(),
+				fork:parent.is_some(),
+				archived,
+				parent:parent.map(str::to_string),
+				topics:vec!["editor".to_string()],
+				..Default::default()
+			},
+		};
+
+		(repository, entry)
+	}
+
+	fn matches(filter:&str, (repository, entry):&(Repository, Entry)) -> bool {
+		Expression::parse(filter).unwrap().matches(&Subject { repository, cache:Some(entry) })
+	}
+
+	#[test]
+	fn matches_keys_and_values() {
+		let fork = entry("CodeEditorLand/vscode", &["extension"], Some("microsoft/vscode"), false);
+
+		assert!(matches("tag:extension", &fork));
+
+		assert!(matches("topic:edit*", &fork));
+
+		assert!(matches("parent.owner:microsoft", &fork));
+
+		assert!(matches("parent:microsoft/vscode", &fork));
+
+		assert!(matches("name:/^vs/", &fork));
+
+		assert!(matches("repo:\"CodeEditorLand/*\"", &fork));
+
+		assert!(matches("fork", &fork));
+
+		assert!(!matches("archived", &fork));
+
+		assert!(!matches("tag:theme", &fork));
+	}
+
+	#[test]
+	fn binds_not_over_and_over_or() {
+		let archived = entry("CodeEditorLand/old", &[], None, true);
+
+		assert!(matches("!fork && archived", &archived));
+
+		assert!(matches("tag:theme || archived && !fork", &archived));
+
+		assert!(!matches("(tag:theme || archived) && fork", &archived));
+
+		assert!(matches("!(tag:theme || fork)", &archived));
+
+		assert!(matches("!!archived", &archived));
+	}
+
+	#[test]
+	fn rejects_malformed_filters() {
+		for filter in [
+			"",
+			"tag:a &&",
+			"tag:a & tag:b",
+			"(tag:a",
+			"tag:a)",
+			"colour:red",
+			"archived:yes",
+			"name:\"open",
+			"name:/(/",
+		] {
+			assert!(Expression::parse(filter).is_err(), "{:?} parsed", filter);
+		}
+	}
+}
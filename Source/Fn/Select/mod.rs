@@ -0,0 +1,152 @@
+pub mod Expression;
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use regex::Regex;
+
+use crate::Fn::{
+	Cache::{Exclude, Snapshot},
+	Forge::{self, Fleet},
+	Manifest::{Manifest, Repository},
+};
+
+// Which repositories of the manifest a task works on. Empty means all of them.
+#[derive(Clone, Debug, Default)]
+pub struct Selector {
+	only:Vec<Regex>,
+
+	except:Vec<Regex>,
+
+	expression:Option<Expression::Expression>,
+
+	// The selection this one narrows, which a repository has to match as well.
+	within:Option<Box<Selector>>,
+}
+
+// A manifest entry together with what the last `Cache::Get` learned about it, if anything.
+pub struct Subject<'a> {
+	pub repository:&'a Repository,
+
+	pub cache:Option<&'a Snapshot::Entry>,
+}
+
+impl Subject<'_> {
+	fn remote(&self) -> Option<&Forge::Repository> {
+		self.cache.map(|entry| &entry.repository)
+	}
+
+	pub fn forge(&self) -> &str {
+		self.cache
+			.map(|entry| entry.forge.as_str())
+			.or(self.repository.forge.as_deref())
+			.unwrap_or(Fleet::DEFAULT)
+	}
+
+	pub fn branch(&self) -> &str {
+		self.repository
+			.branch
+			.as_deref()
+			.or(self.remote().map(|remote| remote.default_branch.as_str()))
+			.filter(|branch| !branch.is_empty())
+			.unwrap_or("main")
+	}
+
+	pub fn parent(&self) -> Option<(&str, &str)> {
+		self.repository.parent().or_else(|| {
+			self.remote()?.parent.as_deref().and_then(|parent| Forge::split(parent).ok())
+		})
+	}
+
+	pub fn topics(&self) -> &[String] {
+		self.remote().map_or(&[], |remote| &remote.topics)
+	}
+
+	pub fn archived(&self) -> bool {
+		self.remote().is_some_and(|remote| remote.archived)
+	}
+
+	pub fn fork(&self) -> bool {
+		self.parent().is_some() || self.remote().is_some_and(|remote| remote.fork)
+	}
+}
+
+impl Selector {
+	// `only` and `except` are globs over `owner/name` or the bare name; commas separate several.
+	pub fn new(only:&[String], except:&[String], expression:Option<&str>) -> Result<Self> {
+		let compile = |pattern:&[String]| -> Result<Vec<Regex>> {
+			pattern
+				.iter()
+				.flat_map(|pattern| pattern.split(','))
+				.map(str::trim)
+				.filter(|pattern| !pattern.is_empty())
+				.map(Exclude::compile)
+				.collect()
+		};
+
+		Ok(Self {
+			only:compile(only)?,
+			except:compile(except)?,
+			expression:expression
+				.filter(|expression| !expression.trim().is_empty())
+				.map(Expression::Expression::parse)
+				.transpose()?,
+			within:None,
+		})
+	}
+
+	// `narrow` applied on top of this selection, e.g. a pipeline step within the command line's.
+	pub fn narrow(&self, narrow:Selector) -> Self {
+		if self.is_empty() {
+			return narrow;
+		}
+
+		Self { within:Some(Box::new(self.clone())), ..narrow }
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.only.is_empty()
+			&& self.except.is_empty()
+			&& self.expression.is_none()
+			&& self.within.is_none()
+	}
+
+	pub fn matches(&self, subject:&Subject) -> bool {
+		let name = |pattern:&Regex| {
+			pattern.is_match(&subject.repository.full_name())
+				|| pattern.is_match(&subject.repository.name)
+		};
+
+		(self.only.is_empty() || self.only.iter().any(name))
+			&& !self.except.iter().any(name)
+			&& self.expression.as_ref().is_none_or(|expression| expression.matches(subject))
+			&& self.within.as_ref().is_none_or(|within| within.matches(subject))
+	}
+
+	pub fn select<'a>(
+		&self,
+		manifest:&'a Manifest,
+		snapshot:Option<&Snapshot::Snapshot>,
+	) -> Vec<&'a Repository> {
+		let cache = snapshot
+			.map(|snapshot| {
+				snapshot
+					.repository
+					.iter()
+					.map(|entry| (entry.repository.full_name().to_lowercase(), entry))
+					.collect::<BTreeMap<_, _>>()
+			})
+			.unwrap_or_default();
+
+		manifest
+			.repository
+			.iter()
+			.filter(|repository| {
+				self.matches(&Subject {
+					repository,
+					cache:cache.get(&repository.full_name().to_lowercase()).copied(),
+				})
+			})
+			.collect()
+	}
+}
@@ -1,70 +1,52 @@
 use std::process::Command;
 
-fn main() {
-	println!("Process: Sort/Detail.sh");
+use anyhow::Result;
 
-	// Context: CodeEditorLand/Environment/Stream
+use crate::Fn::{
+	Action,
+	Task::{RepoContext, Task, TaskOutcome},
+};
 
-	for repository in std::fs::read_to_string(
-		std::env::current_dir()
-			.expect("Failed to get current directory")
-			.join("../Cache/Repository/Build.md"),
-	)?
-	.lines()
-	.map(|s| s.to_string())
-	.collect()
-	.expect("Failed to read repositories")
-	{
-		let folder = repository.replace("CodeEditorLand/", "");
+#[derive(Default)]
+pub struct SortDetail;
 
-		let output =
-			Command::new("cd").arg(&folder).output().expect("Failed to execute cd command");
+impl Task for SortDetail {
+	fn name(&self) -> &'static str {
+		"sort detail"
+	}
+
+	fn description(&self) -> &'static str {
+		"Sort package.json with sort-package-json"
+	}
 
-		println!("{}", String::from_utf8_lossy(&output.stdout));
+	fn applies(&self, context:&RepoContext) -> bool {
+		context.path.exists()
+	}
 
-		let output = Command::new("pwd").output().expect("Failed to execute pwd command");
+	fn parallel(&self) -> bool {
+		true
+	}
 
-		println!("{}", String::from_utf8_lossy(&output.stdout));
+	fn backup(&self) -> bool {
+		true
+	}
 
-		let output = Command::new("find")
-			.arg(".")
-			.arg("-type")
-			.arg("d")
-			.arg("(")
-			.arg("-iname")
-			.arg("node_modules")
-			.arg("-o")
-			.arg("-iname")
-			.arg("vendor")
-			.arg("-o")
-			.arg("-iname")
-			.arg("dist")
-			.arg("-o")
-			.arg("-iname")
-			.arg("target")
-			.arg("-o")
-			.arg("-iname")
-			.arg(".git")
-			.arg("-o")
-			.arg("-iname")
-			.arg(".next")
-			.arg(")")
-			.arg("-prune")
-			.arg("-false")
-			.arg("-o")
-			.arg("-iname")
-			.arg("package.json")
-			.arg("-type")
-			.arg("f")
-			.arg("-execdir")
-			.arg("sort-package-json")
-			.output()
-			.expect("Failed to execute find command");
+	fn run(&self, context:&RepoContext) -> Result<TaskOutcome> {
+		let package = Action::Find::Fn(&context.path, "package.json");
 
-		println!("{}", String::from_utf8_lossy(&output.stdout));
+		if package.is_empty() {
+			return Ok(TaskOutcome::Unchanged);
+		}
 
-		let output = Command::new("cd").arg("-").output().expect("Failed to execute cd - command");
+		// Same as `find . … -iname package.json -type f -execdir sort-package-json`
+		let written =
+			context.execute_writing(Command::new("sort-package-json").args(&package), &package)?;
 
-		println!("{}", String::from_utf8_lossy(&output.stdout));
+		// A dry run cannot tell what sorting would change
+		Ok(if written.is_empty() && !context.is_dry_run() {
+			TaskOutcome::Unchanged
+		} else {
+			TaskOutcome::Changed
+		})
 	}
 }
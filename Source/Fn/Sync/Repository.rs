@@ -1,55 +1,53 @@
-use std::process::Command;
-
-fn main() {
-	println!("Process: Sync/Repository.sh");
-
-	// Context: CodeEditorLand/Environment/Stream
-	for repository in read_array(
-		&std::fs::read_to_string(
-			std::env::current_dir()
-				.expect("Failed to get current directory")
-				.join("../Cache/Repository/Build.md"),
-		)?
-		.lines()
-		.map(|s| s.to_string())
-		.collect(),
-	)
-	.expect("Failed to read repositories")
-	{
-		println!(
-			"{}",
-			String::from_utf8_lossy(
-				&Command::new("cd")
-					.arg(&repository.replace("CodeEditorLand/", ""))
-					.output()
-					.expect("Failed to execute cd command")
-					.stdout
-			)
-		);
-
-		println!(
-			"{}",
-			String::from_utf8_lossy(
-				&Command::new("git")
-					.arg("add")
-					.arg(".")
-					.output()
-					.expect("Failed to execute git add command")
-					.stdout
-			)
-		);
-
-		// TODO: Add more commands here...
-
-		println!(
-			"{}",
-			String::from_utf8_lossy(
-				&Command::new("cd")
-					.arg("-")
-					.output()
-					.expect("Failed to execute cd - command")
-					.stdout
-			)
-		);
+use anyhow::Result;
+use serde::Deserialize;
+
+use crate::Fn::{
+	Sync::Upstream::Upstream,
+	Task::{RepoContext, Task, TaskOutcome},
+};
+
+// Takes in the commits of the parent with `strategy`, see `Upstream` for the rest.
+#[derive(Default, Deserialize)]
+#[serde(transparent)]
+pub struct SyncRepository {
+	pub upstream:Upstream,
+}
+
+impl Task for SyncRepository {
+	fn name(&self) -> &'static str {
+		"sync"
+	}
+
+	fn description(&self) -> &'static str {
+		"Sync forks with their parent"
+	}
+
+	fn applies(&self, context:&RepoContext) -> bool {
+		context.is_cloned()
+	}
+
+	fn parallel(&self) -> bool {
+		true
+	}
+
+	fn backup(&self) -> bool {
+		true
+	}
+
+	fn run(&self, context:&RepoContext) -> Result<TaskOutcome> {
+		let parent = &self.upstream.parent;
+
+		// Not every repository is a fork
+		if !parent.exists(context)? {
+			return Ok(TaskOutcome::Skipped(format!("no {} remote", parent)));
+		}
+
+		let synced = self.upstream.sync(context)?;
+
+		Ok(if synced.commit.is_empty() && !synced.pushed {
+			TaskOutcome::Unchanged
+		} else {
+			TaskOutcome::Changed
+		})
 	}
 }
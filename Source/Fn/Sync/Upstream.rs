@@ -0,0 +1,743 @@
+use std::{fmt, path::PathBuf, process::Command, str::FromStr};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::Fn::Task::{Execution, RepoContext};
+
+// How a fork takes in what its parent did since they parted.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Strategy {
+	// A merge commit on top of the fork, which keeps both histories.
+	#[default]
+	Merge,
+
+	// The commits of the fork replayed on the parent, which rewrites the branch.
+	RebaseForkCommits,
+
+	// The parent as it is, with the `overlay` paths of the fork put back in one commit on top.
+	ResetToParentPlusOverlay,
+
+	// Only when the fork has no commits of its own.
+	FastForwardOnly,
+}
+
+impl Strategy {
+	pub const ALL:[Self; 4] = [
+		Self::Merge,
+		Self::RebaseForkCommits,
+		Self::ResetToParentPlusOverlay,
+		Self::FastForwardOnly,
+	];
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			Self::Merge => "merge",
+			Self::RebaseForkCommits => "rebase-fork-commits",
+			Self::ResetToParentPlusOverlay => "reset-to-parent-plus-overlay",
+			Self::FastForwardOnly => "fast-forward-only",
+		}
+	}
+
+	// Whether the branch may lose commits the remote has, so pushing it takes a force.
+	pub fn is_rewriting(&self) -> bool {
+		matches!(self, Self::RebaseForkCommits | Self::ResetToParentPlusOverlay)
+	}
+}
+
+impl FromStr for Strategy {
+	type Err = anyhow::Error;
+
+	fn from_str(name:&str) -> Result<Self> {
+		Self::ALL.into_iter().find(|strategy| strategy.name() == name).with_context(|| {
+			format!(
+				"Unknown strategy {}, expected one of {}",
+				name,
+				Self::ALL.map(|strategy| strategy.name()).join(", ")
+			)
+		})
+	}
+}
+
+impl fmt::Display for Strategy {
+	fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.name())
+	}
+}
+
+// The name of a remote of the checkout, one that can stand in a refspec.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct Remote(String);
+
+impl Remote {
+	pub fn new(name:&str) -> Result<Self> {
+		if name.is_empty()
+			|| name.starts_with('-')
+			|| name
+				.chars()
+				.any(|character| character.is_whitespace() || ":~^?*[\\".contains(character))
+		{
+			bail!("Invalid remote name {:?}", name);
+		}
+
+		Ok(Self(name.to_string()))
+	}
+
+	pub fn name(&self) -> &str {
+		&self.0
+	}
+
+	// Where fetching `branch` of this remote leaves it.
+	pub fn tracking(&self, branch:&str) -> String {
+		format!("refs/remotes/{}/{}", self.0, branch)
+	}
+
+	pub fn exists(&self, context:&RepoContext) -> Result<bool> {
+		Ok(context
+			.query(Command::new("git").arg("remote"))?
+			.stdout
+			.lines()
+			.any(|remote| remote.trim() == self.0))
+	}
+}
+
+impl TryFrom<String> for Remote {
+	type Error = anyhow::Error;
+
+	fn try_from(name:String) -> Result<Self> {
+		Self::new(&name)
+	}
+}
+
+impl From<Remote> for String {
+	fn from(remote:Remote) -> Self {
+		remote.0
+	}
+}
+
+impl fmt::Display for Remote {
+	fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&self.0)
+	}
+}
+
+// Brings the current branch of a checkout up to date with a branch of its parent.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Upstream {
+	pub strategy:Strategy,
+
+	// The parent repository, as `configure` adds it.
+	pub parent:Remote,
+
+	// The fork itself, which `push` updates.
+	pub fork:Remote,
+
+	// Branch of the parent to take in, its default branch unless set.
+	pub branch:Option<String>,
+
+	// Paths of the fork `reset-to-parent-plus-overlay` keeps over the parent, e.g. `package.json`.
+	pub overlay:Vec<PathBuf>,
+
+	// Pushes the synced branch to `fork`, leased on what it was when rewriting it.
+	pub push:bool,
+}
+
+impl Default for Upstream {
+	fn default() -> Self {
+		Self {
+			strategy:Strategy::default(),
+			parent:Remote("upstream".to_string()),
+			fork:Remote("origin".to_string()),
+			branch:None,
+			overlay:Vec::new(),
+			push:false,
+		}
+	}
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Commit {
+	pub id:String,
+
+	pub subject:String,
+}
+
+impl fmt::Display for Commit {
+	fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{} {}", self.id.get(..12).unwrap_or(&self.id), self.subject)
+	}
+}
+
+// What a sync did to one checkout. A dry run leaves `new` at `old`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Synced {
+	pub strategy:Strategy,
+
+	// The local branch that was synced.
+	pub branch:String,
+
+	// The parent branch it took in, e.g. `upstream/main`, and where that pointed.
+	pub upstream:String,
+
+	pub parent:String,
+
+	// Tips of `branch` before and after.
+	pub old:String,
+
+	pub new:String,
+
+	// Commits of the parent `old` did not have, newest first.
+	pub commit:Vec<Commit>,
+
+	// Tip of `branch` on the fork before the sync, `None` when it has no such branch or `push` is
+	// off.
+	pub fork:Option<String>,
+
+	pub pushed:bool,
+}
+
+impl Upstream {
+	pub fn sync(&self, context:&RepoContext) -> Result<Synced> {
+		if !self.parent.exists(context)? {
+			bail!("The checkout has no {} remote, configure adds it", self.parent);
+		}
+
+		let branch = context
+			.query(Command::new("git").args(["symbolic-ref", "--short", "-q", "HEAD"]))?
+			.stdout
+			.trim()
+			.to_string();
+
+		if branch.is_empty() {
+			bail!("HEAD is detached, there is no branch to sync");
+		}
+
+		// A shallow clone has no merge base to work from
+		if context
+			.query(Command::new("git").args(["rev-parse", "--is-shallow-repository"]))?
+			.stdout
+			.trim() == "true"
+		{
+			context.execute(Command::new("git").args([
+				"fetch",
+				"--unshallow",
+				"--no-tags",
+				self.fork.name(),
+			]))?;
+		}
+
+		let source = match &self.branch {
+			Some(branch) => branch.clone(),
+			None => self.default_branch(context)?,
+		};
+
+		let tracking = self.parent.tracking(&source);
+
+		context.execute(Command::new("git").args([
+			"fetch",
+			"--no-tags",
+			self.parent.name(),
+			&format!("+refs/heads/{}:{}", source, tracking),
+		]))?;
+
+		let old = resolve(context, "HEAD")?.context("The branch has no commits yet")?;
+
+		let mut synced = Synced {
+			strategy:self.strategy,
+			branch,
+			upstream:format!("{}/{}", self.parent, source),
+			new:old.clone(),
+			old,
+			..Default::default()
+		};
+
+		// A dry run did not fetch, so only a parent fetched before shows what it would bring in
+		let Some(parent) = resolve(context, &tracking)? else {
+			context.log(format!("Would fetch {} first", synced.upstream));
+
+			return Ok(synced);
+		};
+
+		synced.parent = parent;
+
+		synced.commit = context
+			.query(Command::new("git").args([
+				"log",
+				"--format=%H%x09%s",
+				&format!("{}..{}", synced.old, synced.parent),
+			]))?
+			.stdout
+			.lines()
+			.filter_map(|line| line.split_once('\t'))
+			.map(|(id, subject)| Commit { id:id.to_string(), subject:subject.to_string() })
+			.collect();
+
+		// Taken before anything rewrites the branch, the push leases on it
+		if self.push {
+			synced.fork = self.tip(context, &synced.branch)?;
+		}
+
+		if synced.commit.is_empty() {
+			context.log(format!("{} has everything of {}", synced.branch, synced.upstream));
+		} else {
+			match self.strategy {
+				Strategy::Merge => self.merge(context, &synced)?,
+				Strategy::RebaseForkCommits => self.rebase(context, &synced)?,
+				Strategy::ResetToParentPlusOverlay => self.overlay(context, &synced)?,
+				Strategy::FastForwardOnly => self.fast_forward(context, &synced)?,
+			}
+
+			if !context.is_dry_run() {
+				synced.new = resolve(context, "HEAD")?.context("The branch lost its commits")?;
+
+				context.report_merged(synced.commit.iter().map(|commit| commit.to_string()));
+			}
+
+			context.log(format!(
+				"{} commits of {} into {} by {}",
+				synced.commit.len(),
+				synced.upstream,
+				synced.branch,
+				synced.strategy
+			));
+		}
+
+		// Also a branch an earlier sync left unpushed
+		if self.push && synced.fork.as_ref() != Some(&synced.new) {
+			self.publish(context, &synced)?;
+
+			synced.pushed = true;
+		}
+
+		Ok(synced)
+	}
+
+	// Where `branch` of the fork points, `None` when the fork has no such branch.
+	fn tip(&self, context:&RepoContext, branch:&str) -> Result<Option<String>> {
+		let execution = context.query(Command::new("git").args([
+			"ls-remote",
+			self.fork.name(),
+			&format!("refs/heads/{}", branch),
+		]))?;
+
+		if !execution.success() {
+			return Err(anyhow::Error::from(execution))
+				.with_context(|| format!("Failed to list {}", self.fork));
+		}
+
+		Ok(execution.stdout.split_whitespace().next().map(str::to_string))
+	}
+
+	// The branch `HEAD` of the parent points to.
+	fn default_branch(&self, context:&RepoContext) -> Result<String> {
+		let execution = context.query(Command::new("git").args([
+			"ls-remote",
+			"--symref",
+			self.parent.name(),
+			"HEAD",
+		]))?;
+
+		if !execution.success() {
+			return Err(anyhow::Error::from(execution))
+				.with_context(|| format!("Failed to list {}", self.parent));
+		}
+
+		execution
+			.stdout
+			.lines()
+			.find_map(|line| line.strip_prefix("ref: refs/heads/")?.strip_suffix("\tHEAD"))
+			.map(str::to_string)
+			.with_context(|| format!("{} does not say what its default branch is", self.parent))
+	}
+
+	fn merge(&self, context:&RepoContext, synced:&Synced) -> Result<()> {
+		let result = context.execute(identity(Command::new("git").args([
+			"merge",
+			"--no-edit",
+			"-m",
+			&format!("Merge {} into {}", synced.upstream, synced.branch),
+			&synced.parent,
+		])));
+
+		abort(context, result, "merge", synced)
+	}
+
+	fn rebase(&self, context:&RepoContext, synced:&Synced) -> Result<()> {
+		// Authors stay who they were, only the committer is this tool
+		let result = context.execute(
+			Command::new("git")
+				.args(["rebase", &synced.parent])
+				.env("GIT_COMMITTER_NAME", "Maintain")
+				.env("GIT_COMMITTER_EMAIL", "maintain@localhost"),
+		);
+
+		abort(context, result, "rebase", synced)
+	}
+
+	fn overlay(&self, context:&RepoContext, synced:&Synced) -> Result<()> {
+		// Only what the fork had can be put back
+		let mut path = Vec::new();
+
+		for overlay in &self.overlay {
+			let spec = format!("{}:{}", synced.old, overlay.display());
+
+			if context.query(Command::new("git").args(["cat-file", "-e", &spec]))?.success() {
+				path.push(overlay);
+			} else {
+				context.log(format!(
+					"{} had no {}, nothing to keep",
+					synced.branch,
+					overlay.display()
+				));
+			}
+		}
+
+		context.execute(Command::new("git").args(["reset", "--hard", &synced.parent]))?;
+
+		if path.is_empty() {
+			return Ok(());
+		}
+
+		context.execute(Command::new("git").args(["checkout", &synced.old, "--"]).args(&path))?;
+
+		// The parent may have had the same content already
+		if context.query(Command::new("git").args(["diff", "--cached", "--quiet"]))?.success() {
+			return Ok(());
+		}
+
+		context.execute(identity(Command::new("git").args([
+			"commit",
+			"--no-verify",
+			"-m",
+			&format!("Keep {} of the fork over {}", list(&path), synced.upstream),
+		])))?;
+
+		Ok(())
+	}
+
+	fn fast_forward(&self, context:&RepoContext, synced:&Synced) -> Result<()> {
+		let ancestor = context.query(Command::new("git").args([
+			"merge-base",
+			"--is-ancestor",
+			&synced.old,
+			&synced.parent,
+		]))?;
+
+		if !ancestor.success() {
+			bail!(
+				"{} has commits {} does not, it cannot fast-forward",
+				synced.branch,
+				synced.upstream
+			);
+		}
+
+		context.execute(Command::new("git").args(["merge", "--ff-only", &synced.parent]))?;
+
+		Ok(())
+	}
+
+	fn publish(&self, context:&RepoContext, synced:&Synced) -> Result<()> {
+		let target = format!("refs/heads/{}", synced.branch);
+
+		let mut command = Command::new("git");
+
+		command.arg("push");
+
+		// Only over what the fork had when the sync started, never over a push since. No tip means
+		// the branch must not exist yet.
+		if self.strategy.is_rewriting() {
+			command.arg(format!(
+				"--force-with-lease={}:{}",
+				target,
+				synced.fork.as_deref().unwrap_or_default()
+			));
+		}
+
+		context.execute(command.args([self.fork.name(), &format!("HEAD:{}", target)]))?;
+
+		Ok(())
+	}
+}
+
+// The commit `name` points to, `None` when it does not exist.
+fn resolve(context:&RepoContext, name:&str) -> Result<Option<String>> {
+	let execution = context.query(Command::new("git").args([
+		"rev-parse",
+		"--verify",
+		"--quiet",
+		&format!("{}^{{commit}}", name),
+	]))?;
+
+	Ok(Some(execution.stdout.trim().to_string()).filter(|_| execution.success()))
+}
+
+// Commits the sync makes are the tool's, whoever the checkout is configured for.
+fn identity(command:&mut Command) -> &mut Command {
+	command
+		.env("GIT_AUTHOR_NAME", "Maintain")
+		.env("GIT_AUTHOR_EMAIL", "maintain@localhost")
+		.env("GIT_COMMITTER_NAME", "Maintain")
+		.env("GIT_COMMITTER_EMAIL", "maintain@localhost")
+}
+
+// Leaves the checkout as it was when `operation` stopped halfway, on a conflict.
+fn abort(
+	context:&RepoContext,
+	result:Result<Execution>,
+	operation:&str,
+	synced:&Synced,
+) -> Result<()> {
+	let Err(error) = result else {
+		return Ok(());
+	};
+
+	let _ = context.execute(Command::new("git").args([operation, "--abort"]));
+
+	Err(error).with_context(|| {
+		format!(
+			"Failed to {} {} into {}, left as it was",
+			operation, synced.upstream, synced.branch
+		)
+	})
+}
+
+fn list(path:&[&PathBuf]) -> String {
+	path.iter().map(|path| path.display().to_string()).collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(test)]
+mod test {
+	use std::{fs, path::Path};
+
+	use super::{Strategy, Upstream};
+	use crate::Fn::Task::Test::{git, Scratch};
+
+	// The checkout of `scratch` as a fork: `origin` is a bare copy of it, `upstream` a parent with
+	// one commit more.
+	fn fork(scratch:&Scratch) -> (String, String) {
+		let checkout = scratch.checkout();
+
+		let remote = scratch.workspace.root.join("remote");
+
+		std::fs::create_dir_all(&remote).unwrap();
+
+		let origin = remote.join("origin.git").display().to_string();
+
+		let parent = remote.join("parent.git").display().to_string();
+
+		git(&checkout, &["clone", "-q", "--bare", ".", &origin]);
+
+		git(&checkout, &["clone", "-q", "--bare", ".", &parent]);
+
+		git(&checkout, &["remote", "add", "origin", &origin]);
+
+		git(&checkout, &["remote", "add", "upstream", &parent]);
+
+		let work = remote.join("work");
+
+		git(&remote, &["clone", "-q", &parent, "work"]);
+
+		git(&work, &["commit", "-q", "--allow-empty", "-m", "Parent"]);
+
+		git(&work, &["push", "-q", "origin", "HEAD:main"]);
+
+		(origin, parent)
+	}
+
+	fn head(path:&Path) -> String {
+		git(path, &["rev-parse", "main"])
+	}
+
+	// Commits `file` with `content` in the checkout at `path`.
+	fn commit(path:&Path, file:&str, content:&str) {
+		fs::write(path.join(file), content).unwrap();
+
+		git(path, &["add", file]);
+
+		git(path, &["commit", "-q", "-m", &format!("Change {}", file)]);
+	}
+
+	// One more commit of the parent, pushed from its working copy.
+	fn parent(scratch:&Scratch, file:&str, content:&str) {
+		let work = scratch.workspace.root.join("remote").join("work");
+
+		commit(&work, file, content);
+
+		git(&work, &["push", "-q", "origin", "HEAD:main"]);
+	}
+
+	#[test]
+	fn pushes_a_branch_synced_before() {
+		let scratch = Scratch::new("sync-push");
+
+		let (origin, _) = fork(&scratch);
+
+		let context = scratch.context("sync");
+
+		let synced = Upstream::default().sync(&context).unwrap();
+
+		assert_eq!(synced.commit.len(), 1);
+
+		assert!(!synced.pushed);
+
+		assert_ne!(head(Path::new(&origin)), synced.new);
+
+		// Nothing new upstream, but the fork is still behind the checkout
+		let synced = Upstream { push:true, ..Default::default() }.sync(&context).unwrap();
+
+		assert!(synced.commit.is_empty());
+
+		assert!(synced.pushed);
+
+		assert_eq!(head(Path::new(&origin)), synced.new);
+	}
+
+	#[test]
+	fn leases_on_the_fork_before_the_sync() {
+		let scratch = Scratch::new("sync-lease");
+
+		let (origin, _) = fork(&scratch);
+
+		let context = scratch.context("sync");
+
+		let upstream =
+			Upstream { strategy:Strategy::RebaseForkCommits, push:true, ..Default::default() };
+
+		let mut synced = upstream.sync(&context).unwrap();
+
+		assert!(synced.pushed);
+
+		// Someone pushed to the fork after the sync took its tip
+		let stale = synced.fork.clone();
+
+		git(&scratch.checkout(), &["commit", "-q", "--allow-empty", "-m", "Fork"]);
+
+		git(&scratch.checkout(), &["push", "-q", "origin", "main"]);
+
+		let pushed = head(Path::new(&origin));
+
+		git(&scratch.checkout(), &["reset", "-q", "--hard", "HEAD~1"]);
+
+		synced.fork = stale;
+
+		assert!(upstream.publish(&context, &synced).is_err());
+
+		assert_eq!(head(Path::new(&origin)), pushed);
+	}
+
+	#[test]
+	fn resets_to_the_parent_and_keeps_the_overlay() {
+		let scratch = Scratch::new("sync-overlay");
+
+		fork(&scratch);
+
+		let checkout = scratch.checkout();
+
+		commit(&checkout, "package.json", "{ \"name\": \"fork\" }\n");
+
+		commit(&checkout, "README.md", "# fork\n");
+
+		parent(&scratch, "package.json", "{ \"name\": \"parent\" }\n");
+
+		parent(&scratch, "README.md", "# parent\n");
+
+		let upstream = Upstream {
+			strategy:Strategy::ResetToParentPlusOverlay,
+			overlay:vec!["package.json".into(), "Missing.json".into()],
+			..Default::default()
+		};
+
+		let synced = upstream.sync(&scratch.context("sync")).unwrap();
+
+		assert_eq!(synced.commit.len(), 3);
+
+		// Everything of the parent, then one commit with what the fork keeps
+		assert_eq!(git(&checkout, &["rev-parse", "HEAD~1"]), synced.parent);
+
+		assert_eq!(
+			git(&checkout, &["log", "--format=%s", &format!("{}..HEAD", synced.parent)]),
+			"Keep package.json of the fork over upstream/main"
+		);
+
+		assert_eq!(
+			fs::read_to_string(checkout.join("package.json")).unwrap(),
+			"{ \"name\": \"fork\" }\n"
+		);
+
+		assert_eq!(fs::read_to_string(checkout.join("README.md")).unwrap(), "# parent\n");
+
+		assert!(!checkout.join("Missing.json").exists());
+
+		assert_eq!(git(&checkout, &["status", "--porcelain"]), "");
+	}
+
+	#[test]
+	fn fast_forwards_only_a_fork_without_commits_of_its_own() {
+		let scratch = Scratch::new("sync-fast-forward");
+
+		fork(&scratch);
+
+		let checkout = scratch.checkout();
+
+		let context = scratch.context("sync");
+
+		let upstream = Upstream { strategy:Strategy::FastForwardOnly, ..Default::default() };
+
+		let synced = upstream.sync(&context).unwrap();
+
+		assert_eq!(synced.new, synced.parent);
+
+		commit(&checkout, "Fork.md", "# fork\n");
+
+		parent(&scratch, "Parent.md", "# parent\n");
+
+		let old = head(&checkout);
+
+		let error = upstream.sync(&context).unwrap_err();
+
+		assert!(format!("{:#}", error).contains("cannot fast-forward"), "{:#}", error);
+
+		assert_eq!(head(&checkout), old);
+	}
+
+	#[test]
+	fn aborts_a_conflicting_merge_or_rebase() {
+		for strategy in [Strategy::Merge, Strategy::RebaseForkCommits] {
+			let scratch = Scratch::new(&format!("sync-conflict-{}", strategy));
+
+			fork(&scratch);
+
+			let checkout = scratch.checkout();
+
+			commit(&checkout, "README.md", "# fork\n");
+
+			parent(&scratch, "README.md", "# parent\n");
+
+			let old = head(&checkout);
+
+			let error = Upstream { strategy, ..Default::default() }
+				.sync(&scratch.context("sync"))
+				.unwrap_err();
+
+			assert!(format!("{:#}", error).contains("left as it was"), "{:#}", error);
+
+			// On the branch as it was, with nothing half done
+			assert_eq!(git(&checkout, &["symbolic-ref", "--short", "HEAD"]), "main");
+
+			assert_eq!(head(&checkout), old);
+
+			assert_eq!(git(&checkout, &["status", "--porcelain"]), "");
+
+			assert_eq!(fs::read_to_string(checkout.join("README.md")).unwrap(), "# fork\n");
+
+			let directory = checkout.join(".git");
+
+			for state in ["MERGE_HEAD", "rebase-merge", "rebase-apply"] {
+				assert!(!directory.join(state).exists(), "{} left behind", state);
+			}
+		}
+	}
+}
@@ -1 +1,2 @@
 pub mod Repository;
+pub mod Upstream;
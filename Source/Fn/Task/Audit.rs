@@ -0,0 +1,258 @@
+use std::{
+	collections::BTreeMap,
+	fs::{self, File},
+	io::Write,
+	path::{Path, PathBuf},
+	process::Command,
+	sync::Mutex,
+};
+
+use anyhow::{Context, Result};
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+// Audit log inside the workspace state directory. Runs only ever append to it.
+pub const FILE:&str = "Audit.jsonl";
+
+// One line of the audit log.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Record {
+	pub timestamp:String,
+
+	pub run:String,
+
+	pub repository:String,
+
+	pub task:String,
+
+	#[serde(flatten)]
+	pub event:Event,
+}
+
+// Something the tool did to a repository, its checkout or its forge.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Event {
+	Command {
+		command:String,
+
+		status:Option<i32>,
+
+		// Seconds.
+		duration:f64,
+	},
+
+	// Remote refs the push moved, by name, as they were before and after it. A ref missing on one
+	// side did not exist then, `None` means the remote could not be listed.
+	Push {
+		command:String,
+
+		remote:String,
+
+		before:Option<BTreeMap<String, String>>,
+
+		after:Option<BTreeMap<String, String>>,
+	},
+
+	Forge {
+		operation:String,
+
+		success:bool,
+	},
+
+	// Git blob ids of the content, `None` for a file that did not exist before.
+	Write {
+		path:PathBuf,
+
+		before:Option<String>,
+
+		after:String,
+	},
+
+	// `hash` of the file moved, `None` for a directory.
+	Rename {
+		from:PathBuf,
+
+		to:PathBuf,
+
+		hash:Option<String>,
+	},
+
+	// `hash` of the file removed, `None` for a directory.
+	Remove {
+		path:PathBuf,
+
+		hash:Option<String>,
+	},
+}
+
+// The audit log of one run; a dry run changes nothing and writes nothing to it.
+#[derive(Debug, Default)]
+pub struct Audit {
+	run:String,
+
+	file:Option<Mutex<File>>,
+}
+
+impl Audit {
+	pub fn open(path:&Path, run:&str, dry_run:bool) -> Result<Self> {
+		if dry_run {
+			return Ok(Self { run:run.to_string(), file:None });
+		}
+
+		if let Some(parent) = path.parent() {
+			fs::create_dir_all(parent)
+				.with_context(|| format!("Failed to create {}", parent.display()))?;
+		}
+
+		let file = fs::OpenOptions::new()
+			.create(true)
+			.append(true)
+			.open(path)
+			.with_context(|| format!("Failed to open {}", path.display()))?;
+
+		Ok(Self { run:run.to_string(), file:Some(Mutex::new(file)) })
+	}
+
+	pub fn record(&self, repository:&str, task:&str, event:Event) -> Result<()> {
+		let Some(file) = &self.file else {
+			return Ok(());
+		};
+
+		let record = Record {
+			timestamp:Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+			run:self.run.clone(),
+			repository:repository.to_string(),
+			task:task.to_string(),
+			event,
+		};
+
+		let line = serde_json::to_string(&record)? + "\n";
+
+		file.lock()
+			.expect("Audit log poisoned")
+			.write_all(line.as_bytes())
+			.context("Failed to write the audit log")
+	}
+
+	// Replaces `path` through a temporary file, so it is never left half written. Files of the
+	// workspace rather than of a checkout, such as the cache, go under an empty `repository`.
+	pub fn write(&self, repository:&str, task:&str, path:&Path, content:&[u8]) -> Result<()> {
+		let before = hash_file(path)?;
+
+		let after = hash(content)?;
+
+		let temporary = path.with_extension("maintain.tmp");
+
+		fs::write(&temporary, content)
+			.with_context(|| format!("Failed to write {}", temporary.display()))?;
+
+		fs::rename(&temporary, path)
+			.with_context(|| format!("Failed to replace {}", path.display()))?;
+
+		self.record(repository, task, Event::Write { path:path.to_path_buf(), before, after })
+	}
+
+	pub fn rename(&self, repository:&str, task:&str, from:&Path, to:&Path) -> Result<()> {
+		// A directory moves as a whole, only a file has content to hash
+		let hash = if from.is_file() { hash_file(from)? } else { None };
+
+		fs::rename(from, to)
+			.with_context(|| format!("Failed to move {} to {}", from.display(), to.display()))?;
+
+		self.record(
+			repository,
+			task,
+			Event::Rename { from:from.to_path_buf(), to:to.to_path_buf(), hash },
+		)
+	}
+
+	// Removes the file or the whole directory at `path`. Nothing there is nothing to remove, so it
+	// is not recorded either.
+	pub fn remove(&self, repository:&str, task:&str, path:&Path) -> Result<()> {
+		let hash = if path.is_file() { hash_file(path)? } else { None };
+
+		let removed = if path.is_dir() { fs::remove_dir_all(path) } else { fs::remove_file(path) };
+
+		match removed {
+			Ok(()) => {},
+			Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+			Err(error) => {
+				return Err(error).with_context(|| format!("Failed to remove {}", path.display()));
+			},
+		}
+
+		self.record(repository, task, Event::Remove { path:path.to_path_buf(), hash })
+	}
+}
+
+// Every record of the run `run` in the audit log at `path`, in the order they were written.
+pub fn load(path:&Path, run:&str) -> Result<Vec<Record>> {
+	if !path.exists() {
+		return Ok(Vec::new());
+	}
+
+	let content =
+		fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+
+	let mut record = Vec::new();
+
+	for line in content.lines().filter(|line| !line.trim().is_empty()) {
+		let current = serde_json::from_str::<Record>(line)
+			.with_context(|| format!("Invalid audit record in {}", path.display()))?;
+
+		if current.run == run {
+			record.push(current);
+		}
+	}
+
+	Ok(record)
+}
+
+// Git blob id of `content`, the same `git hash-object` prints.
+pub fn hash(content:&[u8]) -> Result<String> {
+	Ok(git2::Oid::hash_object(git2::ObjectType::Blob, content)?.to_string())
+}
+
+// Hash of the file at `path`, `None` when there is none.
+pub fn hash_file(path:&Path) -> Result<Option<String>> {
+	match fs::read(path) {
+		Ok(content) => Ok(Some(hash(&content)?)),
+		Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+		Err(error) => Err(error).with_context(|| format!("Failed to read {}", path.display())),
+	}
+}
+
+// The remote `git push` talks to, the first argument after `push` that is not an option.
+pub fn remote(command:&Command) -> String {
+	command
+		.get_args()
+		.map(|argument| argument.to_string_lossy())
+		.skip_while(|argument| argument != "push")
+		.skip(1)
+		.find(|argument| !argument.starts_with('-'))
+		.map_or_else(|| "origin".to_string(), |remote| remote.into_owned())
+}
+
+type Refs = Option<BTreeMap<String, String>>;
+
+// Narrows the refs of a remote before and after a push to the ones it moved.
+pub fn moved(before:Refs, after:Refs) -> (Refs, Refs) {
+	let (Some(mut before), Some(mut after)) = (before.clone(), after.clone()) else {
+		return (before, after);
+	};
+
+	let same:Vec<String> = before
+		.iter()
+		.filter(|(name, id)| after.get(*name) == Some(id))
+		.map(|(name, _)| name.clone())
+		.collect();
+
+	for name in same {
+		before.remove(&name);
+
+		after.remove(&name);
+	}
+
+	(Some(before), Some(after))
+}
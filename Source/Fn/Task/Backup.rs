@@ -0,0 +1,296 @@
+use std::{
+	collections::BTreeMap,
+	fs::{self, File},
+	io::Write,
+	path::{Path, PathBuf},
+	process::Command,
+	sync::Mutex,
+};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::Fn::Task::RepoContext;
+
+// Records of every run, one file per run inside the workspace state directory.
+pub const DIRECTORY:&str = "Backup";
+
+// Refs of a run live under `<REFS>/<run>`, which keeps their commits from being collected.
+pub const REFS:&str = "refs/maintain/backup";
+
+// How one repository looked before the first task of a run changed it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Record {
+	pub repository:String,
+
+	// `None` for a detached HEAD.
+	pub branch:Option<String>,
+
+	pub head:String,
+
+	// Commit `git stash create` made of uncommitted changes to tracked files, if there were any.
+	pub worktree:Option<String>,
+
+	// Branches of every remote of the checkout, by remote and branch name, and the commit each
+	// pointed to. `None` for a remote whose branches could not be fetched.
+	pub remote:BTreeMap<String, Option<BTreeMap<String, String>>>,
+}
+
+// The backups of one run; a dry run changes nothing and backs nothing up.
+#[derive(Debug, Default)]
+pub struct Backup {
+	run:String,
+
+	file:Option<Mutex<File>>,
+}
+
+impl Backup {
+	pub fn open(state:&Path, run:&str, dry_run:bool) -> Result<Self> {
+		if dry_run {
+			return Ok(Self { run:run.to_string(), file:None });
+		}
+
+		let path = file(state, run);
+
+		if let Some(parent) = path.parent() {
+			fs::create_dir_all(parent)
+				.with_context(|| format!("Failed to create {}", parent.display()))?;
+		}
+
+		let file = fs::OpenOptions::new()
+			.create(true)
+			.append(true)
+			.open(&path)
+			.with_context(|| format!("Failed to open {}", path.display()))?;
+
+		Ok(Self { run:run.to_string(), file:Some(Mutex::new(file)) })
+	}
+
+	// Backs up the checkout of `context` and the branches of its remotes, once per run: later
+	// tasks of the same run find the backup in place and leave it as it is.
+	pub fn save(&self, context:&RepoContext) -> Result<()> {
+		let Some(file) = &self.file else {
+			return Ok(());
+		};
+
+		let prefix = format!("{}/{}", REFS, self.run);
+
+		let head = format!("{}/head", prefix);
+
+		if context
+			.query(Command::new("git").args(["rev-parse", "--verify", "--quiet", &head]))?
+			.success()
+		{
+			return Ok(());
+		}
+
+		let commit = context.query(Command::new("git").args(["rev-parse", "--verify", "HEAD"]))?;
+
+		// Nothing is committed yet, so there is nothing to lose either
+		if !commit.success() {
+			return Ok(());
+		}
+
+		let branch =
+			context.query(Command::new("git").args(["symbolic-ref", "--short", "-q", "HEAD"]))?;
+
+		// The commit only backs up, so whoever made it does not matter
+		let worktree = context.query(
+			Command::new("git")
+				.args(["stash", "create"])
+				.env("GIT_AUTHOR_NAME", "Maintain")
+				.env("GIT_AUTHOR_EMAIL", "maintain@localhost")
+				.env("GIT_COMMITTER_NAME", "Maintain")
+				.env("GIT_COMMITTER_EMAIL", "maintain@localhost"),
+		)?;
+
+		if !worktree.success() {
+			bail!("Failed to save uncommitted changes: {}", worktree.stderr.trim());
+		}
+
+		let remote = self.remote(context, &prefix)?;
+
+		let record = Record {
+			repository:context.repository.full_name(),
+			branch:Some(branch.stdout.trim().to_string()).filter(|_| branch.success()),
+			head:commit.stdout.trim().to_string(),
+			worktree:Some(worktree.stdout.trim().to_string()).filter(|stash| !stash.is_empty()),
+			remote,
+		};
+
+		if let Some(worktree) = &record.worktree {
+			context.execute(Command::new("git").args([
+				"update-ref",
+				&format!("{}/worktree", prefix),
+				worktree,
+			]))?;
+		}
+
+		// Last, as its presence means the backup is complete
+		context.execute(Command::new("git").args(["update-ref", &head, &record.head]))?;
+
+		let line = serde_json::to_string(&record)? + "\n";
+
+		file.lock()
+			.expect("Backup log poisoned")
+			.write_all(line.as_bytes())
+			.context("Failed to write the backup record")
+	}
+
+	// Fetches every branch of every remote under `<prefix>/remote/<remote>/`, so their commits
+	// are at hand to push back even when the checkout never had them. Which remote a task pushes
+	// to is up to it, e.g. `sync --fork`, so none is left out. Most tasks never touch a remote, so
+	// one out of reach is left without a backup rather than failing them.
+	fn remote(
+		&self,
+		context:&RepoContext,
+		prefix:&str,
+	) -> Result<BTreeMap<String, Option<BTreeMap<String, String>>>> {
+		let remotes = context.query(Command::new("git").arg("remote"))?;
+
+		let mut backup = BTreeMap::new();
+
+		for remote in remotes.stdout.lines().map(str::trim).filter(|remote| !remote.is_empty()) {
+			let branch = match self.fetch(context, prefix, remote) {
+				Ok(branch) => Some(branch),
+				Err(error) => {
+					context.log(format!("Branches of {} are not backed up: {:#}", remote, error));
+
+					None
+				},
+			};
+
+			backup.insert(remote.to_string(), branch);
+		}
+
+		Ok(backup)
+	}
+
+	fn fetch(
+		&self,
+		context:&RepoContext,
+		prefix:&str,
+		remote:&str,
+	) -> Result<BTreeMap<String, String>> {
+		let namespace = format!("{}/remote/{}/", prefix, remote);
+
+		context.execute(Command::new("git").args([
+			"fetch",
+			"--no-tags",
+			remote,
+			&format!("+refs/heads/*:{}*", namespace),
+		]))?;
+
+		let refs = context.query(Command::new("git").args([
+			"for-each-ref",
+			"--format=%(objectname) %(refname)",
+			&namespace,
+		]))?;
+
+		Ok(refs
+			.stdout
+			.lines()
+			.filter_map(|line| line.split_once(' '))
+			.filter_map(|(id, name)| {
+				Some((name.strip_prefix(&namespace)?.to_string(), id.to_string()))
+			})
+			.collect())
+	}
+}
+
+pub fn file(state:&Path, run:&str) -> PathBuf {
+	state.join(DIRECTORY).join(format!("{}.jsonl", run))
+}
+
+// Every repository the run `run` backed up, by `owner/name`.
+pub fn load(state:&Path, run:&str) -> Result<BTreeMap<String, Record>> {
+	let path = file(state, run);
+
+	if !path.exists() {
+		bail!("Run {} backed nothing up, {} does not exist", run, path.display());
+	}
+
+	let content =
+		fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+
+	content
+		.lines()
+		.filter(|line| !line.trim().is_empty())
+		.map(|line| {
+			let record = serde_json::from_str::<Record>(line)
+				.with_context(|| format!("Invalid backup record in {}", path.display()))?;
+
+			Ok((record.repository.clone(), record))
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod test {
+	use super::{load, Backup};
+	use crate::Fn::Task::Test::{git, Scratch};
+
+	#[test]
+	fn backs_up_the_branches_of_every_remote() {
+		let scratch = Scratch::new("backup-remote");
+
+		let checkout = scratch.checkout().to_string_lossy().into_owned();
+
+		for remote in ["origin", "fork"] {
+			let bare = format!("{}.git", remote);
+
+			git(&scratch.workspace.root, &["clone", "-q", "--bare", &checkout, &bare]);
+
+			let url = scratch.workspace.root.join(&bare).to_string_lossy().into_owned();
+
+			git(&scratch.checkout(), &["remote", "add", remote, &url]);
+		}
+
+		git(&scratch.checkout(), &["commit", "-q", "--allow-empty", "-m", "Fork"]);
+
+		git(&scratch.checkout(), &["push", "-q", "fork", "HEAD:next"]);
+
+		let backup = Backup::open(&scratch.workspace.state, &scratch.workspace.run, false).unwrap();
+
+		backup.save(&scratch.context("move src")).unwrap();
+
+		let record = load(&scratch.workspace.state, &scratch.workspace.run).unwrap();
+
+		let remote = &record["owner/name"].remote;
+
+		let first = git(&scratch.checkout(), &["rev-parse", "HEAD~1"]);
+
+		let head = git(&scratch.checkout(), &["rev-parse", "HEAD"]);
+
+		assert_eq!(remote.keys().collect::<Vec<_>>(), ["fork", "origin"]);
+
+		let origin = remote["origin"].clone().expect("origin is not backed up");
+
+		assert_eq!(origin.into_iter().collect::<Vec<_>>(), [("main".to_string(), first.clone())]);
+
+		let fork = remote["fork"].clone().expect("fork is not backed up");
+
+		assert_eq!(fork.get("main"), Some(&first));
+
+		assert_eq!(fork.get("next"), Some(&head));
+	}
+
+	#[test]
+	fn backs_up_the_checkout_without_the_remote() {
+		let scratch = Scratch::new("backup-offline");
+
+		let missing = scratch.workspace.root.join("missing.git");
+
+		git(&scratch.checkout(), &["remote", "add", "origin", &missing.to_string_lossy()]);
+
+		let backup = Backup::open(&scratch.workspace.state, &scratch.workspace.run, false).unwrap();
+
+		backup.save(&scratch.context("move src")).unwrap();
+
+		let record = load(&scratch.workspace.state, &scratch.workspace.run).unwrap();
+
+		assert_eq!(record["owner/name"].head, git(&scratch.checkout(), &["rev-parse", "HEAD"]));
+
+		assert_eq!(record["owner/name"].remote.get("origin"), Some(&None));
+	}
+}
@@ -0,0 +1,104 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::{Context, Result};
+
+static CANCELLED:AtomicBool = AtomicBool::new(false);
+
+// Takes over Ctrl-C: the first one lets the repositories in flight finish their task and cancels
+// the rest, the second one aborts on the spot.
+pub fn install() -> Result<()> {
+	ctrlc::set_handler(|| {
+		if CANCELLED.swap(true, Ordering::SeqCst) {
+			eprintln!("Aborted.");
+
+			std::process::exit(130);
+		}
+
+		eprintln!(
+			"Cancelling, repositories in flight finish their task first. Press Ctrl-C again to \
+			 abort."
+		);
+	})
+	.context("Failed to handle Ctrl-C")
+}
+
+pub fn is_cancelled() -> bool {
+	CANCELLED.load(Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod test {
+	use std::{env, process::Command, thread, time::Duration};
+
+	use super::{install, is_cancelled};
+	use crate::Fn::Task::{Journal::Journal, Registry::Registry, Run, TaskOutcome, Test::Scratch};
+
+	// Set in the copy of the test binary that takes the interrupts, so no other test ever sees a
+	// cancelled run.
+	const CHILD:&str = "MAINTAIN_TEST_CANCEL";
+
+	const NAME:&str =
+		"Fn::Task::Cancel::test::cancels_on_the_first_interrupt_and_aborts_on_the_second";
+
+	fn interrupt() {
+		let status = Command::new("kill")
+			.args(["-INT", &std::process::id().to_string()])
+			.status()
+			.expect("Cannot run kill!");
+
+		assert!(status.success());
+	}
+
+	#[test]
+	fn cancels_on_the_first_interrupt_and_aborts_on_the_second() {
+		if env::var_os(CHILD).is_none() {
+			let output = Command::new(env::current_exe().unwrap())
+				.args(["--exact", NAME, "--nocapture"])
+				.env(CHILD, "1")
+				.output()
+				.unwrap();
+
+			let stderr = String::from_utf8_lossy(&output.stderr);
+
+			assert_eq!(output.status.code(), Some(130), "{}", stderr);
+
+			assert!(stderr.contains("Cancelling") && stderr.contains("Aborted."), "{}", stderr);
+
+			return;
+		}
+
+		install().unwrap();
+
+		assert!(!is_cancelled());
+
+		interrupt();
+
+		for _ in 0..200 {
+			if is_cancelled() {
+				break;
+			}
+
+			thread::sleep(Duration::from_millis(10));
+		}
+
+		assert!(is_cancelled());
+
+		// Nothing starts once cancelled, preparation included
+		let scratch = Scratch::new("cancel");
+
+		let task = Registry::create("append detail", &toml::Table::new()).unwrap();
+
+		let entry =
+			Run::Fn(&scratch.workspace, task.name(), task.as_ref(), &Journal::default()).unwrap();
+
+		assert_eq!(entry[0].outcome, TaskOutcome::Cancelled);
+
+		drop(scratch);
+
+		interrupt();
+
+		thread::sleep(Duration::from_secs(10));
+
+		panic!("The second interrupt did not abort");
+	}
+}
@@ -0,0 +1,174 @@
+use std::{
+	collections::HashMap,
+	fs::{self, File},
+	io::Write,
+	path::Path,
+	sync::Mutex,
+};
+
+use anyhow::{Context, Result};
+
+use crate::Fn::Task::{Summary::Entry, TaskOutcome};
+
+// Journal file inside the workspace state directory.
+pub const FILE:&str = "Journal.jsonl";
+
+// Every repository a run finished, one JSON line per step and repository, written as soon as it
+// finishes so a crashed run can pick up where it stopped.
+#[derive(Debug, Default)]
+pub struct Journal {
+	// Outcomes of the run being resumed, by step and repository.
+	earlier:HashMap<(String, String), TaskOutcome>,
+
+	// `None` in a dry run, which leaves the journal of the real run alone.
+	file:Option<Mutex<File>>,
+}
+
+impl Journal {
+	// Starts a new journal at `path`, dropping the one an earlier run left.
+	pub fn create(path:&Path, dry_run:bool) -> Result<Self> {
+		if dry_run {
+			return Ok(Self::default());
+		}
+
+		Ok(Self { earlier:HashMap::new(), file:Some(Mutex::new(open(path, true)?)) })
+	}
+
+	// Continues the journal at `path`, remembering what the earlier run finished.
+	pub fn resume(path:&Path, dry_run:bool) -> Result<Self> {
+		let mut earlier = HashMap::new();
+
+		let mut content = String::new();
+
+		if path.exists() {
+			content = fs::read_to_string(path)
+				.with_context(|| format!("Failed to read {}", path.display()))?;
+
+			for line in content.lines().filter(|line| !line.trim().is_empty()) {
+				// A crash can cut the last line short, that repository simply runs again
+				let Ok(entry) = serde_json::from_str::<Entry>(line) else {
+					continue;
+				};
+
+				earlier.insert((entry.task, entry.repository), entry.outcome);
+			}
+		}
+
+		if dry_run {
+			return Ok(Self { earlier, file:None });
+		}
+
+		let mut file = open(path, false)?;
+
+		// The next entry starts a line of its own rather than completing the one cut short
+		if !content.is_empty() && !content.ends_with('\n') {
+			file.write_all(b"\n").context("Failed to write the run journal")?;
+		}
+
+		Ok(Self { earlier, file:Some(Mutex::new(file)) })
+	}
+
+	// How the earlier run finished the step `name` for `repository`. Failed and cancelled work
+	// counts as unfinished.
+	pub fn finished(&self, name:&str, repository:&str) -> Option<&TaskOutcome> {
+		self.earlier
+			.get(&(name.to_string(), repository.to_string()))
+			.filter(|outcome| !matches!(outcome, TaskOutcome::Failed(_) | TaskOutcome::Cancelled))
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.earlier.is_empty()
+	}
+
+	pub fn record(&self, entry:&Entry) -> Result<()> {
+		let Some(file) = &self.file else {
+			return Ok(());
+		};
+
+		let line = serde_json::to_string(entry)? + "\n";
+
+		file.lock()
+			.expect("Journal poisoned")
+			.write_all(line.as_bytes())
+			.context("Failed to write the run journal")
+	}
+}
+
+fn open(path:&Path, truncate:bool) -> Result<File> {
+	if let Some(parent) = path.parent() {
+		fs::create_dir_all(parent)
+			.with_context(|| format!("Failed to create {}", parent.display()))?;
+	}
+
+	fs::OpenOptions::new()
+		.create(true)
+		.append(!truncate)
+		.write(true)
+		.truncate(truncate)
+		.open(path)
+		.with_context(|| format!("Failed to open {}", path.display()))
+}
+
+#[cfg(test)]
+mod test {
+	use std::fs;
+
+	use super::Journal;
+	use crate::Fn::Task::{
+		Summary::Entry,
+		TaskOutcome::{self, *},
+		Test::directory,
+	};
+
+	fn line(task:&str, repository:&str, outcome:TaskOutcome) -> String {
+		serde_json::to_string(&Entry::new(
+			task,
+			repository.to_string(),
+			outcome,
+			Default::default(),
+		))
+		.unwrap() + "\n"
+	}
+
+	#[test]
+	fn resumes_failed_cancelled_and_cut_short_work() {
+		let path = directory("journal").join("Journal.jsonl");
+
+		let content = [
+			line("sync", "owner/changed", Changed),
+			line("sync", "owner/failed", Failed(vec!["rejected".to_string()])),
+			line("sync", "owner/cancelled", Cancelled),
+			line("sync", "owner/skipped", Skipped("not applicable".to_string())),
+			line("sync", "owner/cut", Changed)[..20].to_string(),
+		]
+		.concat();
+
+		fs::write(&path, content).unwrap();
+
+		let journal = Journal::resume(&path, false).unwrap();
+
+		assert_eq!(journal.finished("sync", "owner/changed"), Some(&Changed));
+
+		assert_eq!(journal.finished("sync", "owner/failed"), None);
+
+		assert_eq!(journal.finished("sync", "owner/cancelled"), None);
+
+		assert!(journal.finished("sync", "owner/skipped").is_some());
+
+		assert_eq!(journal.finished("sync", "owner/cut"), None);
+
+		// Another step running the same task has not finished anything yet
+		assert_eq!(journal.finished("sync fork", "owner/changed"), None);
+
+		// What the resumed run finishes goes after the line cut short
+		journal
+			.record(&Entry::new("sync", "owner/cut".to_string(), Changed, Default::default()))
+			.unwrap();
+
+		let journal = Journal::resume(&path, true).unwrap();
+
+		assert_eq!(journal.finished("sync", "owner/cut"), Some(&Changed));
+
+		let _ = fs::remove_dir_all(path.parent().unwrap());
+	}
+}
@@ -0,0 +1,230 @@
+use std::{
+	process::Command,
+	sync::{Condvar, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+
+// How many repositories may use one resource at once, whatever `--jobs` allows, under `[limit]`
+// in the manifest.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct Limit {
+	pub push:usize,
+
+	// Clones, fetches and pulls.
+	pub fetch:usize,
+
+	// Forge calls that change something.
+	pub forge:usize,
+}
+
+impl Default for Limit {
+	fn default() -> Self {
+		Self { push:4, fetch:8, forge:2 }
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resource {
+	Push,
+
+	Fetch,
+
+	Forge,
+}
+
+impl Resource {
+	// What a command needs, judged by its git subcommand.
+	pub fn of(command:&Command) -> Option<Self> {
+		if command.get_program() != "git" {
+			return None;
+		}
+
+		let mut argument = command.get_args().map(|argument| argument.to_string_lossy());
+
+		// Options before the subcommand, `-C <path>` and `-c <name>=<value>` take a value
+		while let Some(option) = argument.next() {
+			match option.as_ref() {
+				"-C" | "-c" => {
+					argument.next();
+				},
+				option if option.starts_with('-') => {},
+				"push" => return Some(Self::Push),
+				"clone" | "fetch" | "pull" => return Some(Self::Fetch),
+				_ => return None,
+			}
+		}
+
+		None
+	}
+}
+
+// The limits of one run, shared by every repository in it.
+pub struct Gate {
+	push:Semaphore,
+
+	fetch:Semaphore,
+
+	forge:Semaphore,
+}
+
+impl Gate {
+	pub fn new(limit:&Limit) -> Self {
+		Self {
+			push:Semaphore::new(limit.push),
+			fetch:Semaphore::new(limit.fetch),
+			forge:Semaphore::new(limit.forge),
+		}
+	}
+
+	// Calls `call` once `resource` has room for it.
+	pub fn hold<T>(&self, resource:Option<Resource>, call:impl FnOnce() -> T) -> T {
+		let semaphore = match resource {
+			Some(Resource::Push) => &self.push,
+			Some(Resource::Fetch) => &self.fetch,
+			Some(Resource::Forge) => &self.forge,
+			None => return call(),
+		};
+
+		semaphore.acquire();
+
+		// Released on the way out even when `call` panics
+		let _permit = Permit(semaphore);
+
+		call()
+	}
+}
+
+impl Default for Gate {
+	fn default() -> Self {
+		Self::new(&Limit::default())
+	}
+}
+
+struct Semaphore {
+	free:Mutex<usize>,
+
+	released:Condvar,
+}
+
+impl Semaphore {
+	// A limit of 0 is taken as 1, so a resource can never be locked out entirely.
+	fn new(limit:usize) -> Self {
+		Self { free:Mutex::new(limit.max(1)), released:Condvar::new() }
+	}
+
+	fn acquire(&self) {
+		let mut free = self.free.lock().expect("Semaphore poisoned");
+
+		while *free == 0 {
+			free = self.released.wait(free).expect("Semaphore poisoned");
+		}
+
+		*free -= 1;
+	}
+
+	fn release(&self) {
+		*self.free.lock().expect("Semaphore poisoned") += 1;
+
+		self.released.notify_one();
+	}
+}
+
+struct Permit<'a>(&'a Semaphore);
+
+impl Drop for Permit<'_> {
+	fn drop(&mut self) {
+		self.0.release();
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::{
+		panic::{self, AssertUnwindSafe},
+		process::Command,
+		sync::atomic::{AtomicUsize, Ordering},
+		thread,
+		time::Duration,
+	};
+
+	use super::{Gate, Limit, Resource};
+
+	// The most calls holding `resource` at once, out of eight trying together.
+	fn most(gate:&Gate, resource:Option<Resource>) -> usize {
+		let (current, most) = (AtomicUsize::new(0), AtomicUsize::new(0));
+
+		thread::scope(|scope| {
+			for _ in 0..8 {
+				scope.spawn(|| {
+					gate.hold(resource, || {
+						let now = current.fetch_add(1, Ordering::SeqCst) + 1;
+
+						most.fetch_max(now, Ordering::SeqCst);
+
+						thread::sleep(Duration::from_millis(20));
+
+						current.fetch_sub(1, Ordering::SeqCst);
+					})
+				});
+			}
+		});
+
+		most.into_inner()
+	}
+
+	#[test]
+	fn lets_as_many_through_as_the_limit() {
+		let gate = Gate::new(&Limit { push:1, fetch:3, forge:0 });
+
+		assert_eq!(most(&gate, Some(Resource::Push)), 1);
+
+		assert!(most(&gate, Some(Resource::Fetch)) <= 3);
+
+		// No limit at all is taken as one
+		assert_eq!(most(&gate, Some(Resource::Forge)), 1);
+
+		assert!(most(&gate, None) > 1);
+	}
+
+	#[test]
+	fn frees_the_resource_when_the_call_panics() {
+		let gate = Gate::new(&Limit { push:1, ..Default::default() });
+
+		let result = panic::catch_unwind(AssertUnwindSafe(|| {
+			gate.hold(Some(Resource::Push), || panic!("push failed"))
+		}));
+
+		assert!(result.is_err());
+
+		assert_eq!(gate.hold(Some(Resource::Push), || 1), 1);
+	}
+
+	#[test]
+	fn judges_a_command_by_its_subcommand() {
+		let of =
+			|program:&str, argument:&[&str]| Resource::of(Command::new(program).args(argument));
+
+		assert_eq!(of("git", &["push", "origin", "main"]), Some(Resource::Push));
+
+		// The values of `-C` and `-c` are no subcommands, whatever they are called
+		assert_eq!(of("git", &["-C", "push", "fetch", "origin"]), Some(Resource::Fetch));
+
+		assert_eq!(of("git", &["-c", "push.default=current", "push"]), Some(Resource::Push));
+
+		assert_eq!(
+			of("git", &["--no-pager", "-C", "/tmp", "-c", "a=b", "clone", "url"]),
+			Some(Resource::Fetch)
+		);
+
+		assert_eq!(of("git", &["pull", "--ff-only"]), Some(Resource::Fetch));
+
+		// Only the subcommand counts, not an argument that happens to look like one
+		assert_eq!(of("git", &["log", "push"]), None);
+
+		assert_eq!(of("git", &[]), None);
+
+		assert_eq!(of("pnpm", &["push"]), None);
+	}
+}
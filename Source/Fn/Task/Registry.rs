@@ -0,0 +1,94 @@
+use anyhow::{bail, Context, Result};
+use serde::de::DeserializeOwned;
+
+use crate::Fn::{
+	Append::Detail::AppendDetail,
+	Clean::Detail::CleanDetail,
+	Clone::Repository::CloneRepository,
+	Configure::Repository::ConfigureRepository,
+	Fork::Organization::ForkOrganization,
+	Module::Git::ModuleGit,
+	Move::{license::MoveLicense, package::MovePackage, src::MoveSrc},
+	Rename::{Branch::RenameBranch, Repository::RenameRepository},
+	Replace::{Copyright::ReplaceCopyright, Import::ReplaceImport},
+	Setting::Repository::SettingRepository,
+	Sort::Detail::SortDetail,
+	Sync::Repository::SyncRepository,
+	Task::Task,
+};
+
+// Every task the tool ships, with its default arguments, in the order a full run applies them.
+pub struct Registry {
+	task:Vec<Box<dyn Task>>,
+}
+
+impl Default for Registry {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Registry {
+	pub fn new() -> Self {
+		Self {
+			task:vec![
+				Box::new(CloneRepository::default()),
+				Box::new(ModuleGit::default()),
+				Box::new(ConfigureRepository::default()),
+				Box::new(SettingRepository),
+				Box::new(RenameRepository),
+				Box::new(RenameBranch::default()),
+				Box::new(SyncRepository::default()),
+				Box::new(CleanDetail),
+				Box::new(MoveLicense),
+				Box::new(MovePackage),
+				Box::new(MoveSrc),
+				Box::new(AppendDetail),
+				Box::new(SortDetail),
+				Box::new(ForkOrganization::default()),
+				Box::new(ReplaceCopyright),
+				Box::new(ReplaceImport),
+			],
+		}
+	}
+
+	// The task `name` with `argument` replacing its defaults, e.g. `{ depth = 0 }` for `clone`.
+	pub fn create(name:&str, argument:&toml::Table) -> Result<Box<dyn Task>> {
+		let task:Box<dyn Task> = match name {
+			"clone" => Box::new(parse::<CloneRepository>(name, argument)?),
+			"module git" => Box::new(parse::<ModuleGit>(name, argument)?),
+			"configure" => Box::new(parse::<ConfigureRepository>(name, argument)?),
+			"rename branch" => Box::new(parse::<RenameBranch>(name, argument)?),
+			"sync" => Box::new(parse::<SyncRepository>(name, argument)?),
+			"fork" => Box::new(parse::<ForkOrganization>(name, argument)?),
+			_ => {
+				if !argument.is_empty() {
+					bail!("Task {} takes no arguments", name);
+				}
+
+				let Some(task) = Self::new().task.into_iter().find(|task| task.name() == name)
+				else {
+					bail!("Unknown task {}", name);
+				};
+
+				task
+			},
+		};
+
+		Ok(task)
+	}
+
+	pub fn get(&self, name:&str) -> Option<&dyn Task> {
+		self.iter().find(|task| task.name() == name)
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item=&dyn Task> {
+		self.task.iter().map(Box::as_ref)
+	}
+}
+
+fn parse<T:DeserializeOwned>(name:&str, argument:&toml::Table) -> Result<T> {
+	toml::Value::Table(argument.clone())
+		.try_into()
+		.with_context(|| format!("Invalid arguments for {}", name))
+}
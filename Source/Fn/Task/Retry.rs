@@ -0,0 +1,225 @@
+use std::{
+	collections::hash_map::RandomState,
+	hash::{BuildHasher, Hasher},
+	io, thread,
+	time::{Duration, SystemTime},
+};
+
+use anyhow::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::Fn::{Forge::Status, Task::Execution};
+
+// How often and how patiently an operation that reaches the network is tried again. Set per task
+// under `[retry."<task>"]` in the manifest; fields left out keep their defaults.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct Policy {
+	// Tries in total, so 1 never retries.
+	pub attempts:u32,
+
+	// Seconds before the first retry, doubled for every retry after it.
+	pub delay:f64,
+
+	// Longest wait between two tries, in seconds.
+	pub limit:f64,
+
+	// Waits a random part, between half and all, of every delay, so a fleet of repositories that
+	// failed together does not retry together.
+	pub jitter:bool,
+}
+
+impl Default for Policy {
+	fn default() -> Self {
+		Self { attempts:3, delay:2.0, limit:60.0, jitter:true }
+	}
+}
+
+impl Policy {
+	pub fn none() -> Self {
+		Self { attempts:1, ..Default::default() }
+	}
+
+	// Wait before try `attempt + 1`.
+	pub fn delay(&self, attempt:u32) -> Duration {
+		let delay = (self.delay * 2f64.powi(attempt.saturating_sub(1) as i32)).min(self.limit);
+
+		let delay = if self.jitter { delay * (0.5 + random() / 2.0) } else { delay };
+
+		Duration::from_secs_f64(delay.max(0.0))
+	}
+
+	// Calls `call` until it succeeds, fails for good or runs out of attempts. Retries are reported
+	// under `label`.
+	pub fn run<T>(&self, label:&str, mut call:impl FnMut() -> Result<T>) -> Result<T> {
+		let mut attempt = 1;
+
+		loop {
+			match call() {
+				Err(error) if attempt < self.attempts && retryable(&error) => {
+					// A forge that says how long to wait is believed, up to `limit`
+					let delay = self.delay(attempt).max(
+						retry_after(&error)
+							.unwrap_or_default()
+							.min(Duration::from_secs_f64(self.limit.max(0.0))),
+					);
+
+					eprintln!(
+						"{}: {:#}, retrying in {:.1}s ({} of {})",
+						label,
+						error,
+						delay.as_secs_f64(),
+						attempt + 1,
+						self.attempts
+					);
+
+					thread::sleep(delay);
+
+					attempt += 1;
+				},
+				Err(error) if attempt > 1 => {
+					return Err(error.context(format!("Gave up after {} attempts", attempt)));
+				},
+				result => return result,
+			}
+		}
+	}
+}
+
+// Whether `error` may go away by itself: network trouble in git, libgit2 or a connection of our
+// own, and forge answers that say the forge is overloaded or down.
+pub fn retryable(error:&Error) -> bool {
+	error.chain().any(|cause| {
+		if let Some(status) = cause.downcast_ref::<Status>() {
+			// GitHub answers 403 to a client over its rate limit
+			return status.code == 429
+				|| status.code >= 500
+				|| (status.code == 403 && status.retry_after.is_some());
+		}
+
+		if let Some(error) = cause.downcast_ref::<git2::Error>() {
+			return matches!(
+				error.class(),
+				git2::ErrorClass::Net | git2::ErrorClass::Http | git2::ErrorClass::Ssh
+			) || error.code() == git2::ErrorCode::Timeout;
+		}
+
+		// A command past its timeout, or a connection that broke off
+		if let Some(error) = cause.downcast_ref::<io::Error>() {
+			return matches!(
+				error.kind(),
+				io::ErrorKind::TimedOut
+					| io::ErrorKind::ConnectionReset
+					| io::ErrorKind::ConnectionAborted
+			);
+		}
+
+		if let Some(error) = cause.downcast_ref::<ureq::Error>() {
+			return matches!(error, ureq::Error::Transport(_));
+		}
+
+		if let Some(execution) = cause.downcast_ref::<Execution>() {
+			let stderr = execution.stderr.to_lowercase();
+
+			return TRANSIENT.iter().any(|pattern| stderr.contains(pattern));
+		}
+
+		false
+	})
+}
+
+// The wait the forge asked for, when one did.
+fn retry_after(error:&Error) -> Option<Duration> {
+	error.chain().find_map(|cause| cause.downcast_ref::<Status>()?.retry_after)
+}
+
+// What git prints when the network, not the repository, was the problem.
+const TRANSIENT:[&str; 12] = [
+	"could not resolve host",
+	"connection timed out",
+	"connection reset",
+	"connection refused",
+	"operation timed out",
+	"the remote end hung up unexpectedly",
+	"early eof",
+	"rpc failed",
+	"returned error: 5",
+	"returned error: 429",
+	"gnutls",
+	"ssl_read",
+];
+
+// Between 0 and 1, good enough to spread retries without pulling in a random number generator.
+fn random() -> f64 {
+	let mut hasher = RandomState::new().build_hasher();
+
+	hasher.write_u128(
+		SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_nanos(),
+	);
+
+	hasher.finish() as f64 / u64::MAX as f64
+}
+
+#[cfg(test)]
+mod test {
+	use std::{io, time::Duration};
+
+	use anyhow::{anyhow, Error};
+
+	use super::{retryable, Policy};
+	use crate::Fn::Forge::Status;
+
+	fn io(kind:io::ErrorKind) -> Error {
+		Error::new(io::Error::new(kind, "io")).context("Failed to run git fetch")
+	}
+
+	fn status(code:u16, retry_after:Option<Duration>) -> Error {
+		let status = Status {
+			code,
+			method:"GET".to_string(),
+			url:"/repos".to_string(),
+			body:String::new(),
+			retry_after,
+		};
+
+		Error::new(status).context("Failed to list repositories")
+	}
+
+	#[test]
+	fn tells_transient_errors_apart() {
+		assert!(retryable(&io(io::ErrorKind::TimedOut)));
+
+		assert!(retryable(&io(io::ErrorKind::ConnectionReset)));
+
+		assert!(!retryable(&io(io::ErrorKind::NotFound)));
+
+		assert!(retryable(&status(429, None)));
+
+		assert!(retryable(&status(502, None)));
+
+		assert!(retryable(&status(403, Some(Duration::from_secs(1)))));
+
+		assert!(!retryable(&status(403, None)));
+
+		assert!(!retryable(&status(404, None)));
+
+		assert!(!retryable(&anyhow!("Invalid manifest")));
+	}
+
+	#[test]
+	fn stops_at_the_first_lasting_error() {
+		let policy = Policy { attempts:3, delay:0.0, ..Default::default() };
+
+		let mut attempt = 0;
+
+		let result:anyhow::Result<()> = policy.run("test", || {
+			attempt += 1;
+
+			Err(if attempt == 1 { io(io::ErrorKind::TimedOut) } else { status(404, None) })
+		});
+
+		assert_eq!(attempt, 2);
+
+		assert!(format!("{:#}", result.unwrap_err()).starts_with("Gave up after 2 attempts"));
+	}
+}
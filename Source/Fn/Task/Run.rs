@@ -0,0 +1,263 @@
+use std::{
+	panic::{self, AssertUnwindSafe},
+	time::Instant,
+};
+
+use anyhow::{anyhow, Context, Result};
+use rayon::prelude::*;
+
+use crate::Fn::{
+	Forge::Fleet::Fleet,
+	Manifest::Repository,
+	Task::{
+		Audit, Backup, Cancel, Journal::Journal, Limit::Gate, RepoContext, Summary::Entry, Task,
+		TaskOutcome,
+	},
+	Workspace::Workspace,
+};
+
+// Runs `task` on every repository the workspace selects and reports how each one went, under
+// `name`: that of the task itself or of the pipeline step running it. A failing or panicking
+// repository does not stop the others; only a workspace that cannot be loaded fails the whole
+// run. Repositories `journal` has as finished under `name` are not run again.
+pub fn Fn(workspace:&Workspace, name:&str, task:&dyn Task, journal:&Journal) -> Result<Vec<Entry>> {
+	if workspace.dry_run {
+		println!("Task: {} (dry run, nothing is changed)", name);
+	} else {
+		println!("Task: {}", name);
+	}
+
+	let manifest = workspace.manifest()?;
+
+	let fleet = Fleet::new(&manifest)?;
+
+	let repositories = workspace.select(&manifest)?;
+
+	let resumed = repositories
+		.iter()
+		.any(|repository| journal.finished(name, &repository.full_name()).is_some());
+
+	// Preparing again would undo what the finished repositories did, e.g. truncate what they
+	// appended, and a cancelled run prepares nothing. Without its preparation the task cannot run
+	// anywhere.
+	let prepared = if resumed || Cancel::is_cancelled() { Ok(()) } else { task.prepare(workspace) };
+
+	if let Err(error) = prepared {
+		eprintln!("{}: failed, {:#}", name, error);
+
+		let outcome = TaskOutcome::failed(&error);
+
+		return Ok(repositories
+			.iter()
+			.map(|repository| {
+				let entry =
+					Entry::new(name, repository.full_name(), outcome.clone(), Default::default());
+
+				record(journal, &entry);
+
+				entry
+			})
+			.collect());
+	}
+
+	let gate = Gate::new(&manifest.limit);
+
+	let audit =
+		Audit::Audit::open(&workspace.state.join(Audit::FILE), &workspace.run, workspace.dry_run)?;
+
+	let backup = if task.backup() {
+		Backup::Backup::open(&workspace.state, &workspace.run, workspace.dry_run)?
+	} else {
+		Backup::Backup::default()
+	};
+
+	if task.backup() && !workspace.dry_run {
+		println!("Checkouts are backed up as run {}", workspace.run);
+	}
+
+	let retry = manifest.retry.get(task.name()).cloned().unwrap_or_else(|| task.retry());
+
+	let run = |repository:&&Repository| -> Entry {
+		// Once cancelled, repositories in flight finish and the rest do not start
+		if Cancel::is_cancelled() {
+			let entry = Entry::new(
+				name,
+				repository.full_name(),
+				TaskOutcome::Cancelled,
+				Default::default(),
+			);
+
+			record(journal, &entry);
+
+			return entry;
+		}
+
+		if let Some(outcome) = journal.finished(name, &repository.full_name()) {
+			let outcome = TaskOutcome::Skipped(format!("finished earlier as {}", outcome));
+
+			println!("{}: {}", repository.full_name(), outcome);
+
+			return Entry::new(name, repository.full_name(), outcome, Default::default());
+		}
+
+		let mut context =
+			RepoContext::new(workspace, &manifest, &fleet, &gate, &audit, task.name(), repository);
+
+		context.retry = retry.clone();
+
+		let start = Instant::now();
+
+		let outcome = if task.applies(&context) {
+			panic::catch_unwind(AssertUnwindSafe(|| {
+				backup.save(&context).context("Failed to back up before the task")?;
+
+				task.run(&context)
+			}))
+			.unwrap_or_else(|payload| Err(anyhow!("panicked: {}", message(payload.as_ref()))))
+		} else {
+			Ok(TaskOutcome::Skipped("not applicable".to_string()))
+		};
+
+		let outcome = match outcome {
+			Ok(outcome) => {
+				// One block per repository, so parallel repositories do not interleave their plans
+				let mut report = outcome.to_string();
+
+				if workspace.dry_run {
+					for action in context.action() {
+						report += &format!("\n\t{}", action);
+					}
+				}
+
+				context.log(report);
+
+				outcome
+			},
+			Err(error) => {
+				eprintln!("{}: failed, {:#}", repository.full_name(), error);
+
+				TaskOutcome::failed(&error)
+			},
+		};
+
+		let mut entry = Entry::new(name, repository.full_name(), outcome, start.elapsed());
+
+		entry.pushed = context.pushed();
+
+		entry.written = context.written();
+
+		entry.merged = context.merged();
+
+		record(journal, &entry);
+
+		entry
+	};
+
+	if !task.parallel() || workspace.jobs == 1 {
+		return Ok(repositories.iter().map(run).collect());
+	}
+
+	let pool = rayon::ThreadPoolBuilder::new()
+		.num_threads(workspace.jobs)
+		.build()
+		.context("Failed to start the worker threads")?;
+
+	Ok(pool.install(|| repositories.par_iter().map(run).collect()))
+}
+
+// A journal that cannot be written only costs the ability to resume, not the run itself.
+fn record(journal:&Journal, entry:&Entry) {
+	if let Err(error) = journal.record(entry) {
+		eprintln!("{}: {:#}", entry.repository, error);
+	}
+}
+
+fn message(payload:&(dyn std::any::Any+Send)) -> String {
+	if let Some(message) = payload.downcast_ref::<&str>() {
+		message.to_string()
+	} else if let Some(message) = payload.downcast_ref::<String>() {
+		message.clone()
+	} else {
+		"unknown cause".to_string()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::sync::atomic::{AtomicUsize, Ordering};
+
+	use anyhow::Result;
+
+	use crate::Fn::{
+		Task::{
+			Journal::{Journal, FILE},
+			RepoContext, Task, TaskOutcome,
+			Test::Scratch,
+		},
+		Workspace::Workspace,
+	};
+
+	// Counts how often it is prepared and run.
+	#[derive(Default)]
+	struct Count {
+		prepared:AtomicUsize,
+
+		run:AtomicUsize,
+	}
+
+	impl Task for Count {
+		fn name(&self) -> &'static str {
+			"count"
+		}
+
+		fn description(&self) -> &'static str {
+			"Count"
+		}
+
+		fn prepare(&self, _workspace:&Workspace) -> Result<()> {
+			self.prepared.fetch_add(1, Ordering::Relaxed);
+
+			Ok(())
+		}
+
+		fn run(&self, _context:&RepoContext) -> Result<TaskOutcome> {
+			self.run.fetch_add(1, Ordering::Relaxed);
+
+			Ok(TaskOutcome::Changed)
+		}
+	}
+
+	#[test]
+	fn resumes_every_step_of_a_task_on_its_own() {
+		let scratch = Scratch::new("run-resume");
+
+		let path = scratch.workspace.state.join(FILE);
+
+		let task = Count::default();
+
+		let first =
+			super::Fn(&scratch.workspace, "first", &task, &Journal::create(&path, false).unwrap())
+				.unwrap();
+
+		assert_eq!(first[0].task, "first");
+
+		assert_eq!(first[0].outcome, TaskOutcome::Changed);
+
+		let journal = Journal::resume(&path, false).unwrap();
+
+		let again = super::Fn(&scratch.workspace, "first", &task, &journal).unwrap();
+
+		assert!(matches!(again[0].outcome, TaskOutcome::Skipped(_)));
+
+		// The same task under another step name has not run yet, preparation included
+		let second = super::Fn(&scratch.workspace, "second", &task, &journal).unwrap();
+
+		assert_eq!(second[0].task, "second");
+
+		assert_eq!(second[0].outcome, TaskOutcome::Changed);
+
+		assert_eq!(task.prepared.load(Ordering::Relaxed), 2);
+
+		assert_eq!(task.run.load(Ordering::Relaxed), 2);
+	}
+}
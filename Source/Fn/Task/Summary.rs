@@ -0,0 +1,270 @@
+use std::{
+	collections::BTreeMap,
+	fmt, fs,
+	path::{Path, PathBuf},
+	time::Duration,
+};
+
+use anyhow::{Context, Result};
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::Fn::Task::TaskOutcome;
+
+// Summary file inside the workspace state directory.
+pub const FILE:&str = "Summary.json";
+
+// What one invocation did to every repository it touched, task by task.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Summary {
+	// Id of the run, the one its backups and audit log lines carry.
+	pub run:String,
+
+	// Arguments the tool was started with.
+	pub command:String,
+
+	// Steps the run went through, in order.
+	pub pipeline:Vec<String>,
+
+	pub started:String,
+
+	pub finished:String,
+
+	pub dry_run:bool,
+
+	pub entry:Vec<Entry>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Entry {
+	// The pipeline step, which is the task unless `[[pipeline]]` names it otherwise.
+	pub task:String,
+
+	pub repository:String,
+
+	#[serde(flatten)]
+	pub outcome:TaskOutcome,
+
+	// Seconds the task spent on the repository.
+	pub duration:f64,
+
+	// Remote refs the task pushed, by name, and the commit each points to now.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub pushed:BTreeMap<String, String>,
+
+	// Files the task wrote or moved into place, relative to the checkout.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub written:Vec<PathBuf>,
+
+	// Upstream commits the task merged, one `<id> <subject>` line each.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub merged:Vec<String>,
+}
+
+impl Entry {
+	pub fn new(task:&str, repository:String, outcome:TaskOutcome, duration:Duration) -> Self {
+		Self {
+			task:task.to_string(),
+			repository,
+			outcome,
+			duration:duration.as_secs_f64(),
+			pushed:BTreeMap::new(),
+			written:Vec::new(),
+			merged:Vec::new(),
+		}
+	}
+}
+
+impl Summary {
+	pub fn new(run:&str, command:String, dry_run:bool) -> Self {
+		Self { run:run.to_string(), command, started:now(), dry_run, ..Default::default() }
+	}
+
+	pub fn finish(&mut self) {
+		self.finished = now();
+
+		for entry in &self.entry {
+			if !self.pipeline.contains(&entry.task) {
+				self.pipeline.push(entry.task.clone());
+			}
+		}
+	}
+
+	pub fn count(&self, matches:impl Fn(&TaskOutcome) -> bool) -> usize {
+		self.entry.iter().filter(|entry| matches(&entry.outcome)).count()
+	}
+
+	pub fn failed(&self) -> usize {
+		self.count(|outcome| matches!(outcome, TaskOutcome::Failed(_)))
+	}
+
+	pub fn cancelled(&self) -> usize {
+		self.count(|outcome| *outcome == TaskOutcome::Cancelled)
+	}
+
+	pub fn load(path:&Path) -> Result<Self> {
+		let content = fs::read_to_string(path)
+			.with_context(|| format!("Failed to read {}", path.display()))?;
+
+		serde_json::from_str(&content)
+			.with_context(|| format!("Failed to parse {}", path.display()))
+	}
+
+	// Seconds from start to finish, 0 for a summary without times.
+	pub fn duration(&self) -> f64 {
+		let time = |time:&str| chrono::DateTime::parse_from_rfc3339(time).ok();
+
+		match (time(&self.started), time(&self.finished)) {
+			(Some(started), Some(finished)) => {
+				(finished - started).num_milliseconds() as f64 / 1000.0
+			},
+			_ => 0.0,
+		}
+	}
+
+	pub fn save(&self, path:&Path) -> Result<()> {
+		if let Some(parent) = path.parent() {
+			fs::create_dir_all(parent)
+				.with_context(|| format!("Failed to create {}", parent.display()))?;
+		}
+
+		fs::write(path, serde_json::to_string_pretty(self)? + "\n")
+			.with_context(|| format!("Failed to write {}", path.display()))
+	}
+}
+
+impl fmt::Display for Summary {
+	fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.entry.is_empty() {
+			return writeln!(f, "No repositories were processed.");
+		}
+
+		let width = |column:fn(&Entry) -> usize, title:&str| {
+			self.entry.iter().map(column).chain([title.len()]).max().unwrap_or_default()
+		};
+
+		let task = width(|entry| entry.task.len(), "Task");
+
+		let repository = width(|entry| entry.repository.len(), "Repository");
+
+		writeln!(f, "{:<task$}  {:<repository$}  {:>8}  Outcome", "Task", "Repository", "Time")?;
+
+		for entry in &self.entry {
+			// Long error output stays in the JSON, the table keeps one line per repository
+			let outcome = entry.outcome.to_string();
+
+			writeln!(
+				f,
+				"{:<task$}  {:<repository$}  {:>7.1}s  {}",
+				entry.task,
+				entry.repository,
+				entry.duration,
+				outcome.lines().next().unwrap_or_default()
+			)?;
+		}
+
+		writeln!(
+			f,
+			"{} changed, {} unchanged, {} skipped, {} failed, {} cancelled",
+			self.count(|outcome| *outcome == TaskOutcome::Changed),
+			self.count(|outcome| *outcome == TaskOutcome::Unchanged),
+			self.count(|outcome| matches!(outcome, TaskOutcome::Skipped(_))),
+			self.failed(),
+			self.cancelled()
+		)
+	}
+}
+
+fn now() -> String {
+	Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod test {
+	use std::{fs, time::Duration};
+
+	use anyhow::anyhow;
+
+	use super::{Entry, Summary};
+	use crate::Fn::Task::{TaskOutcome, Test::directory};
+
+	fn summary() -> Summary {
+		let mut summary = Summary::new("run", "daily".to_string(), false);
+
+		let error = anyhow!("conflict").context("Failed to merge");
+
+		summary.entry = [
+			("clone", "owner/Editor", TaskOutcome::Changed),
+			("sync", "owner/Editor", TaskOutcome::failed(&error)),
+			("clone", "CodeEditorLand/SideView", TaskOutcome::Skipped("archived".to_string())),
+			("sync", "CodeEditorLand/SideView", TaskOutcome::Cancelled),
+			("build", "owner/Editor", TaskOutcome::Unchanged),
+		]
+		.into_iter()
+		.map(|(task, repository, outcome)| {
+			Entry::new(task, repository.to_string(), outcome, Duration::from_millis(1500))
+		})
+		.collect();
+
+		summary
+	}
+
+	#[test]
+	fn counts_every_outcome_once() {
+		let mut summary = summary();
+
+		summary.finish();
+
+		assert_eq!(summary.pipeline, ["clone", "sync", "build"]);
+
+		assert_eq!((summary.failed(), summary.cancelled()), (1, 1));
+
+		assert_eq!(
+			summary.to_string(),
+			"Task   Repository                   Time  Outcome\n\
+			 clone  owner/Editor                 1.5s  changed\n\
+			 sync   owner/Editor                 1.5s  failed, Failed to merge: conflict\n\
+			 clone  CodeEditorLand/SideView      1.5s  skipped, archived\n\
+			 sync   CodeEditorLand/SideView      1.5s  cancelled\n\
+			 build  owner/Editor                 1.5s  unchanged\n\
+			 1 changed, 1 unchanged, 1 skipped, 1 failed, 1 cancelled\n"
+		);
+
+		assert_eq!(Summary::default().to_string(), "No repositories were processed.\n");
+	}
+
+	#[test]
+	fn keeps_the_outcome_and_its_detail_in_the_json() {
+		let path = directory("summary").join("Summary.json");
+
+		let summary = summary();
+
+		summary.save(&path).unwrap();
+
+		let json:serde_json::Value =
+			serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+
+		assert_eq!(
+			json["entry"][1],
+			serde_json::json!({
+				"task": "sync",
+				"repository": "owner/Editor",
+				"outcome": "failed",
+				"detail": ["Failed to merge", "conflict"],
+				"duration": 1.5,
+			})
+		);
+
+		let loaded = Summary::load(&path).unwrap();
+
+		assert_eq!(
+			loaded.entry.iter().map(|entry| &entry.outcome).collect::<Vec<_>>(),
+			summary.entry.iter().map(|entry| &entry.outcome).collect::<Vec<_>>()
+		);
+
+		assert_eq!(loaded.started, summary.started);
+
+		fs::remove_dir_all(path.parent().unwrap()).unwrap();
+	}
+}
@@ -0,0 +1,278 @@
+use std::{
+	collections::BTreeMap,
+	fs,
+	io::{BufRead, BufReader, Read, Write},
+	net::TcpListener,
+	path::{Path, PathBuf},
+	process::Command,
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc, Mutex,
+	},
+	thread,
+};
+
+use crate::Fn::{
+	Config::Config,
+	Forge::{Endpoint, Fleet::Fleet, Kind},
+	Manifest::{Manifest, Repository},
+	Select::Selector,
+	Task::{Audit, Limit::Gate, RepoContext},
+	Workspace::Workspace,
+};
+
+static COUNT:AtomicUsize = AtomicUsize::new(0);
+
+// An empty directory of its own under the temporary directory, removed by `Scratch`.
+pub fn directory(name:&str) -> PathBuf {
+	let path = std::env::temp_dir().join(format!(
+		"maintain-{}-{}-{}",
+		name,
+		std::process::id(),
+		COUNT.fetch_add(1, Ordering::Relaxed)
+	));
+
+	let _ = fs::remove_dir_all(&path);
+
+	fs::create_dir_all(&path).expect("Cannot create a scratch directory!");
+
+	path
+}
+
+// Runs git in `path` and answers with its output, failing the test when git does.
+pub fn git(path:&Path, argument:&[&str]) -> String {
+	let output = Command::new("git")
+		.args(argument)
+		.current_dir(path)
+		.env("GIT_AUTHOR_NAME", "Test")
+		.env("GIT_AUTHOR_EMAIL", "test@localhost")
+		.env("GIT_COMMITTER_NAME", "Test")
+		.env("GIT_COMMITTER_EMAIL", "test@localhost")
+		.output()
+		.expect("Cannot run git!");
+
+	assert!(
+		output.status.success(),
+		"git {} failed: {}",
+		argument.join(" "),
+		String::from_utf8_lossy(&output.stderr)
+	);
+
+	String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+// A workspace holding one repository, `owner/name`, listed in Build.md and checked out with a
+// first commit.
+pub struct Scratch {
+	pub workspace:Workspace,
+
+	pub manifest:Manifest,
+
+	pub fleet:Fleet,
+
+	pub gate:Gate,
+
+	pub audit:Audit::Audit,
+}
+
+impl Scratch {
+	pub fn new(name:&str) -> Self {
+		let root = directory(name);
+
+		let workspace = Workspace::new(&root, Config::default(), Selector::default());
+
+		let manifest =
+			Manifest { repository:vec![Repository::new("owner", "name")], ..Default::default() };
+
+		let checkout = workspace.path(&manifest.repository[0]);
+
+		fs::create_dir_all(&checkout).expect("Cannot create the checkout!");
+
+		git(&checkout, &["init", "-q", "-b", "main"]);
+
+		fs::write(checkout.join("README.md"), "# name\n").expect("Cannot write README.md!");
+
+		git(&checkout, &["add", "."]);
+
+		git(&checkout, &["commit", "-q", "-m", "First"]);
+
+		fs::create_dir_all(&workspace.cache).expect("Cannot create the cache!");
+
+		fs::write(workspace.cache.join("Build.md"), "owner/name\n")
+			.expect("Cannot write Build.md!");
+
+		let audit = Audit::Audit::open(&workspace.state.join(Audit::FILE), &workspace.run, false)
+			.expect("Cannot open the audit log!");
+
+		Self {
+			fleet:Fleet::new(&manifest).expect("Cannot open the fleet!"),
+			gate:Gate::default(),
+			audit,
+			workspace,
+			manifest,
+		}
+	}
+
+	pub fn context<'a>(&'a self, task:&'a str) -> RepoContext<'a> {
+		RepoContext::new(
+			&self.workspace,
+			&self.manifest,
+			&self.fleet,
+			&self.gate,
+			&self.audit,
+			task,
+			&self.manifest.repository[0],
+		)
+	}
+
+	pub fn checkout(&self) -> PathBuf {
+		self.workspace.path(&self.manifest.repository[0])
+	}
+
+	// A local forge under the workspace holding a bare copy of `owner/name`, made the `github`
+	// forge of the configuration.
+	pub fn forge(&mut self) -> PathBuf {
+		let root = self.workspace.root.join("Forge");
+
+		fs::create_dir_all(root.join("owner")).expect("Cannot create the forge!");
+
+		let checkout = self.checkout().to_string_lossy().into_owned();
+
+		git(&root.join("owner"), &["clone", "-q", "--bare", &checkout, "name.git"]);
+
+		self.workspace.config.forge.insert(
+			"github".to_string(),
+			Endpoint {
+				kind:Kind::Local,
+				url:Some(root.to_string_lossy().into_owned()),
+				owner:vec!["owner".to_string()],
+				..Default::default()
+			},
+		);
+
+		root
+	}
+
+	// Every record of the audit log so far.
+	pub fn audited(&self) -> Vec<Audit::Record> {
+		fs::read_to_string(self.workspace.state.join(Audit::FILE))
+			.unwrap_or_default()
+			.lines()
+			.map(|line| serde_json::from_str(line).expect("Invalid audit record!"))
+			.collect()
+	}
+}
+
+impl Drop for Scratch {
+	fn drop(&mut self) {
+		let _ = fs::remove_dir_all(&self.workspace.root);
+	}
+}
+
+// One request the stand-in received.
+#[derive(Clone, Debug, Default)]
+pub struct Request {
+	pub method:String,
+
+	// Path and query, e.g. `/orgs/owner/repos?per_page=100`.
+	pub path:String,
+
+	// By lowercase name.
+	pub header:BTreeMap<String, String>,
+
+	pub body:String,
+}
+
+// What the stand-in answers: status, headers and body.
+pub type Response = (u16, Vec<(String, String)>, String);
+
+// A local HTTP stand-in for forges and webhooks, answering every request with `answer`.
+pub struct Server {
+	pub url:String,
+
+	received:Arc<Mutex<Vec<Request>>>,
+}
+
+impl Server {
+	pub fn new(answer:impl Fn(&Request) -> Response+Send+'static) -> Self {
+		let listener = TcpListener::bind("127.0.0.1:0").expect("Cannot bind the stand-in!");
+
+		let url = format!("http://{}", listener.local_addr().expect("Cannot get the address!"));
+
+		let received = Arc::new(Mutex::new(Vec::new()));
+
+		let log = received.clone();
+
+		thread::spawn(move || {
+			for stream in listener.incoming() {
+				let Ok(mut stream) = stream else {
+					continue;
+				};
+
+				let mut reader =
+					BufReader::new(stream.try_clone().expect("Cannot clone the stream!"));
+
+				let mut line = String::new();
+
+				if reader.read_line(&mut line).is_err() || line.is_empty() {
+					continue;
+				}
+
+				let mut part = line.split_whitespace();
+
+				let mut request = Request {
+					method:part.next().unwrap_or_default().to_string(),
+					path:part.next().unwrap_or_default().to_string(),
+					..Default::default()
+				};
+
+				loop {
+					let mut line = String::new();
+
+					if reader.read_line(&mut line).unwrap_or(0) == 0 || line.trim().is_empty() {
+						break;
+					}
+
+					if let Some((name, value)) = line.split_once(':') {
+						request.header.insert(name.trim().to_lowercase(), value.trim().to_string());
+					}
+				}
+
+				let length =
+					request.header.get("content-length").and_then(|length| length.parse().ok());
+
+				let mut body = vec![0; length.unwrap_or(0)];
+
+				let _ = reader.read_exact(&mut body);
+
+				request.body = String::from_utf8_lossy(&body).into_owned();
+
+				let (code, header, body) = answer(&request);
+
+				log.lock().expect("Stand-in log poisoned").push(request);
+
+				let mut response = format!(
+					"HTTP/1.1 {} Stand-in\r\nContent-Length: {}\r\nConnection: close\r\n",
+					code,
+					body.len()
+				);
+
+				for (name, value) in header {
+					response.push_str(&format!("{}: {}\r\n", name, value));
+				}
+
+				response.push_str("\r\n");
+
+				response.push_str(&body);
+
+				let _ = stream.write_all(response.as_bytes());
+			}
+		});
+
+		Self { url, received }
+	}
+
+	pub fn received(&self) -> Vec<Request> {
+		self.received.lock().expect("Stand-in log poisoned").clone()
+	}
+}
@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Fn::Task::Limit::Resource;
+
+// Seconds an operation may take before it is killed, under `[timeout]` in the manifest.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct Timeout {
+	// Any command that is not one of the below.
+	pub command:f64,
+
+	pub push:f64,
+
+	// Clones, fetches and pulls.
+	pub fetch:f64,
+
+	// One forge API call, unless `[forge.<name>]` sets its own.
+	pub forge:f64,
+}
+
+impl Default for Timeout {
+	fn default() -> Self {
+		Self { command:600.0, push:600.0, fetch:1800.0, forge:300.0 }
+	}
+}
+
+impl Timeout {
+	pub fn of(&self, resource:Option<Resource>) -> Duration {
+		let seconds = match resource {
+			Some(Resource::Push) => self.push,
+			Some(Resource::Fetch) => self.fetch,
+			Some(Resource::Forge) => self.forge,
+			None => self.command,
+		};
+
+		Duration::from_secs_f64(seconds.max(1.0))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::{
+		io,
+		process::Command,
+		time::{Duration, Instant},
+	};
+
+	use super::Timeout;
+	use crate::Fn::Task::{Limit::Resource, Test::Scratch};
+
+	#[test]
+	fn takes_the_timeout_of_the_resource() {
+		let timeout = Timeout { command:10.0, push:20.0, fetch:30.0, forge:0.2 };
+
+		assert_eq!(timeout.of(None), Duration::from_secs(10));
+
+		assert_eq!(timeout.of(Some(Resource::Push)), Duration::from_secs(20));
+
+		assert_eq!(timeout.of(Some(Resource::Fetch)), Duration::from_secs(30));
+
+		// Less than a second would kill commands before they started
+		assert_eq!(timeout.of(Some(Resource::Forge)), Duration::from_secs(1));
+	}
+
+	#[test]
+	fn kills_a_command_past_its_timeout() {
+		let mut scratch = Scratch::new("timeout");
+
+		scratch.manifest.timeout.command = 1.0;
+
+		let start = Instant::now();
+
+		let error =
+			scratch.context("sort detail").query(Command::new("sleep").arg("10")).unwrap_err();
+
+		assert!(start.elapsed() < Duration::from_secs(5));
+
+		let cause = error.chain().find_map(|cause| cause.downcast_ref::<io::Error>()).unwrap();
+
+		assert_eq!(cause.kind(), io::ErrorKind::TimedOut);
+	}
+}
@@ -0,0 +1,736 @@
+pub mod Audit;
+pub mod Backup;
+pub mod Cancel;
+pub mod Journal;
+pub mod Limit;
+pub mod Registry;
+pub mod Retry;
+pub mod Run;
+pub mod Summary;
+#[cfg(test)]
+pub mod Test;
+pub mod Timeout;
+
+use std::{
+	collections::BTreeMap,
+	fmt, fs,
+	io::{self, Read, Write},
+	path::{Path, PathBuf},
+	process::{Command, Output, Stdio},
+	sync::Mutex,
+	thread,
+	time::{Duration, Instant},
+};
+
+use anyhow::{Context, Error, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::Fn::{
+	Forge::{Fleet::Fleet, Forge},
+	Manifest::{Manifest, Repository},
+	Workspace::Workspace,
+};
+
+// One maintenance step, applied to every selected repository by `Run`.
+pub trait Task: Send+Sync {
+	// Name the registry and the pipeline know the task by, e.g. `move license`.
+	fn name(&self) -> &'static str;
+
+	fn description(&self) -> &'static str;
+
+	// Whether the task has anything to do for this repository at all.
+	fn applies(&self, _context:&RepoContext) -> bool {
+		true
+	}
+
+	fn run(&self, context:&RepoContext) -> Result<TaskOutcome>;
+
+	// Runs once before the first repository, e.g. to truncate a file every repository appends to.
+	fn prepare(&self, _workspace:&Workspace) -> Result<()> {
+		Ok(())
+	}
+
+	// Whether several repositories may run at once.
+	fn parallel(&self) -> bool {
+		false
+	}
+
+	// Whether the task changes checkouts or their remote branches, which are then backed up before
+	// the first such task of a run touches them.
+	fn backup(&self) -> bool {
+		false
+	}
+
+	// How commands and forge calls of the task are retried, unless the manifest says otherwise.
+	fn retry(&self) -> Retry::Policy {
+		Retry::Policy::default()
+	}
+}
+
+// Everything a task knows about the repository it runs on.
+pub struct RepoContext<'a> {
+	pub workspace:&'a Workspace,
+
+	pub manifest:&'a Manifest,
+
+	pub fleet:&'a Fleet,
+
+	pub repository:&'a Repository,
+
+	// Concurrency limits shared with the other repositories of the run.
+	pub gate:&'a Limit::Gate,
+
+	// Every command and change of the run ends up here, whichever repository made it.
+	pub audit:&'a Audit::Audit,
+
+	// Name of the task running, for the audit log.
+	pub task:&'a str,
+
+	// Checkout of the repository inside the workspace, which need not exist yet.
+	pub path:PathBuf,
+
+	// Applied to every command and forge call that changes something.
+	pub retry:Retry::Policy,
+
+	// Everything the task changed, or in a dry run would have changed.
+	action:Mutex<Vec<Action>>,
+
+	execution:Mutex<Vec<Execution>>,
+
+	// Remote refs the task moved and where they point now.
+	pushed:Mutex<BTreeMap<String, String>>,
+
+	merged:Mutex<Vec<String>>,
+}
+
+// A finished subprocess.
+#[derive(Clone, Debug, Default)]
+pub struct Execution {
+	pub command:String,
+
+	// Exit code, `None` when a signal ended the process.
+	pub status:Option<i32>,
+
+	pub stdout:String,
+
+	pub stderr:String,
+
+	pub duration:Duration,
+}
+
+impl Execution {
+	pub fn success(&self) -> bool {
+		self.status == Some(0)
+	}
+
+	fn line(command:&Command) -> String {
+		std::iter::once(command.get_program())
+			.chain(command.get_args())
+			.map(|part| part.to_string_lossy())
+			.collect::<Vec<_>>()
+			.join(" ")
+	}
+}
+
+// A command that did not succeed is an error of its own, so `Retry` can read its stderr.
+impl fmt::Display for Execution {
+	fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{} failed: {}", self.command, self.stderr.trim())
+	}
+}
+
+impl std::error::Error for Execution {}
+
+// One change a task makes to a repository, its checkout or its forge.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+	Command(String),
+
+	Forge(String),
+
+	Write(PathBuf),
+
+	Rename(PathBuf, PathBuf),
+
+	Remove(PathBuf),
+}
+
+impl fmt::Display for Action {
+	fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Command(command) => write!(f, "run {}", command),
+			Self::Forge(operation) => write!(f, "forge {}", operation),
+			Self::Write(path) => write!(f, "write {}", path.display()),
+			Self::Rename(from, to) => write!(f, "move {} to {}", from.display(), to.display()),
+			Self::Remove(path) => write!(f, "remove {}", path.display()),
+		}
+	}
+}
+
+impl<'a> RepoContext<'a> {
+	pub fn new(
+		workspace:&'a Workspace,
+		manifest:&'a Manifest,
+		fleet:&'a Fleet,
+		gate:&'a Limit::Gate,
+		audit:&'a Audit::Audit,
+		task:&'a str,
+		repository:&'a Repository,
+	) -> Self {
+		Self {
+			workspace,
+			manifest,
+			fleet,
+			repository,
+			gate,
+			audit,
+			task,
+			path:workspace.path(repository),
+			retry:Retry::Policy::default(),
+			action:Mutex::new(Vec::new()),
+			execution:Mutex::new(Vec::new()),
+			pushed:Mutex::new(BTreeMap::new()),
+			merged:Mutex::new(Vec::new()),
+		}
+	}
+
+	pub fn is_dry_run(&self) -> bool {
+		self.workspace.dry_run
+	}
+
+	pub fn action(&self) -> Vec<Action> {
+		self.action.lock().expect("Action log poisoned").clone()
+	}
+
+	fn record(&self, action:Action) {
+		self.action.lock().expect("Action log poisoned").push(action);
+	}
+
+	fn audit(&self, event:Audit::Event) -> Result<()> {
+		self.audit.record(&self.repository.full_name(), self.task, event)
+	}
+
+	pub fn pushed(&self) -> BTreeMap<String, String> {
+		self.pushed.lock().expect("Push log poisoned").clone()
+	}
+
+	// Upstream commits the task merged, `<id> <subject>` each, for the run report.
+	pub fn report_merged(&self, commit:impl IntoIterator<Item=String>) {
+		self.merged.lock().expect("Merge log poisoned").extend(commit);
+	}
+
+	pub fn merged(&self) -> Vec<String> {
+		self.merged.lock().expect("Merge log poisoned").clone()
+	}
+
+	// Files the task wrote or moved into place, relative to the checkout when inside it.
+	pub fn written(&self) -> Vec<PathBuf> {
+		let mut written = Vec::new();
+
+		for action in self.action() {
+			let path = match action {
+				Action::Write(path) | Action::Rename(_, path) => path,
+				_ => continue,
+			};
+
+			let path = path.strip_prefix(&self.path).map(Path::to_path_buf).unwrap_or(path);
+
+			if !written.contains(&path) {
+				written.push(path);
+			}
+		}
+
+		written
+	}
+
+	// Every command the task ran, in order.
+	pub fn execution(&self) -> Vec<Execution> {
+		self.execution.lock().expect("Execution log poisoned").clone()
+	}
+
+	// Prints `message` with every line prefixed by the repository, in one piece so repositories
+	// running in parallel do not tear each other's lines apart.
+	pub fn log(&self, message:impl fmt::Display) {
+		let name = self.repository.full_name();
+
+		let message = message.to_string();
+
+		let text:String =
+			message.trim_end().lines().map(|line| format!("{}: {}\n", name, line)).collect();
+
+		print!("{}", text);
+	}
+
+	// Runs a command that only reads, inside the checkout unless it names another directory.
+	// Its exit status is left for the caller to judge; running past its timeout is an error.
+	pub fn query(&self, command:&mut Command) -> Result<Execution> {
+		if command.get_current_dir().is_none() {
+			command.current_dir(&self.path);
+		}
+
+		let line = Execution::line(command);
+
+		let timeout = self.manifest.timeout.of(Limit::Resource::of(command));
+
+		let start = Instant::now();
+
+		let output = output(command, timeout);
+
+		self.audit(Audit::Event::Command {
+			command:line.clone(),
+			status:output.as_ref().ok().and_then(|output| output.status.code()),
+			duration:start.elapsed().as_secs_f64(),
+		})?;
+
+		let output = output.with_context(|| format!("Failed to run {}", line))?;
+
+		let execution = Execution {
+			command:line,
+			status:output.status.code(),
+			stdout:String::from_utf8_lossy(&output.stdout).into_owned(),
+			stderr:String::from_utf8_lossy(&output.stderr).into_owned(),
+			duration:start.elapsed(),
+		};
+
+		self.execution.lock().expect("Execution log poisoned").push(execution.clone());
+
+		Ok(execution)
+	}
+
+	// Runs a command that changes something, like `query`, and fails when it does, after the
+	// retries its failure allows. A dry run records it and answers with an empty success instead.
+	pub fn execute(&self, command:&mut Command) -> Result<Execution> {
+		let line = Execution::line(command);
+
+		self.record(Action::Command(line.clone()));
+
+		if self.is_dry_run() {
+			return Ok(Execution { command:line, status:Some(0), ..Default::default() });
+		}
+
+		let resource = Limit::Resource::of(command);
+
+		// The refs a push is about to move, so the audit log can tell what it moved them from
+		let remote = (resource == Some(Limit::Resource::Push)).then(|| Audit::remote(command));
+
+		let before = remote.as_ref().and_then(|remote| self.refs(remote));
+
+		let result = self.retry.run(&self.repository.full_name(), || {
+			let execution = self.gate.hold(resource, || self.query(command))?;
+
+			if !execution.success() {
+				return Err(execution.into());
+			}
+
+			Ok(execution)
+		});
+
+		if let Some(remote) = remote {
+			let after = self.refs(&remote);
+
+			let (before, after) = Audit::moved(before, after);
+
+			self.pushed
+				.lock()
+				.expect("Push log poisoned")
+				.extend(after.clone().unwrap_or_default());
+
+			self.audit(Audit::Event::Push { command:line, remote, before, after })?;
+		}
+
+		result
+	}
+
+	// Runs a command like `execute` that rewrites the files at `path` in place, and audits the ones
+	// whose content it changed, which it answers with. A dry run answers with none.
+	pub fn execute_writing(&self, command:&mut Command, path:&[PathBuf]) -> Result<Vec<PathBuf>> {
+		if self.is_dry_run() {
+			self.execute(command)?;
+
+			return Ok(Vec::new());
+		}
+
+		let before = path.iter().map(|path| Audit::hash_file(path)).collect::<Result<Vec<_>>>()?;
+
+		self.execute(command)?;
+
+		let mut written = Vec::new();
+
+		for (path, before) in path.iter().zip(before) {
+			let Some(after) = Audit::hash_file(path)? else {
+				continue;
+			};
+
+			if before.as_ref() == Some(&after) {
+				continue;
+			}
+
+			self.record(Action::Write(path.clone()));
+
+			self.audit(Audit::Event::Write { path:path.clone(), before, after })?;
+
+			written.push(path.clone());
+		}
+
+		Ok(written)
+	}
+
+	// Refs of `remote` by name, `None` when they cannot be listed.
+	fn refs(&self, remote:&str) -> Option<BTreeMap<String, String>> {
+		let execution = self.query(Command::new("git").arg("ls-remote").arg(remote)).ok()?;
+
+		if !execution.success() {
+			return None;
+		}
+
+		Some(
+			execution
+				.stdout
+				.lines()
+				.filter_map(|line| line.split_once('\t'))
+				.map(|(id, name)| (name.to_string(), id.to_string()))
+				.collect(),
+		)
+	}
+
+	// Calls the forge for an operation that changes the remote repository, described by
+	// `operation` for the plan. A dry run answers with the default result.
+	pub fn remote<T:Default>(
+		&self,
+		operation:String,
+		call:impl Fn(&dyn Forge) -> Result<T>,
+	) -> Result<T> {
+		let forge = self.forge()?;
+
+		self.record(Action::Forge(operation.clone()));
+
+		if self.is_dry_run() {
+			return Ok(T::default());
+		}
+
+		let result = self.retry.run(&self.repository.full_name(), || {
+			self.gate.hold(Some(Limit::Resource::Forge), || call(forge))
+		});
+
+		self.audit(Audit::Event::Forge { operation, success:result.is_ok() })?;
+
+		result
+	}
+
+	// Calls the forge for an operation that only reads, so a dry run makes it as well.
+	pub fn lookup<T>(&self, call:impl Fn(&dyn Forge) -> Result<T>) -> Result<T> {
+		let forge = self.forge()?;
+
+		self.retry.run(&self.repository.full_name(), || {
+			self.gate.hold(Some(Limit::Resource::Forge), || call(forge))
+		})
+	}
+
+	// Replaces `path` through a temporary file, so it is never left half written.
+	pub fn write(&self, path:&Path, content:impl AsRef<[u8]>) -> Result<()> {
+		self.record(Action::Write(path.to_path_buf()));
+
+		if self.is_dry_run() {
+			return Ok(());
+		}
+
+		self.audit.write(&self.repository.full_name(), self.task, path, content.as_ref())
+	}
+
+	pub fn append(&self, path:&Path, content:impl AsRef<[u8]>) -> Result<()> {
+		self.record(Action::Write(path.to_path_buf()));
+
+		if self.is_dry_run() {
+			return Ok(());
+		}
+
+		let before = Audit::hash_file(path)?;
+
+		fs::OpenOptions::new()
+			.create(true)
+			.append(true)
+			.open(path)
+			.and_then(|mut file| file.write_all(content.as_ref()))
+			.with_context(|| format!("Failed to append to {}", path.display()))?;
+
+		let after = Audit::hash_file(path)?.unwrap_or_default();
+
+		self.audit(Audit::Event::Write { path:path.to_path_buf(), before, after })
+	}
+
+	pub fn rename(&self, from:&Path, to:&Path) -> Result<()> {
+		self.record(Action::Rename(from.to_path_buf(), to.to_path_buf()));
+
+		if self.is_dry_run() {
+			return Ok(());
+		}
+
+		self.audit.rename(&self.repository.full_name(), self.task, from, to)
+	}
+
+	// Removes the file or the whole directory at `path`, if there is one.
+	pub fn remove(&self, path:&Path) -> Result<()> {
+		self.record(Action::Remove(path.to_path_buf()));
+
+		if self.is_dry_run() {
+			return Ok(());
+		}
+
+		self.audit.remove(&self.repository.full_name(), self.task, path)
+	}
+
+	// The forge hosting the repository.
+	pub fn forge(&self) -> Result<&'a dyn Forge> {
+		self.fleet.get(self.repository)
+	}
+
+	pub fn is_cloned(&self) -> bool {
+		self.path.join(".git").exists()
+	}
+}
+
+// Runs `command` in a process group of its own, so Ctrl-C in the terminal only reaches this tool
+// and never stops git halfway through writing the index. Killed once `timeout` has passed.
+fn output(command:&mut Command, timeout:Duration) -> io::Result<Output> {
+	#[cfg(unix)]
+	std::os::unix::process::CommandExt::process_group(command, 0);
+
+	let mut child =
+		command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+
+	let stdout = drain(child.stdout.take());
+
+	let stderr = drain(child.stderr.take());
+
+	let start = Instant::now();
+
+	loop {
+		if let Some(status) = child.try_wait()? {
+			return Ok(Output {
+				status,
+				stdout:stdout.join().unwrap_or_default(),
+				stderr:stderr.join().unwrap_or_default(),
+			});
+		}
+
+		if start.elapsed() >= timeout {
+			// The readers are left behind, a grandchild such as ssh may hold the pipes open
+			child.kill()?;
+
+			child.wait()?;
+
+			return Err(io::Error::new(
+				io::ErrorKind::TimedOut,
+				format!("timed out after {}s", timeout.as_secs()),
+			));
+		}
+
+		thread::sleep(Duration::from_millis(20));
+	}
+}
+
+fn drain(pipe:Option<impl Read+Send+'static>) -> thread::JoinHandle<Vec<u8>> {
+	thread::spawn(move || {
+		let mut buffer = Vec::new();
+
+		if let Some(mut pipe) = pipe {
+			let _ = pipe.read_to_end(&mut buffer);
+		}
+
+		buffer
+	})
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "outcome", content = "detail", rename_all = "lowercase")]
+pub enum TaskOutcome {
+	Changed,
+
+	Unchanged,
+
+	Skipped(String),
+
+	// The error and every cause under it, outermost first.
+	Failed(Vec<String>),
+
+	// Not started because the run was cancelled.
+	Cancelled,
+}
+
+impl TaskOutcome {
+	pub fn failed(error:&Error) -> Self {
+		Self::Failed(error.chain().map(ToString::to_string).collect())
+	}
+}
+
+// Whether the task changed anything.
+impl From<bool> for TaskOutcome {
+	fn from(changed:bool) -> Self {
+		if changed {
+			Self::Changed
+		} else {
+			Self::Unchanged
+		}
+	}
+}
+
+impl fmt::Display for TaskOutcome {
+	fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Changed => write!(f, "changed"),
+			Self::Unchanged => write!(f, "unchanged"),
+			Self::Skipped(reason) => write!(f, "skipped, {}", reason),
+			Self::Failed(chain) => write!(f, "failed, {}", chain.join(": ")),
+			Self::Cancelled => write!(f, "cancelled"),
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::{fs, process::Command};
+
+	use crate::Fn::Task::{
+		Action,
+		Audit::{self, Event},
+		Test::{git, Scratch},
+	};
+
+	#[test]
+	fn audits_files_a_command_rewrites() {
+		let scratch = Scratch::new("execute-writing");
+
+		let sorted = scratch.checkout().join("sorted.json");
+
+		let unsorted = scratch.checkout().join("package.json");
+
+		fs::write(&sorted, "{}\n").unwrap();
+
+		fs::write(&unsorted, "{\"b\":1}\n").unwrap();
+
+		let written = scratch
+			.context("sort detail")
+			.execute_writing(
+				Command::new("sed").args(["-i", "s/b/a/"]).arg(&sorted).arg(&unsorted),
+				&[sorted.clone(), unsorted.clone()],
+			)
+			.unwrap();
+
+		assert_eq!(written, vec![unsorted.clone()]);
+
+		let write:Vec<_> = scratch
+			.audited()
+			.into_iter()
+			.filter_map(|record| match record.event {
+				Event::Write { path, before, after } => Some((path, before.is_some(), after)),
+				_ => None,
+			})
+			.collect();
+
+		assert_eq!(write.len(), 1);
+
+		assert_eq!(write[0].0, unsorted);
+
+		assert!(write[0].1);
+
+		assert_eq!(write[0].2, Audit::hash(b"{\"a\":1}\n").unwrap());
+	}
+
+	#[test]
+	fn records_what_a_dry_run_would_change() {
+		let mut scratch = Scratch::new("dry-run");
+
+		scratch.workspace.dry_run = true;
+
+		let checkout = scratch.checkout();
+
+		let readme = checkout.join("README.md");
+
+		let context = scratch.context("move src");
+
+		context.write(&checkout.join("LICENSE"), "MIT\n").unwrap();
+
+		context.append(&readme, "More\n").unwrap();
+
+		context.rename(&readme, &checkout.join("Readme.md")).unwrap();
+
+		context.remove(&readme).unwrap();
+
+		let commit = context
+			.execute(Command::new("git").args(["commit", "-q", "--allow-empty", "-m", "Dry"]))
+			.unwrap();
+
+		assert!(commit.success());
+
+		// Reading still happens, it is how a task decides what it would do
+		let head = context.query(Command::new("git").args(["rev-parse", "HEAD"])).unwrap();
+
+		assert_eq!(head.stdout.trim(), git(&checkout, &["rev-parse", "HEAD"]));
+
+		assert_eq!(
+			context.action(),
+			[
+				Action::Write(checkout.join("LICENSE")),
+				Action::Write(readme.clone()),
+				Action::Rename(readme.clone(), checkout.join("Readme.md")),
+				Action::Remove(readme.clone()),
+				Action::Command("git commit -q --allow-empty -m Dry".to_string()),
+			]
+		);
+
+		assert!(!checkout.join("LICENSE").exists());
+
+		assert_eq!(fs::read_to_string(&readme).unwrap(), "# name\n");
+
+		assert_eq!(git(&checkout, &["rev-list", "--count", "HEAD"]), "1");
+
+		assert_eq!(git(&checkout, &["status", "--porcelain"]), "");
+
+		// Only what was read is in the audit log
+		let audited:Vec<_> = scratch
+			.audited()
+			.into_iter()
+			.map(|record| match record.event {
+				Event::Command { command, .. } => command,
+				event => format!("{:?}", event),
+			})
+			.collect();
+
+		assert_eq!(audited, ["git rev-parse HEAD"]);
+	}
+
+	#[test]
+	fn runs_commands_inside_the_checkout() {
+		let scratch = Scratch::new("execute-directory");
+
+		let checkout = scratch.checkout();
+
+		let nested = checkout.join("Source");
+
+		fs::create_dir_all(&nested).unwrap();
+
+		let context = scratch.context("sync");
+
+		let directory = |command:&mut Command| {
+			fs::canonicalize(context.query(command).unwrap().stdout.trim()).unwrap()
+		};
+
+		assert_eq!(directory(&mut Command::new("pwd")), fs::canonicalize(&checkout).unwrap());
+
+		// A command that names its own directory keeps it
+		assert_eq!(
+			directory(Command::new("pwd").current_dir(&nested)),
+			fs::canonicalize(&nested).unwrap()
+		);
+
+		context.execute(Command::new("git").args(["checkout", "-q", "-b", "next"])).unwrap();
+
+		assert_eq!(git(&checkout, &["branch", "--show-current"]), "next");
+
+		let execution = context.execution();
+
+		assert_eq!(execution.len(), 3);
+
+		assert_eq!(execution[2].command, "git checkout -q -b next");
+	}
+}
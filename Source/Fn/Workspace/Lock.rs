@@ -0,0 +1,115 @@
+use std::{
+	fmt,
+	fs::{self, File, TryLockError},
+	io::Write,
+	path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+
+// Lock file inside the workspace state directory, held by every run that changes something.
+pub const FILE:&str = "Run.lock";
+
+// Exit status of a run that found the lock held, which `daemon` counts as skipped rather than
+// failed.
+pub const BUSY:i32 = 75;
+
+// Another process holds the lock, kept typed so the exit status can tell it from a failure.
+#[derive(Debug)]
+pub struct Busy {
+	pub path:PathBuf,
+
+	// Process id the holder wrote into the lock file.
+	pub holder:String,
+}
+
+impl fmt::Display for Busy {
+	fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"Another run holds {} (process {}), try again once it finishes",
+			self.path.display(),
+			self.holder
+		)
+	}
+}
+
+impl std::error::Error for Busy {}
+
+// An exclusive lock on a file, released when dropped or when the process ends however it ends.
+pub struct Lock {
+	_file:File,
+}
+
+impl Lock {
+	// Takes the lock at `path`, or `None` while another process holds it.
+	pub fn acquire(path:&Path) -> Result<Option<Self>> {
+		if let Some(parent) = path.parent() {
+			fs::create_dir_all(parent)
+				.with_context(|| format!("Failed to create {}", parent.display()))?;
+		}
+
+		let mut file = fs::OpenOptions::new()
+			.create(true)
+			.truncate(false)
+			.write(true)
+			.open(path)
+			.with_context(|| format!("Failed to open {}", path.display()))?;
+
+		match file.try_lock() {
+			Ok(()) => {},
+			Err(TryLockError::WouldBlock) => return Ok(None),
+			Err(TryLockError::Error(error)) => {
+				return Err(error).with_context(|| format!("Failed to lock {}", path.display()));
+			},
+		}
+
+		// Only for whoever wonders which process holds it
+		file.set_len(0)?;
+
+		writeln!(file, "{}", std::process::id())?;
+
+		Ok(Some(Self { _file:file }))
+	}
+
+	// Like `acquire`, failing while another process holds the lock.
+	pub fn hold(path:&Path) -> Result<Self> {
+		match Self::acquire(path)? {
+			Some(lock) => Ok(lock),
+			None => {
+				let holder = fs::read_to_string(path).unwrap_or_default();
+
+				Err(Busy { path:path.to_path_buf(), holder:holder.trim().to_string() }.into())
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::fs;
+
+	use super::{Busy, Lock};
+	use crate::Fn::Task::Test::directory;
+
+	#[test]
+	fn tells_a_held_lock_apart() {
+		let path = directory("lock").join("Run.lock");
+
+		let held = Lock::hold(&path).unwrap();
+
+		assert!(Lock::acquire(&path).unwrap().is_none());
+
+		let error = Lock::hold(&path).err().unwrap();
+
+		let busy = error.downcast_ref::<Busy>().unwrap();
+
+		assert_eq!(busy.holder, std::process::id().to_string());
+
+		drop(held);
+
+		assert!(Lock::acquire(&path).unwrap().is_some());
+
+		fs::remove_dir_all(path.parent().unwrap()).unwrap();
+	}
+}
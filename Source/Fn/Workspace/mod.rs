@@ -0,0 +1,158 @@
+pub mod Lock;
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::Fn::{
+	Cache::Snapshot,
+	Config::{Config, FILE},
+	Manifest::{Load, Manifest, Repository},
+	Select::Selector,
+};
+
+// Where the fleet is checked out and which of its repositories a command works on.
+#[derive(Clone, Debug, Default)]
+pub struct Workspace {
+	// Every configured path is resolved against this directory.
+	pub root:PathBuf,
+
+	// Repository folders are resolved against this directory.
+	pub checkout:PathBuf,
+
+	// Holds Build.toml, Build.md and the inventory snapshots.
+	pub cache:PathBuf,
+
+	// What the tool keeps about its own runs.
+	pub state:PathBuf,
+
+	pub selector:Selector,
+
+	// Tasks only record what they would change.
+	pub dry_run:bool,
+
+	// Repositories a parallel task works on at once, 0 for one per CPU.
+	pub jobs:usize,
+
+	pub config:Config,
+
+	// Identifies this invocation in the audit log, history and backup refs, e.g.
+	// `20261017-093000-042-4711`. The milliseconds and process id tell apart runs started within
+	// the same second.
+	pub run:String,
+}
+
+impl Workspace {
+	// Paths of `config` are taken relative to `root`.
+	pub fn new(root:&Path, config:Config, selector:Selector) -> Self {
+		Self {
+			root:root.to_path_buf(),
+			checkout:root.join(&config.workspace.checkout),
+			cache:root.join(&config.workspace.cache),
+			state:root.join(&config.workspace.state),
+			selector,
+			dry_run:false,
+			jobs:config.workspace.jobs,
+			config,
+			run:format!(
+				"{}-{}",
+				chrono::Local::now().format("%Y%m%d-%H%M%S-%3f"),
+				std::process::id()
+			),
+		}
+	}
+
+	// The manifest with the fleet settings and `[repository]` sections of the configuration
+	// applied.
+	pub fn manifest(&self) -> Result<Manifest> {
+		let mut manifest = Load::Fn(&self.cache, &self.config.workspace.owner)?;
+
+		self.config.fleet(&mut manifest);
+
+		for repository in &mut manifest.repository {
+			self.config.apply(repository);
+		}
+
+		Ok(manifest)
+	}
+
+	// The repositories of `manifest` the selector picks, using the last snapshot when one exists.
+	pub fn select<'a>(&self, manifest:&'a Manifest) -> Result<Vec<&'a Repository>> {
+		if self.selector.is_empty() {
+			return Ok(manifest.repository.iter().collect());
+		}
+
+		let snapshot = self.cache.join(Snapshot::FILE);
+
+		let snapshot =
+			if snapshot.exists() { Some(Snapshot::Snapshot::load(&snapshot)?) } else { None };
+
+		Ok(self.selector.select(manifest, snapshot.as_ref()))
+	}
+
+	pub fn path(&self, repository:&Repository) -> PathBuf {
+		self.checkout.join(repository.folder())
+	}
+
+	// The nearest directory from `start` upwards that holds a `Maintain.toml`, or a manifest where
+	// the default configuration looks for one.
+	pub fn discover(start:&Path) -> Option<PathBuf> {
+		let manifest = Config::default().workspace.cache.join("Build.toml");
+
+		start
+			.ancestors()
+			.find(|directory| directory.join(FILE).is_file() || directory.join(&manifest).is_file())
+			.map(Path::to_path_buf)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::fs;
+
+	use super::Workspace;
+	use crate::Fn::Task::Test::directory;
+
+	#[test]
+	fn finds_the_nearest_workspace_upwards() {
+		let root = directory("discover");
+
+		let nested = root.join("Application").join("Editor").join("Source");
+
+		fs::create_dir_all(&nested).unwrap();
+
+		fs::write(root.join("Maintain.toml"), "").unwrap();
+
+		assert_eq!(Workspace::discover(&nested), Some(root.clone()));
+
+		// A nearer one wins
+		fs::write(root.join("Application").join("Maintain.toml"), "").unwrap();
+
+		assert_eq!(Workspace::discover(&nested), Some(root.join("Application")));
+
+		// A directory by that name is no configuration
+		fs::create_dir_all(nested.join("Maintain.toml")).unwrap();
+
+		assert_eq!(Workspace::discover(&nested), Some(root.join("Application")));
+
+		fs::remove_dir_all(&root).unwrap();
+	}
+
+	#[test]
+	fn takes_a_manifest_where_the_defaults_look_for_one() {
+		let root = directory("discover-manifest");
+
+		let cache = root.join("Cache").join("Repository");
+
+		fs::create_dir_all(&cache).unwrap();
+
+		fs::write(cache.join("Build.toml"), "").unwrap();
+
+		// From inside the cache directory as well
+		assert_eq!(Workspace::discover(&cache), Some(root.clone()));
+
+		assert_eq!(Workspace::discover(&root), Some(root.clone()));
+
+		fs::remove_dir_all(&root).unwrap();
+	}
+}
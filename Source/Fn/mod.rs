@@ -1 +1,24 @@
+pub mod Action;
+pub mod Append;
 pub mod Cache;
+pub mod Clean;
+pub mod Clone;
+pub mod Config;
+pub mod Configure;
+pub mod Cron;
+pub mod Forge;
+pub mod Fork;
+pub mod History;
+pub mod Manifest;
+pub mod Module;
+pub mod Move;
+pub mod Rename;
+pub mod Replace;
+pub mod Report;
+pub mod Rollback;
+pub mod Select;
+pub mod Setting;
+pub mod Sort;
+pub mod Sync;
+pub mod Task;
+pub mod Workspace;